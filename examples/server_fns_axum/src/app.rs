@@ -1,18 +1,18 @@
 use futures::StreamExt;
-use http::Method;
+use http::{Method, StatusCode};
 use leptos::{html::Input, prelude::*, task::spawn_local};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use server_fn::{
-    client::{browser::BrowserClient, Client},
+    client::{browser::BrowserClient, Intercept, Interceptor, Layered},
     codec::{
         Encoding, FromReq, FromRes, GetUrl, IntoReq, IntoRes, MultipartData,
         MultipartFormData, Postcard, Rkyv, SerdeLite, StreamingText,
         TextStream,
     },
+    error::ServerFnErrorStatus,
     request::{browser::BrowserRequest, ClientReq, Req},
     response::{browser::BrowserResponse, ClientRes, Res},
 };
-use std::future::Future;
 #[cfg(feature = "ssr")]
 use std::sync::{
     atomic::{AtomicU8, Ordering},
@@ -651,6 +651,9 @@ pub fn FileWatcher() -> impl IntoView {
 /// `Serialize` and `Deserialize`, although these can be used to generate the `FromStr`/`Display`
 /// implementations if you'd like. However, it's much lighter weight to use something like `strum`
 /// simply to generate those trait implementations.
+///
+/// It also implements `ServerFnErrorStatus`, which chooses the HTTP status of the response, so
+/// that these errors are sent as `400 Bad Request` rather than `500 Internal Server Error`.
 #[server]
 pub async fn ascii_uppercase(
     text: String,
@@ -674,6 +677,12 @@ pub enum InvalidArgument {
     NotAscii,
 }
 
+impl ServerFnErrorStatus for InvalidArgument {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::BAD_REQUEST
+    }
+}
+
 #[component]
 pub fn CustomErrorTypes() -> impl IntoView {
     let input_ref = NodeRef::<Input>::new();
@@ -827,37 +836,31 @@ pub fn CustomEncoding() -> impl IntoView {
 /// Middleware lets you modify the request/response on the server.
 ///
 /// On the client, you might also want to modify the request. For example, you may need to add a
-/// custom header for authentication on every request. You can do this by creating a "custom
-/// client."
+/// custom header for authentication on every request. You can do this with an "interceptor,"
+/// which can either be added to every request with `add_client_middleware`, or to a single
+/// server function by wrapping its client in `Layered`.
 #[component]
 pub fn CustomClientExample() -> impl IntoView {
-    // Define a type for our client.
-    pub struct CustomClient;
-
-    // Implement the `Client` trait for it.
-    impl<CustErr> Client<CustErr> for CustomClient {
-        // BrowserRequest and BrowserResponse are the defaults used by other server functions.
-        // They are wrappers for the underlying Web Fetch API types.
-        type Request = BrowserRequest;
-        type Response = BrowserResponse;
-
-        // Our custom `send()` implementation does all the work.
-        fn send(
-            req: Self::Request,
-        ) -> impl Future<Output = Result<Self::Response, ServerFnError<CustErr>>>
-               + Send {
+    // Define a type for our interceptor.
+    #[derive(Default)]
+    pub struct CustomHeader;
+
+    // BrowserRequest and BrowserResponse are the types used by the default browser client.
+    // They are wrappers for the underlying Web Fetch API types.
+    impl Interceptor<BrowserRequest, BrowserResponse> for CustomHeader {
+        fn on_request(
+            &self,
+            req: &mut BrowserRequest,
+        ) -> Result<(), ServerFnError> {
             // BrowserRequest derefs to the underlying Request type from gloo-net,
             // so we can get access to the headers here
-            let headers = req.headers();
-            // modify the headers by appending one
-            headers.append("X-Custom-Header", "foobar");
-            // delegate back out to BrowserClient to send the modified request
-            BrowserClient::send(req)
+            req.headers().append("X-Custom-Header", "foobar");
+            Ok(())
         }
     }
 
-    // Specify our custom client with `client = `
-    #[server(client = CustomClient)]
+    // Specify a client that sends requests through our interceptor with `client = `
+    #[server(client = Layered<BrowserClient, Intercept<CustomHeader>>)]
     pub async fn fn_with_custom_client() -> Result<(), ServerFnError> {
         use http::header::HeaderMap;
         use leptos_axum::extract;
@@ -340,90 +340,143 @@ pub fn handle_server_fns_with_context(
 ) -> Route {
     web::to(move |req: HttpRequest, payload: Payload| {
         let additional_context = additional_context.clone();
-        async move {
-            let additional_context = additional_context.clone();
+        handle_server_fn_request(additional_context, req, payload)
+    })
+}
 
-            let path = req.path();
-            let method = req.method();
-            if let Some(mut service) =
-                server_fn::actix::get_server_fn_service(path, method)
-            {
-                let owner = Owner::new();
-                owner
-                    .with(|| {
-                        ScopedFuture::new(async move {
-                            additional_context();
-                            provide_context(Request::new(&req));
-                            let res_options = ResponseOptions::default();
-                            provide_context(res_options.clone());
-
-                            // store Accepts and Referer in case we need them for redirect (below)
-                            let accepts_html = req
-                                .headers()
-                                .get(ACCEPT)
-                                .and_then(|v| v.to_str().ok())
-                                .map(|v| v.contains("text/html"))
-                                .unwrap_or(false);
-                            let referrer = req.headers().get(REFERER).cloned();
-
-                            // actually run the server fn
-                            let mut res = ActixResponse(
-                                service
-                                    .0
-                                    .run(ActixRequest::from((req, payload)))
-                                    .await
-                                    .take(),
-                            );
-
-                            // if it accepts text/html (i.e., is a plain form post) and doesn't already have a
-                            // Location set, then redirect to the Referer
-                            if accepts_html {
-                                if let Some(referrer) = referrer {
-                                    let has_location =
-                                        res.0.headers().get(LOCATION).is_some();
-                                    if !has_location {
-                                        *res.0.status_mut() = StatusCode::FOUND;
-                                        res.0
-                                            .headers_mut()
-                                            .insert(LOCATION, referrer);
-                                    }
-                                }
+async fn handle_server_fn_request(
+    additional_context: impl Fn() + 'static + Clone + Send,
+    req: HttpRequest,
+    payload: Payload,
+) -> HttpResponse {
+    let path = req.path();
+    let method = req.method();
+    if let Some(mut service) =
+        server_fn::actix::get_server_fn_service(path, method)
+    {
+        let owner = Owner::new();
+        owner
+            .with(|| {
+                ScopedFuture::new(async move {
+                    additional_context();
+                    provide_context(Request::new(&req));
+                    let res_options = ResponseOptions::default();
+                    #[cfg(feature = "cookies")]
+                    provide_cookies(&req, &res_options);
+                    provide_context(res_options.clone());
+
+                    // store Accepts and Referer in case we need them for redirect (below)
+                    let accepts_html = req
+                        .headers()
+                        .get(ACCEPT)
+                        .and_then(|v| v.to_str().ok())
+                        .map(|v| v.contains("text/html"))
+                        .unwrap_or(false);
+                    let referrer = req.headers().get(REFERER).cloned();
+
+                    // actually run the server fn
+                    let mut res = ActixResponse(
+                        service
+                            .0
+                            .run(ActixRequest::from((req, payload)))
+                            .await
+                            .take(),
+                    );
+
+                    // if it accepts text/html (i.e., is a plain form post) and doesn't already have a
+                    // Location set, then redirect to the Referer
+                    if accepts_html {
+                        if let Some(referrer) = referrer {
+                            let has_location =
+                                res.0.headers().get(LOCATION).is_some();
+                            if !has_location {
+                                *res.0.status_mut() = StatusCode::FOUND;
+                                res.0.headers_mut().insert(LOCATION, referrer);
                             }
+                        }
+                    }
 
-                            // the Location header may have been set to Referer, so any redirection by the
-                            // user must overwrite it
-                            {
-                                let mut res_options = res_options.0.write();
-                                let headers = res.0.headers_mut();
+                    // the Location header may have been set to Referer, so any redirection by the
+                    // user must overwrite it
+                    {
+                        let mut res_options = res_options.0.write();
+                        let headers = res.0.headers_mut();
 
-                                for location in
-                                    res_options.headers.remove(header::LOCATION)
-                                {
-                                    headers.insert(header::LOCATION, location);
-                                }
-                            }
+                        for location in
+                            res_options.headers.remove(header::LOCATION)
+                        {
+                            headers.insert(header::LOCATION, location);
+                        }
+                    }
 
-                            // apply status code and headers if user changed them
-                            res.extend_response(&res_options);
-                            res.0
-                        })
-                    })
-                    .await
-            } else {
-                HttpResponse::BadRequest().body(format!(
-                    "Could not find a server function at the route {:?}. \
-                     \n\nIt's likely that either
+                    // apply status code and headers if user changed them
+                    res.extend_response(&res_options);
+                    res.0
+                })
+            })
+            .await
+    } else if let Some(err) = server_fn::version::unserved_version(
+        path,
+        server_fn::actix::server_fn_paths().map(|(path, _)| path),
+    )
+    .or_else(|| {
+        server_fn::version::client_outdated(
+            req.headers()
+                .get(server_fn::version::BUILD_VERSION_HEADER)
+                .and_then(|h| h.to_str().ok()),
+        )
+    }) {
+        <server_fn::response::actix::ActixResponse as server_fn::response::Res<
+            server_fn::error::NoCustomError,
+        >>::error_response(path, &err)
+        .take()
+    } else {
+        HttpResponse::BadRequest().body(format!(
+            "Could not find a server function at the route {:?}. \
+             \n\nIt's likely that either
                          1. The API prefix you specify in the `#[server]` \
-                     macro doesn't match the prefix at which your server \
-                     function handler is mounted, or \n2. You are on a \
-                     platform that doesn't support automatic server function \
-                     registration and you need to call \
-                     ServerFn::register_explicit() on the server function \
-                     type, somewhere in your `main` function.",
-                    req.path()
-                ))
-            }
-        }
+             macro doesn't match the prefix at which your server \
+             function handler is mounted, or \n2. You are on a \
+             platform that doesn't support automatic server function \
+             registration and you need to call \
+             ServerFn::register_explicit() on the server function \
+             type, somewhere in your `main` function.",
+            req.path()
+        ))
+    }
+}
+
+/// An Actix [struct@Route](actix_web::Route) that responds to a batch of server function calls
+/// sent by [`BatchClient`](server_fn::batch::BatchClient), running each one as
+/// [`handle_server_fns`] would.
+///
+/// [`LeptosRoutes`] registers this at the [`batch_path`](server_fn::batch::batch_path) of each
+/// server function automatically.
+#[cfg(feature = "batch")]
+pub fn handle_server_fn_batch() -> Route {
+    handle_server_fn_batch_with_context(|| {})
+}
+
+/// An Actix [struct@Route](actix_web::Route) that responds to a batch of server function calls
+/// sent by [`BatchClient`](server_fn::batch::BatchClient), running each one as
+/// [`handle_server_fns_with_context`] would, with the same additional context.
+#[cfg(feature = "batch")]
+pub fn handle_server_fn_batch_with_context(
+    additional_context: impl Fn() + 'static + Clone + Send,
+) -> Route {
+    web::post().to(move |req: HttpRequest, payload: Payload| {
+        let additional_context = additional_context.clone();
+        server_fn::actix::handle_batch_with(
+            req,
+            payload,
+            move |req, payload| {
+                handle_server_fn_request(
+                    additional_context.clone(),
+                    req,
+                    payload,
+                )
+            },
+        )
     })
 }
 
@@ -758,6 +811,18 @@ fn provide_contexts(
 ) {
     let path = leptos_corrected_path(&req);
 
+    #[cfg(feature = "csrf")]
+    if let Some(cookie) = leptos::csrf::provide_csrf_token(
+        req.headers()
+            .get(header::COOKIE)
+            .and_then(|h| h.to_str().ok()),
+    ) {
+        if let Ok(cookie) = HeaderValue::from_str(&cookie) {
+            res_options.append_header(header::SET_COOKIE, cookie);
+        }
+    }
+    #[cfg(feature = "cookies")]
+    provide_cookies(&req, res_options);
     provide_context(RequestUrl::new(&path));
     provide_context(meta_context.clone());
     provide_context(res_options.clone());
@@ -766,6 +831,22 @@ fn provide_contexts(
     leptos::nonce::provide_nonce();
 }
 
+/// Provides the request's cookies as [`Cookies`](leptos::cookies::Cookies), adding a
+/// `Set-Cookie` header to the response options for each change.
+#[cfg(feature = "cookies")]
+fn provide_cookies(req: &HttpRequest, res_options: &ResponseOptions) {
+    let cookies = req
+        .headers()
+        .get_all(header::COOKIE)
+        .filter_map(|h| h.to_str().ok());
+    let res_options = res_options.clone();
+    leptos::cookies::provide_cookies(cookies, move |cookie| {
+        if let Ok(cookie) = HeaderValue::from_str(&cookie) {
+            res_options.append_header(header::SET_COOKIE, cookie);
+        }
+    });
+}
+
 fn leptos_corrected_path(req: &HttpRequest) -> String {
     let path = req.path();
     let query = req.query_string();
@@ -1391,6 +1472,19 @@ where
                 router = router.route(path, handler);
             }
         }
+        #[cfg(feature = "batch")]
+        for path in server_fn::batch::batch_paths(
+            server_fn::actix::server_fn_paths().map(|(path, _)| path),
+        ) {
+            if !excluded.contains(path.as_str()) {
+                router = router.route(
+                    &path,
+                    handle_server_fn_batch_with_context(
+                        additional_context.clone(),
+                    ),
+                );
+            }
+        }
 
         // register routes defined in Leptos's Router
         for listing in paths.iter().filter(|p| !p.exclude) {
@@ -1504,6 +1598,19 @@ impl LeptosRoutes for &mut ServiceConfig {
                 router = router.route(path, handler);
             }
         }
+        #[cfg(feature = "batch")]
+        for path in server_fn::batch::batch_paths(
+            server_fn::actix::server_fn_paths().map(|(path, _)| path),
+        ) {
+            if !excluded.contains(path.as_str()) {
+                router = router.route(
+                    &path,
+                    handle_server_fn_batch_with_context(
+                        additional_context.clone(),
+                    ),
+                );
+            }
+        }
 
         // register routes defined in Leptos's Router
         for listing in paths.iter().filter(|p| !p.exclude) {
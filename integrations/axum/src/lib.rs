@@ -317,6 +317,36 @@ pub async fn handle_server_fns(req: Request<Body>) -> impl IntoResponse {
     handle_server_fns_inner(|| {}, req).await
 }
 
+/// An Axum handler that responds to a batch of server function calls sent by
+/// [`BatchClient`](server_fn::batch::BatchClient), running each one as
+/// [`handle_server_fns`] would.
+///
+/// [`LeptosRoutes`] registers this at the [`batch_path`](server_fn::batch::batch_path) of each
+/// server function automatically.
+#[cfg(feature = "batch")]
+pub async fn handle_server_fn_batch(req: Request<Body>) -> impl IntoResponse {
+    handle_server_fn_batch_with_context(|| {}, req).await
+}
+
+/// An Axum handler that responds to a batch of server function calls sent by
+/// [`BatchClient`](server_fn::batch::BatchClient), running each one as
+/// [`handle_server_fns_with_context`] would, with the same additional context.
+#[cfg(feature = "batch")]
+pub async fn handle_server_fn_batch_with_context(
+    additional_context: impl Fn() + 'static + Clone + Send,
+    req: Request<Body>,
+) -> impl IntoResponse {
+    server_fn::axum::handle_batch_with(req, move |req| {
+        let additional_context = additional_context.clone();
+        async move {
+            handle_server_fns_inner(additional_context, req)
+                .await
+                .into_response()
+        }
+    })
+    .await
+}
+
 fn init_executor() {
     #[cfg(feature = "wasm")]
     let _ = any_spawner::Executor::init_wasm_bindgen();
@@ -383,8 +413,10 @@ async fn handle_server_fns_inner(
             .with(|| {
                 ScopedFuture::new(async move {
                     additional_context();
-                    provide_context(parts);
                     let res_options = ResponseOptions::default();
+                    #[cfg(feature = "cookies")]
+                    provide_cookies(&parts, &res_options);
+                    provide_context(parts);
                     provide_context(res_options.clone());
 
                     // store Accepts and Referer in case we need them for redirect (below)
@@ -418,6 +450,21 @@ async fn handle_server_fns_inner(
                 })
             })
             .await
+    } else if let Some(err) = server_fn::version::unserved_version(
+        &path,
+        server_fn::axum::server_fn_paths().map(|(path, _)| path),
+    )
+    .or_else(|| {
+        server_fn::version::client_outdated(
+            parts
+                .headers
+                .get(server_fn::version::BUILD_VERSION_HEADER)
+                .and_then(|h| h.to_str().ok()),
+        )
+    }) {
+        Ok(<Response<Body> as server_fn::response::Res<
+            server_fn::error::NoCustomError,
+        >>::error_response(&path, &err))
     } else {
         Response::builder()
             .status(StatusCode::BAD_REQUEST)
@@ -925,6 +972,19 @@ fn provide_contexts(
     parts: Parts,
     default_res_options: ResponseOptions,
 ) {
+    #[cfg(feature = "csrf")]
+    if let Some(cookie) = leptos::csrf::provide_csrf_token(
+        parts
+            .headers
+            .get(header::COOKIE)
+            .and_then(|h| h.to_str().ok()),
+    ) {
+        if let Ok(cookie) = HeaderValue::from_str(&cookie) {
+            default_res_options.append_header(header::SET_COOKIE, cookie);
+        }
+    }
+    #[cfg(feature = "cookies")]
+    provide_cookies(&parts, &default_res_options);
     provide_context(RequestUrl::new(path));
     provide_context(meta_context.clone());
     provide_context(parts);
@@ -933,6 +993,23 @@ fn provide_contexts(
     leptos::nonce::provide_nonce();
 }
 
+/// Provides the request's cookies as [`Cookies`](leptos::cookies::Cookies), adding a
+/// `Set-Cookie` header to the response options for each change.
+#[cfg(feature = "cookies")]
+fn provide_cookies(parts: &Parts, res_options: &ResponseOptions) {
+    let cookies = parts
+        .headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|h| h.to_str().ok());
+    let res_options = res_options.clone();
+    leptos::cookies::provide_cookies(cookies, move |cookie| {
+        if let Ok(cookie) = HeaderValue::from_str(&cookie) {
+            res_options.append_header(header::SET_COOKIE, cookie);
+        }
+    });
+}
+
 /// Returns an Axum [Handler](axum::handler::Handler) that listens for a `GET` request and tries
 /// to route it using [leptos_router], asynchronously rendering an HTML page after all
 /// `async` resources have loaded.
@@ -1795,6 +1872,23 @@ where
             }
         }
 
+        // register the endpoint for batched server function calls
+        #[cfg(feature = "batch")]
+        for path in server_fn::batch::batch_paths(
+            server_fn::axum::server_fn_paths().map(|(path, _)| path),
+        ) {
+            if !excluded.contains(path.as_str()) {
+                let cx_with_state = cx_with_state.clone();
+                router = router.route(
+                    &path,
+                    post(move |req: Request<Body>| async move {
+                        handle_server_fn_batch_with_context(cx_with_state, req)
+                            .await
+                    }),
+                );
+            }
+        }
+
         // register router paths
         for listing in paths.iter().filter(|p| !p.exclude) {
             let path = listing.path();
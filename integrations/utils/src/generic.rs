@@ -0,0 +1,888 @@
+//! A server integration built on [`http::Request`] and [`http::Response`], which does not
+//! depend on any particular server framework.
+//!
+//! Requests are taken as `Request<Bytes>`, with their body already read into memory, and
+//! responses are returned as `Response<Body>`, whose body may be streamed. Requests to server
+//! functions should be read with [`read_server_fn_body`], which enforces their body limits as
+//! the body is read. An adapter for a new
+//! framework only has to convert its own request and response types to and from these, and
+//! register the handlers in this module with its router:
+//!
+//! - [`handle_server_fns`] runs server functions, which should be mounted at the API prefix
+//!   (`/api/*fn_name` by default).
+//! - [`generate_route_list`] lists the routes of the app, each with the handler from
+//!   [`render_app`] for its [`SsrMode`].
+//! - [`file_and_error_handler`] serves static files from the site root, and renders the app
+//!   with a `404 Not Found` status for any other path.
+//!
+//! For `#[server]` functions to use these request and response types, enable the `generic`
+//! feature of this crate (which enables it on `server_fn` and `leptos_macro`), and don't enable
+//! the `axum` or `actix` features of those crates. As with the other integrations, an
+//! [`Executor`](leptos::task::Executor) must be initialized before any of these handlers
+//! are used.
+//!
+//! ```rust,ignore
+//! // a plain `hyper` service
+//! let routes = generate_route_list(App);
+//! let handle = move |req: Request<Incoming>| {
+//!     let routes = routes.clone();
+//!     async move {
+//!         let (parts, body) = req.into_parts();
+//!         let res = if parts.uri.path().starts_with("/api/") {
+//!             let req = Request::from_parts(parts, BodyDataStream::new(body));
+//!             match read_server_fn_body(req).await {
+//!                 Ok(req) => handle_server_fns(req).await,
+//!                 Err(res) => res,
+//!             }
+//!         } else {
+//!             let req = Request::from_parts(parts, body.collect().await?.to_bytes());
+//!             match routes.iter().find(|r| r.matches(req.uri().path())) {
+//!                 Some(route) => render_app(route.mode().clone(), || {}, App)(req).await,
+//!                 None => file_and_error_handler(options.clone(), shell)(req).await,
+//!             }
+//!         };
+//!         Ok::<_, hyper::Error>(res.map(into_hyper_body))
+//!     }
+//! };
+//! ```
+
+use crate::{BoxedFnOnce, ExtendResponse, PinnedFuture, PinnedStream};
+use bytes::Bytes;
+use futures::{stream::once, Stream, StreamExt};
+use http::{
+    header::{
+        self, HeaderName, HeaderValue, ACCEPT, ACCEPT_ENCODING, LOCATION,
+        REFERER,
+    },
+    request::Parts,
+    HeaderMap, Method, Request, Response, StatusCode,
+};
+use hydration_context::SsrSharedContext;
+use leptos::{
+    config::LeptosOptions,
+    context::{provide_context, use_context},
+    reactive::{computed::ScopedFuture, owner::Owner},
+    IntoView,
+};
+use leptos_meta::ServerMetaContext;
+use leptos_router::{
+    components::provide_server_redirect, location::RequestUrl, ExpandOptionals,
+    PathSegment, RouteList, SsrMode,
+};
+use parking_lot::RwLock;
+pub use server_fn::response::generic::Body;
+use server_fn::{
+    error::NoCustomError, redirect::REDIRECT_HEADER, request::get_body_limits,
+    response::Res, ServerFnError,
+};
+use std::{fmt::Display, path::Path, sync::Arc};
+
+/// This struct lets you define headers and override the status of the Response from an Element or a Server Function
+/// Typically contained inside of a ResponseOptions. Setting this is useful for cookies and custom responses.
+#[derive(Debug, Clone, Default)]
+pub struct ResponseParts {
+    /// If provided, this will overwrite any other status code for this response.
+    pub status: Option<StatusCode>,
+    /// The map of headers that should be added to the response.
+    pub headers: HeaderMap,
+}
+
+impl ResponseParts {
+    /// Insert a header, overwriting any previous value with the same key
+    pub fn insert_header(&mut self, key: HeaderName, value: HeaderValue) {
+        self.headers.insert(key, value);
+    }
+    /// Append a header, leaving any header with the same key intact
+    pub fn append_header(&mut self, key: HeaderName, value: HeaderValue) {
+        self.headers.append(key, value);
+    }
+}
+
+/// Allows you to override details of the HTTP response like the status code and add Headers/Cookies.
+///
+/// `ResponseOptions` is provided via context by [`handle_server_fns`], [`render_app`] and the other
+/// handlers in this module.
+#[derive(Debug, Clone, Default)]
+pub struct ResponseOptions(pub Arc<RwLock<ResponseParts>>);
+
+impl ResponseOptions {
+    /// A simpler way to overwrite the contents of `ResponseOptions` with a new `ResponseParts`.
+    pub fn overwrite(&self, parts: ResponseParts) {
+        *self.0.write() = parts
+    }
+    /// Set the status of the returned Response.
+    pub fn set_status(&self, status: StatusCode) {
+        self.0.write().status = Some(status);
+    }
+    /// Insert a header, overwriting any previous value with the same key.
+    pub fn insert_header(&self, key: HeaderName, value: HeaderValue) {
+        self.0.write().headers.insert(key, value);
+    }
+    /// Append a header, leaving any header with the same key intact.
+    pub fn append_header(&self, key: HeaderName, value: HeaderValue) {
+        self.0.write().headers.append(key, value);
+    }
+}
+
+struct GenericResponse(Response<Body>);
+
+impl ExtendResponse for GenericResponse {
+    type ResponseOptions = ResponseOptions;
+
+    fn from_stream(
+        stream: impl Stream<Item = String> + Send + 'static,
+    ) -> Self {
+        GenericResponse(Response::new(Body::Async(Box::pin(
+            stream.map(|chunk| Ok(Bytes::from(chunk))),
+        ))))
+    }
+
+    fn extend_response(&mut self, res_options: &Self::ResponseOptions) {
+        let mut res_options = res_options.0.write();
+        if let Some(status) = res_options.status {
+            *self.0.status_mut() = status;
+        }
+        self.0
+            .headers_mut()
+            .extend(std::mem::take(&mut res_options.headers));
+    }
+
+    fn set_default_content_type(&mut self, content_type: &str) {
+        let headers = self.0.headers_mut();
+        if !headers.contains_key(header::CONTENT_TYPE) {
+            headers.insert(
+                header::CONTENT_TYPE,
+                HeaderValue::from_str(content_type).unwrap(),
+            );
+        }
+    }
+}
+
+/// Redirects the user from within a server function or while rendering.
+///
+/// This sets the `Location` header. Plain navigations and form submissions (whose `Accept`
+/// header contains `text/html`) also get a `302 Found` status, while calls from the server
+/// function client get a header that tells the client to redirect once it has the result.
+pub fn redirect(path: &str) {
+    let (Some(req), Some(res)) =
+        (use_context::<Parts>(), use_context::<ResponseOptions>())
+    else {
+        eprintln!(
+            "Couldn't retrieve either Parts or ResponseOptions while trying \
+             to redirect()."
+        );
+        return;
+    };
+    res.insert_header(
+        LOCATION,
+        HeaderValue::from_str(path).expect("Failed to create HeaderValue"),
+    );
+    if accepts_html(&req.headers) {
+        res.set_status(StatusCode::FOUND);
+    } else {
+        res.insert_header(
+            HeaderName::from_static(REDIRECT_HEADER),
+            HeaderValue::from_static(""),
+        );
+    }
+}
+
+fn accepts_html(headers: &HeaderMap) -> bool {
+    headers
+        .get(ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.contains("text/html"))
+        .unwrap_or(false)
+}
+
+/// Runs the server function that the request is for, and returns its response.
+///
+/// ## Provided Context Types
+/// This function always provides context values including the following types:
+/// - [`Parts`]
+/// - [`ResponseOptions`]
+pub async fn handle_server_fns(req: Request<Bytes>) -> Response<Body> {
+    handle_server_fns_with_context(|| {}, req).await
+}
+
+/// Runs the server function that the request is for, with additional context, and returns its
+/// response.
+///
+/// The same context should be provided when rendering the app, as server functions may also be
+/// called during server rendering.
+pub async fn handle_server_fns_with_context(
+    additional_context: impl Fn() + 'static + Clone + Send,
+    req: Request<Bytes>,
+) -> Response<Body> {
+    let method = req.method().clone();
+    let path = req.uri().path().to_string();
+    let Some(mut service) =
+        server_fn::generic::get_server_fn_service(&path, method)
+    else {
+        // responds with the same errors as when no server function is found
+        return server_fn::generic::handle_server_fn(req).await;
+    };
+
+    let (parts, body) = req.into_parts();
+    let req = Request::from_parts(parts.clone(), body);
+    let owner = Owner::new();
+    owner
+        .with(|| {
+            ScopedFuture::new(async move {
+                additional_context();
+                let res_options = ResponseOptions::default();
+                #[cfg(feature = "cookies")]
+                provide_cookies(&parts, &res_options);
+                provide_context(res_options.clone());
+                let accepts_html = accepts_html(&parts.headers);
+                let referrer = parts.headers.get(REFERER).cloned();
+                provide_context(parts);
+
+                let mut res = GenericResponse(service.0.run(req).await);
+
+                // a plain form submission goes back to the page it came from, unless the
+                // server function redirected somewhere else
+                if accepts_html {
+                    if let Some(referrer) = referrer {
+                        if !res.0.headers().contains_key(LOCATION) {
+                            *res.0.status_mut() = StatusCode::FOUND;
+                            res.0.headers_mut().insert(LOCATION, referrer);
+                        }
+                    }
+                }
+
+                res.extend_response(&res_options);
+                res.0
+            })
+        })
+        .await
+}
+
+/// Reads the body of a request to a server function into memory, so that it can be passed to
+/// [`handle_server_fns`].
+///
+/// As this integration reads the whole body before the server function runs, a body larger
+/// than the `max_body_size` of the server function's
+/// [`BodyLimits`](server_fn::request::BodyLimits) (or its
+/// `max_multipart_size`, for a multipart body) is refused with a `413 Payload Too Large`
+/// response. This happens before any of the body is read if it has a `Content-Length` header,
+/// and as soon as it passes the limit otherwise.
+pub async fn read_server_fn_body<B, E>(
+    req: Request<B>,
+) -> Result<Request<Bytes>, Response<Body>>
+where
+    B: Stream<Item = Result<Bytes, E>> + Unpin,
+    E: Display,
+{
+    let limits = server_fn::generic::get_server_fn_body_limits(
+        req.uri().path(),
+        req.method().clone(),
+    )
+    .unwrap_or_else(get_body_limits);
+    let is_multipart = req
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("multipart/form-data"));
+    let max = if is_multipart {
+        limits.max_multipart_size()
+    } else {
+        limits.max_body_size()
+    };
+    read_limited_body(req, max).await
+}
+
+async fn read_limited_body<B, E>(
+    req: Request<B>,
+    max: Option<usize>,
+) -> Result<Request<Bytes>, Response<Body>>
+where
+    B: Stream<Item = Result<Bytes, E>> + Unpin,
+    E: Display,
+{
+    let (parts, mut body) = req.into_parts();
+    let too_large = |max: usize| {
+        <Response<Body> as Res<NoCustomError>>::error_response(
+            parts.uri.path(),
+            &ServerFnError::PayloadTooLarge(format!(
+                "the request body is larger than the limit of {max} bytes"
+            )),
+        )
+    };
+    let content_length = parts
+        .headers
+        .get(header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<usize>().ok());
+    if let (Some(max), Some(len)) = (max, content_length) {
+        if len > max {
+            return Err(too_large(max));
+        }
+    }
+    let mut buf = Vec::with_capacity(content_length.unwrap_or_default());
+    while let Some(chunk) = body.next().await {
+        let chunk = chunk.map_err(|e| {
+            Response::builder()
+                .status(StatusCode::BAD_REQUEST)
+                .body(Body::from(format!("failed to read the body: {e}")))
+                .unwrap()
+        })?;
+        buf.extend_from_slice(&chunk);
+        if let Some(max) = max {
+            if buf.len() > max {
+                return Err(too_large(max));
+            }
+        }
+    }
+    Ok(Request::from_parts(parts, buf.into()))
+}
+
+/// Returns a handler that renders the app in the given [`SsrMode`].
+///
+/// `SsrMode::Static` routes are rendered asynchronously on each request, as static generation
+/// depends on the framework's file serving.
+///
+/// ## Provided Context Types
+/// This function always provides context values including the following types:
+/// - [`Parts`]
+/// - [`ResponseOptions`]
+/// - [`ServerMetaContext`]
+pub fn render_app<IV>(
+    mode: SsrMode,
+    additional_context: impl Fn() + 'static + Clone + Send,
+    app_fn: impl Fn() -> IV + Clone + Send + 'static,
+) -> impl Fn(Request<Bytes>) -> PinnedFuture<Response<Body>> + Clone + Send + 'static
+where
+    IV: IntoView + 'static,
+{
+    let stream_builder: fn(
+        IV,
+        BoxedFnOnce<PinnedStream<String>>,
+    ) -> PinnedFuture<PinnedStream<String>> = match mode {
+        SsrMode::OutOfOrder | SsrMode::PartiallyBlocked => {
+            out_of_order_stream_builder::<IV>
+        }
+        SsrMode::InOrder => in_order_stream_builder::<IV>,
+        SsrMode::Async | SsrMode::Static(_) => async_stream_builder::<IV>,
+    };
+    move |req| {
+        render_response(
+            additional_context.clone(),
+            app_fn.clone(),
+            req,
+            stream_builder,
+        )
+    }
+}
+
+fn out_of_order_stream_builder<IV>(
+    app: IV,
+    chunks: BoxedFnOnce<PinnedStream<String>>,
+) -> PinnedFuture<PinnedStream<String>>
+where
+    IV: IntoView + 'static,
+{
+    Box::pin(async move {
+        Box::pin(app.to_html_stream_out_of_order().chain(chunks()))
+            as PinnedStream<String>
+    })
+}
+
+fn in_order_stream_builder<IV>(
+    app: IV,
+    chunks: BoxedFnOnce<PinnedStream<String>>,
+) -> PinnedFuture<PinnedStream<String>>
+where
+    IV: IntoView + 'static,
+{
+    Box::pin(async move {
+        Box::pin(app.to_html_stream_in_order().chain(chunks()))
+            as PinnedStream<String>
+    })
+}
+
+fn async_stream_builder<IV>(
+    app: IV,
+    chunks: BoxedFnOnce<PinnedStream<String>>,
+) -> PinnedFuture<PinnedStream<String>>
+where
+    IV: IntoView + 'static,
+{
+    Box::pin(async move {
+        let app = app.to_html_stream_in_order().collect::<String>().await;
+        let chunks = chunks();
+        Box::pin(once(async move { app }).chain(chunks)) as PinnedStream<String>
+    })
+}
+
+fn render_response<IV>(
+    additional_context: impl Fn() + 'static + Clone + Send,
+    app_fn: impl FnOnce() -> IV + Send + 'static,
+    req: Request<Bytes>,
+    stream_builder: fn(
+        IV,
+        BoxedFnOnce<PinnedStream<String>>,
+    ) -> PinnedFuture<PinnedStream<String>>,
+) -> PinnedFuture<Response<Body>>
+where
+    IV: IntoView + 'static,
+{
+    Box::pin(async move {
+        let res_options = ResponseOptions::default();
+        let (meta_context, meta_output) = ServerMetaContext::new();
+
+        let context = {
+            let res_options = res_options.clone();
+            move || {
+                let path = req
+                    .uri()
+                    .path_and_query()
+                    .map(|path| path.as_str())
+                    .unwrap_or("/");
+                let full_path = format!("http://leptos.dev{path}");
+                let (parts, _) = req.into_parts();
+                provide_contexts(&full_path, &meta_context, parts, res_options);
+                additional_context();
+            }
+        };
+
+        GenericResponse::from_app(
+            app_fn,
+            meta_output,
+            context,
+            res_options,
+            stream_builder,
+        )
+        .await
+        .0
+    })
+}
+
+fn provide_contexts(
+    path: &str,
+    meta_context: &ServerMetaContext,
+    parts: Parts,
+    res_options: ResponseOptions,
+) {
+    #[cfg(feature = "csrf")]
+    if let Some(cookie) = leptos::csrf::provide_csrf_token(
+        parts
+            .headers
+            .get(header::COOKIE)
+            .and_then(|h| h.to_str().ok()),
+    ) {
+        if let Ok(cookie) = HeaderValue::from_str(&cookie) {
+            res_options.append_header(header::SET_COOKIE, cookie);
+        }
+    }
+    #[cfg(feature = "cookies")]
+    provide_cookies(&parts, &res_options);
+    provide_context(RequestUrl::new(path));
+    provide_context(meta_context.clone());
+    provide_context(parts);
+    provide_context(res_options);
+    provide_server_redirect(redirect);
+    leptos::nonce::provide_nonce();
+}
+
+/// Provides the request's cookies as [`Cookies`](leptos::cookies::Cookies), adding a
+/// `Set-Cookie` header to the response options for each change.
+#[cfg(feature = "cookies")]
+fn provide_cookies(parts: &Parts, res_options: &ResponseOptions) {
+    let cookies = parts
+        .headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|h| h.to_str().ok());
+    let res_options = res_options.clone();
+    leptos::cookies::provide_cookies(cookies, move |cookie| {
+        if let Ok(cookie) = HeaderValue::from_str(&cookie) {
+            res_options.append_header(header::SET_COOKIE, cookie);
+        }
+    });
+}
+
+/// The syntax used for route parameters by a server framework's router.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PathFormat {
+    /// `/posts/:id` and `/files/*path`, as used by `poem` and `axum` 0.7.
+    #[default]
+    Colon,
+    /// `/posts/{id}` and `/files/{*path}`, as used by `matchit` 0.8 and later.
+    Braces,
+    /// `/posts/<id>` and `/files/<**path>`, as used by `salvo`.
+    Angle,
+}
+
+/// A route that the app can render.
+#[derive(Debug, Clone)]
+pub struct RouteListing {
+    segments: Vec<PathSegment>,
+    mode: SsrMode,
+    methods: Vec<leptos_router::Method>,
+}
+
+impl RouteListing {
+    /// The path of this route, with parameters in the given format.
+    pub fn path(&self, format: PathFormat) -> String {
+        let mut path = String::new();
+        for segment in &self.segments {
+            let raw = segment.as_raw_str();
+            if !raw.is_empty() && !raw.starts_with('/') {
+                path.push('/');
+            }
+            match (segment, format) {
+                (PathSegment::Static(s), _) => path.push_str(s),
+                (PathSegment::Param(s), PathFormat::Colon) => {
+                    path.push(':');
+                    path.push_str(s);
+                }
+                (PathSegment::Param(s), PathFormat::Braces) => {
+                    path.push_str(&format!("{{{s}}}"))
+                }
+                (PathSegment::Param(s), PathFormat::Angle) => {
+                    path.push_str(&format!("<{s}>"))
+                }
+                (PathSegment::Splat(s), PathFormat::Colon) => {
+                    path.push('*');
+                    path.push_str(s);
+                }
+                (PathSegment::Splat(s), PathFormat::Braces) => {
+                    path.push_str(&format!("{{*{s}}}"))
+                }
+                (PathSegment::Splat(s), PathFormat::Angle) => {
+                    path.push_str(&format!("<**{s}>"))
+                }
+                // optional params have already been expanded
+                (PathSegment::Unit | PathSegment::OptionalParam(_), _) => {}
+            }
+        }
+        if path.is_empty() {
+            path.push('/');
+        }
+        path
+    }
+
+    /// Whether this route matches the given request path.
+    ///
+    /// This can be used by adapters for frameworks that can't register a handler per route.
+    pub fn matches(&self, path: &str) -> bool {
+        let mut parts = path.split('/').filter(|part| !part.is_empty());
+        for segment in &self.segments {
+            match segment {
+                PathSegment::Static(s) => {
+                    for expected in s.split('/').filter(|s| !s.is_empty()) {
+                        if parts.next() != Some(expected) {
+                            return false;
+                        }
+                    }
+                }
+                PathSegment::Param(_) => {
+                    if parts.next().is_none() {
+                        return false;
+                    }
+                }
+                PathSegment::Splat(_) => return true,
+                PathSegment::Unit | PathSegment::OptionalParam(_) => {}
+            }
+        }
+        parts.next().is_none()
+    }
+
+    /// The rendering mode for this route.
+    pub fn mode(&self) -> &SsrMode {
+        &self.mode
+    }
+
+    /// The HTTP request methods this route can handle.
+    pub fn methods(&self) -> impl Iterator<Item = leptos_router::Method> + '_ {
+        self.methods.iter().copied()
+    }
+}
+
+/// Generates a list of all routes defined in Leptos's Router in your app, so that each can be
+/// registered with the framework's router.
+pub fn generate_route_list<IV>(
+    app_fn: impl Fn() -> IV + 'static + Clone + Send,
+) -> Vec<RouteListing>
+where
+    IV: IntoView + 'static,
+{
+    generate_route_list_with_exclusions_and_context(app_fn, None, || {})
+}
+
+/// Generates a list of all routes defined in Leptos's Router in your app, leaving out the
+/// `excluded_routes` (in the same format as [`RouteListing::path`] with
+/// [`PathFormat::Colon`]) and providing additional context to the app.
+pub fn generate_route_list_with_exclusions_and_context<IV>(
+    app_fn: impl Fn() -> IV + Clone + Send + 'static,
+    excluded_routes: Option<Vec<String>>,
+    additional_context: impl Fn() + Clone + Send + 'static,
+) -> Vec<RouteListing>
+where
+    IV: IntoView + 'static,
+{
+    let owner = Owner::new_root(Some(Arc::new(SsrSharedContext::new())));
+    let routes = owner
+        .with(|| {
+            let (mock_parts, _) = Request::new(()).into_parts();
+            let (mock_meta, _) = ServerMetaContext::new();
+            provide_contexts("", &mock_meta, mock_parts, Default::default());
+            additional_context();
+            RouteList::generate(&app_fn)
+        })
+        .unwrap_or_default();
+
+    let mut routes = routes
+        .into_inner()
+        .into_iter()
+        .flat_map(|listing| {
+            let mode = listing.mode().clone();
+            let methods = listing.methods().collect::<Vec<_>>();
+            listing.path().to_vec().expand_optionals().into_iter().map(
+                move |segments| RouteListing {
+                    segments,
+                    mode: mode.clone(),
+                    methods: methods.clone(),
+                },
+            )
+        })
+        .collect::<Vec<_>>();
+
+    if routes.is_empty() {
+        routes.push(RouteListing {
+            segments: Vec::new(),
+            mode: Default::default(),
+            methods: vec![leptos_router::Method::Get],
+        });
+    }
+    if let Some(excluded_routes) = excluded_routes {
+        routes.retain(|route| {
+            !excluded_routes.contains(&route.path(PathFormat::Colon))
+        });
+    }
+    routes
+}
+
+/// Serves the file in `root` at the request's path, if there is one.
+///
+/// A precompressed `.br` or `.gz` version of the file is served instead if the client accepts
+/// it. Paths that contain `..` or other special segments are never served.
+///
+/// The file is read synchronously, so frameworks that have their own static file service
+/// should usually prefer it.
+pub fn serve_static_file(
+    root: &str,
+    req: &Request<Bytes>,
+) -> Option<Response<Body>> {
+    if !matches!(*req.method(), Method::GET | Method::HEAD) {
+        return None;
+    }
+    let path = req.uri().path().trim_start_matches('/');
+    let is_safe = !path.is_empty()
+        && path.split('/').all(|segment| {
+            !segment.is_empty()
+                && segment != "."
+                && segment != ".."
+                && !segment.contains('\\')
+        });
+    if !is_safe {
+        return None;
+    }
+    let file = Path::new(root).join(path);
+    if !file.is_file() {
+        return None;
+    }
+
+    let accepted = req
+        .headers()
+        .get(ACCEPT_ENCODING)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+    let (contents, encoding) = [("br", "br"), ("gzip", "gz")]
+        .into_iter()
+        .filter(|(encoding, _)| accepted.contains(encoding))
+        .find_map(|(encoding, ext)| {
+            let mut compressed = file.clone().into_os_string();
+            compressed.push(format!(".{ext}"));
+            Some((std::fs::read(compressed).ok()?, Some(encoding)))
+        })
+        .or_else(|| Some((std::fs::read(&file).ok()?, None)))?;
+
+    let mut res = Response::builder()
+        .header(header::CONTENT_TYPE, content_type(&file))
+        .header(header::VARY, "accept-encoding");
+    if let Some(encoding) = encoding {
+        res = res.header(header::CONTENT_ENCODING, encoding);
+    }
+    let body = if req.method() == Method::HEAD {
+        Bytes::new()
+    } else {
+        Bytes::from(contents)
+    };
+    res.body(Body::Sync(body)).ok()
+}
+
+fn content_type(file: &Path) -> &'static str {
+    match file.extension().and_then(|ext| ext.to_str()) {
+        Some("html") => "text/html; charset=utf-8",
+        Some("js" | "mjs") => "text/javascript",
+        Some("wasm") => "application/wasm",
+        Some("css") => "text/css",
+        Some("json" | "map") => "application/json",
+        Some("txt") => "text/plain; charset=utf-8",
+        Some("svg") => "image/svg+xml",
+        Some("png") => "image/png",
+        Some("jpg" | "jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("webp") => "image/webp",
+        Some("ico") => "image/x-icon",
+        Some("woff") => "font/woff",
+        Some("woff2") => "font/woff2",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Returns a handler that serves static files (like JS/WASM/CSS) from the site root, and
+/// renders `shell` with a `404 Not Found` status for any other path.
+///
+/// This is meant to be used as the fallback for any request that doesn't match a route of the
+/// app or a server function.
+pub fn file_and_error_handler<IV>(
+    options: LeptosOptions,
+    shell: fn(LeptosOptions) -> IV,
+) -> impl Fn(Request<Bytes>) -> PinnedFuture<Response<Body>> + Clone + Send + 'static
+where
+    IV: IntoView + 'static,
+{
+    move |req| {
+        if let Some(res) = serve_static_file(&options.site_root, &req) {
+            return Box::pin(async move { res });
+        }
+        let app_fn = {
+            let options = options.clone();
+            move || shell(options)
+        };
+        let options = options.clone();
+        let res = render_response(
+            move || provide_context(options.clone()),
+            app_fn,
+            req,
+            async_stream_builder,
+        );
+        Box::pin(async move {
+            let mut res = res.await;
+            *res.status_mut() = StatusCode::NOT_FOUND;
+            res
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::{executor::block_on, stream};
+
+    fn listing(segments: Vec<PathSegment>) -> RouteListing {
+        RouteListing {
+            segments,
+            mode: SsrMode::OutOfOrder,
+            methods: vec![leptos_router::Method::Get],
+        }
+    }
+
+    fn post() -> RouteListing {
+        listing(vec![
+            PathSegment::Static("/posts".into()),
+            PathSegment::Param("id".into()),
+            PathSegment::Static("edit".into()),
+        ])
+    }
+
+    fn files() -> RouteListing {
+        listing(vec![
+            PathSegment::Static("/files".into()),
+            PathSegment::Splat("path".into()),
+        ])
+    }
+
+    #[test]
+    fn paths_use_the_parameter_format() {
+        assert_eq!(post().path(PathFormat::Colon), "/posts/:id/edit");
+        assert_eq!(post().path(PathFormat::Braces), "/posts/{id}/edit");
+        assert_eq!(post().path(PathFormat::Angle), "/posts/<id>/edit");
+        assert_eq!(files().path(PathFormat::Colon), "/files/*path");
+        assert_eq!(files().path(PathFormat::Braces), "/files/{*path}");
+        assert_eq!(files().path(PathFormat::Angle), "/files/<**path>");
+    }
+
+    #[test]
+    fn root_path_is_a_slash() {
+        let root = listing(vec![PathSegment::Unit]);
+        for format in [PathFormat::Colon, PathFormat::Braces, PathFormat::Angle]
+        {
+            assert_eq!(root.path(format), "/");
+        }
+        assert!(root.matches("/"));
+        assert!(!root.matches("/posts"));
+    }
+
+    #[test]
+    fn matches_params_in_place() {
+        assert!(post().matches("/posts/1/edit"));
+        assert!(post().matches("/posts/1/edit/"));
+        assert!(!post().matches("/posts/1"));
+        assert!(!post().matches("/posts/1/edit/more"));
+        assert!(!post().matches("/users/1/edit"));
+    }
+
+    #[test]
+    fn splats_match_the_rest_of_the_path() {
+        assert!(files().matches("/files/a"));
+        assert!(files().matches("/files/a/b/c"));
+        assert!(!files().matches("/other/a"));
+    }
+
+    fn request(
+        content_length: Option<usize>,
+        chunks: Vec<&'static [u8]>,
+    ) -> Request<impl Stream<Item = Result<Bytes, String>> + Unpin> {
+        let mut req = Request::post("/api/upload");
+        if let Some(len) = content_length {
+            req = req.header(header::CONTENT_LENGTH, len);
+        }
+        req.body(stream::iter(
+            chunks.into_iter().map(|c| Ok(Bytes::from_static(c))),
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn reads_bodies_within_the_limit() {
+        let req = block_on(read_limited_body(
+            request(None, vec![b"ab", b"cd"]),
+            Some(4),
+        ))
+        .unwrap_or_else(|_| panic!("the body was refused"));
+        assert_eq!(req.body().as_ref(), b"abcd");
+    }
+
+    #[test]
+    fn refuses_a_long_content_length_before_reading() {
+        // the body itself is short, so only the header can cause this
+        let res =
+            block_on(read_limited_body(request(Some(5), vec![b"ab"]), Some(4)))
+                .unwrap_err();
+        assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[test]
+    fn refuses_a_body_once_it_passes_the_limit() {
+        let res = block_on(read_limited_body(
+            request(None, vec![b"abc", b"def"]),
+            Some(4),
+        ))
+        .unwrap_err();
+        assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+}
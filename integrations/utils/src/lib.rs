@@ -9,6 +9,9 @@ use leptos_config::LeptosOptions;
 use leptos_meta::ServerMetaContextOutput;
 use std::{future::Future, pin::Pin, sync::Arc};
 
+#[cfg(feature = "generic")]
+pub mod generic;
+
 pub type PinnedStream<T> = Pin<Box<dyn Stream<Item = T> + Send>>;
 pub type PinnedFuture<T> = Pin<Box<dyn Future<Output = T> + Send>>;
 pub type BoxedFnOnce<T> = Box<dyn FnOnce() -> T + Send>;
@@ -0,0 +1,353 @@
+use crate::context::{provide_context, use_context};
+use cookie::CookieJar;
+pub use cookie::{Cookie, Key, SameSite};
+use or_poisoned::OrPoisoned;
+use std::{
+    borrow::Cow,
+    fmt::Debug,
+    sync::{Arc, Mutex, OnceLock},
+};
+
+static COOKIE_KEY: OnceLock<Key> = OnceLock::new();
+
+/// Sets the key used to sign and encrypt cookies with [`Cookies::signed`] and
+/// [`Cookies::private`]. Returns `Err(_)` with the key if one has already been set.
+///
+/// The key should be at least 64 bytes of random data that stays the same across restarts and
+/// across every server of the app, or cookies set by one will be rejected by another:
+///
+/// ```rust,ignore
+/// let secret = std::env::var("COOKIE_SECRET").expect("COOKIE_SECRET is set");
+/// leptos::cookies::set_cookie_key(Key::from(secret.as_bytes())).unwrap();
+/// ```
+pub fn set_cookie_key(key: Key) -> Result<(), Key> {
+    COOKIE_KEY.set(key)
+}
+
+type OnSetCookie = Arc<dyn Fn(String) + Send + Sync>;
+
+/// The cookies of the current request, and any changes to them that should be sent with the
+/// response.
+///
+/// When the `cookies` feature is enabled on one of the server integrations, this is provided
+/// via context to server functions and during server rendering, and each change is added to the
+/// response as a `Set-Cookie` header:
+///
+/// ```rust,ignore
+/// #[server]
+/// pub async fn set_theme(theme: String) -> Result<(), ServerFnError> {
+///     let cookies = use_cookies().expect("cookies are provided by the integration");
+///     cookies.add(Cookie::build(("theme", theme)).max_age(Duration::days(365)));
+///     Ok(())
+/// }
+/// ```
+///
+/// A cookie is set for the whole site (with a `Path` of `/`) unless it is given a path of its
+/// own. If the same cookie is changed more than once, browsers apply the last change.
+///
+/// During server rendering, headers can only be changed until the first chunk of the response
+/// has been sent, so cookies should be set before any `<Suspense/>` that streams in later.
+#[derive(Clone)]
+pub struct Cookies {
+    jar: Arc<Mutex<CookieJar>>,
+    on_set_cookie: OnSetCookie,
+}
+
+impl Debug for Cookies {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Cookies")
+            .field("jar", &self.jar)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Cookies {
+    /// Reads the cookies from the values of the request's `Cookie` headers, calling
+    /// `on_set_cookie` with the value of a `Set-Cookie` header each time a cookie is changed.
+    ///
+    /// HTTP/2 clients may split the cookies across several headers, so every one of them
+    /// should be passed.
+    pub fn new<'a>(
+        cookie_headers: impl IntoIterator<Item = &'a str>,
+        on_set_cookie: impl Fn(String) + Send + Sync + 'static,
+    ) -> Self {
+        let mut jar = CookieJar::new();
+        for cookie in cookie_headers
+            .into_iter()
+            .flat_map(|header| Cookie::split_parse_encoded(header.to_owned()))
+            .flatten()
+        {
+            jar.add_original(cookie);
+        }
+        Self {
+            jar: Arc::new(Mutex::new(jar)),
+            on_set_cookie: Arc::new(on_set_cookie),
+        }
+    }
+
+    /// The value of the cookie with the given name.
+    pub fn get(&self, name: &str) -> Option<String> {
+        self.jar
+            .lock()
+            .or_poisoned()
+            .get(name)
+            .map(|cookie| cookie.value().to_string())
+    }
+
+    /// Adds a cookie, or replaces the cookie with the same name.
+    pub fn add(&self, cookie: impl Into<Cookie<'static>>) {
+        let cookie = with_default_path(cookie.into());
+        self.add_with(cookie.name().to_string(), |jar| jar.add(cookie));
+    }
+
+    /// Removes the cookie with the given name, which must have been set with the default
+    /// path of `/`.
+    ///
+    /// To remove a cookie with another path, pass a cookie with that name and path to
+    /// [`remove_cookie`](Self::remove_cookie) instead.
+    pub fn remove(&self, name: impl Into<Cow<'static, str>>) {
+        self.remove_cookie(Cookie::new(name, ""));
+    }
+
+    /// Removes a cookie, which must have the same name, path, and domain as the cookie it
+    /// removes.
+    pub fn remove_cookie(&self, cookie: impl Into<Cookie<'static>>) {
+        let cookie = with_default_path(cookie.into());
+        // the browser may have the cookie even if this request didn't send it, so it is
+        // always told to remove it
+        let mut removal = cookie.clone();
+        removal.make_removal();
+        self.jar.lock().or_poisoned().remove(cookie);
+        (self.on_set_cookie)(removal.encoded().to_string());
+    }
+
+    /// The cookies that are signed with the key set by [`set_cookie_key`], so that they can be
+    /// read by the browser but can't be changed without being rejected.
+    ///
+    /// Returns `None` if no key has been set.
+    pub fn signed(&self) -> Option<SignedCookies<'_>> {
+        Some(SignedCookies {
+            cookies: self,
+            key: COOKIE_KEY.get()?,
+        })
+    }
+
+    /// The cookies that are encrypted with the key set by [`set_cookie_key`], so that they can
+    /// be neither read nor changed by the browser.
+    ///
+    /// Returns `None` if no key has been set.
+    pub fn private(&self) -> Option<PrivateCookies<'_>> {
+        Some(PrivateCookies {
+            cookies: self,
+            key: COOKIE_KEY.get()?,
+        })
+    }
+
+    fn add_with(&self, name: String, f: impl FnOnce(&mut CookieJar)) {
+        let header = {
+            let mut jar = self.jar.lock().or_poisoned();
+            f(&mut jar);
+            jar.delta()
+                .find(|cookie| cookie.name() == name)
+                .map(|cookie| cookie.encoded().to_string())
+        };
+        if let Some(header) = header {
+            (self.on_set_cookie)(header);
+        }
+    }
+}
+
+// a cookie without a path only applies to the path of the page that set it, which is rarely
+// what's wanted from a server function
+fn with_default_path(mut cookie: Cookie<'static>) -> Cookie<'static> {
+    if cookie.path().is_none() {
+        cookie.set_path("/");
+    }
+    cookie
+}
+
+/// Cookies that are signed, returned by [`Cookies::signed`].
+pub struct SignedCookies<'a> {
+    cookies: &'a Cookies,
+    key: &'static Key,
+}
+
+impl Debug for SignedCookies<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SignedCookies")
+            .field("cookies", &self.cookies)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SignedCookies<'_> {
+    /// The value of the cookie with the given name, if its signature is valid.
+    pub fn get(&self, name: &str) -> Option<String> {
+        self.cookies
+            .jar
+            .lock()
+            .or_poisoned()
+            .signed(self.key)
+            .get(name)
+            .map(|cookie| cookie.value().to_string())
+    }
+
+    /// Signs a cookie and adds it, replacing the cookie with the same name.
+    pub fn add(&self, cookie: impl Into<Cookie<'static>>) {
+        let cookie = with_default_path(cookie.into());
+        self.cookies.add_with(cookie.name().to_string(), |jar| {
+            jar.signed_mut(self.key).add(cookie)
+        });
+    }
+
+    /// Removes the cookie with the given name, as [`Cookies::remove`] does.
+    pub fn remove(&self, name: impl Into<Cow<'static, str>>) {
+        self.cookies.remove(name);
+    }
+}
+
+/// Cookies that are encrypted, returned by [`Cookies::private`].
+pub struct PrivateCookies<'a> {
+    cookies: &'a Cookies,
+    key: &'static Key,
+}
+
+impl Debug for PrivateCookies<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PrivateCookies")
+            .field("cookies", &self.cookies)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PrivateCookies<'_> {
+    /// The decrypted value of the cookie with the given name, if it could be decrypted.
+    pub fn get(&self, name: &str) -> Option<String> {
+        self.cookies
+            .jar
+            .lock()
+            .or_poisoned()
+            .private(self.key)
+            .get(name)
+            .map(|cookie| cookie.value().to_string())
+    }
+
+    /// Encrypts a cookie and adds it, replacing the cookie with the same name.
+    pub fn add(&self, cookie: impl Into<Cookie<'static>>) {
+        let cookie = with_default_path(cookie.into());
+        self.cookies.add_with(cookie.name().to_string(), |jar| {
+            jar.private_mut(self.key).add(cookie)
+        });
+    }
+
+    /// Removes the cookie with the given name, as [`Cookies::remove`] does.
+    pub fn remove(&self, name: impl Into<Cow<'static, str>>) {
+        self.cookies.remove(name);
+    }
+}
+
+/// Accesses the cookies of the current request.
+pub fn use_cookies() -> Option<Cookies> {
+    use_context::<Cookies>()
+}
+
+/// Provides the cookies of the current request via context.
+///
+/// This is called by the server integrations with the values of the request's `Cookie`
+/// headers, and they add each `Set-Cookie` header passed to `on_set_cookie` to the response.
+pub fn provide_cookies<'a>(
+    cookie_headers: impl IntoIterator<Item = &'a str>,
+    on_set_cookie: impl Fn(String) + Send + Sync + 'static,
+) {
+    provide_context(Cookies::new(cookie_headers, on_set_cookie));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read<'a>(
+        headers: impl IntoIterator<Item = &'a str>,
+    ) -> (Cookies, Arc<Mutex<Vec<String>>>) {
+        let set = Arc::new(Mutex::new(Vec::new()));
+        let cookies = Cookies::new(headers, {
+            let set = Arc::clone(&set);
+            move |header| set.lock().unwrap().push(header)
+        });
+        (cookies, set)
+    }
+
+    // the `name=value` pair of a `Set-Cookie` header, as the browser would send it back
+    fn sent_back(set_cookie: &str) -> String {
+        set_cookie.split(';').next().unwrap().to_string()
+    }
+
+    fn with_key() {
+        // every test sets the same key, so it doesn't matter which one is first
+        _ = set_cookie_key(Key::from(&[7; 64]));
+    }
+
+    #[test]
+    fn reads_every_cookie_header() {
+        let (cookies, _) = read(["a=1; b=2", "c=3"]);
+        assert_eq!(cookies.get("a").as_deref(), Some("1"));
+        assert_eq!(cookies.get("b").as_deref(), Some("2"));
+        assert_eq!(cookies.get("c").as_deref(), Some("3"));
+        assert_eq!(cookies.get("d"), None);
+    }
+
+    #[test]
+    fn cookies_apply_to_the_whole_site_by_default() {
+        let (cookies, set) = read([]);
+        cookies.add(("theme", "dark"));
+        cookies.add(Cookie::build(("tab", "2")).path("/settings"));
+        let set = set.lock().unwrap();
+        assert_eq!(set[0], "theme=dark; Path=/");
+        assert_eq!(set[1], "tab=2; Path=/settings");
+        assert_eq!(cookies.get("theme").as_deref(), Some("dark"));
+    }
+
+    #[test]
+    fn removes_cookies_that_were_not_sent() {
+        let (cookies, set) = read(["other=1"]);
+        cookies.remove("session");
+        let set = set.lock().unwrap();
+        assert_eq!(set.len(), 1);
+        let removal = Cookie::parse(set[0].clone()).unwrap();
+        assert_eq!(removal.name(), "session");
+        assert_eq!(removal.value(), "");
+        assert_eq!(removal.path(), Some("/"));
+        assert!(removal.max_age().is_some_and(|age| age.is_zero()));
+    }
+
+    #[test]
+    fn signed_cookies_round_trip() {
+        with_key();
+        let (cookies, set) = read([]);
+        cookies.signed().unwrap().add(("user", "42"));
+        let header = sent_back(&set.lock().unwrap()[0]);
+
+        let (next, _) = read([header.as_str()]);
+        assert_eq!(next.signed().unwrap().get("user").as_deref(), Some("42"));
+        // the value can be read, but not changed
+        assert!(next.get("user").unwrap().ends_with("42"));
+        let tampered = header.replace("42", "43");
+        let (next, _) = read([tampered.as_str()]);
+        assert_eq!(next.signed().unwrap().get("user"), None);
+    }
+
+    #[test]
+    fn private_cookies_round_trip() {
+        with_key();
+        let (cookies, set) = read([]);
+        cookies.private().unwrap().add(("user", "42"));
+        let header = sent_back(&set.lock().unwrap()[0]);
+
+        let (next, _) = read([header.as_str()]);
+        assert_eq!(next.private().unwrap().get("user").as_deref(), Some("42"));
+        // the value can be neither read nor changed
+        assert_ne!(next.get("user").as_deref(), Some("42"));
+        let (next, _) = read(["user=42"]);
+        assert_eq!(next.private().unwrap().get("user"), None);
+    }
+}
@@ -0,0 +1,170 @@
+use crate::{
+    context::{provide_context, use_context},
+    IntoView,
+};
+use base64::{
+    alphabet,
+    engine::{self, general_purpose},
+    Engine,
+};
+use rand::{thread_rng, RngCore};
+use server_fn::csrf::{set_cookie_header, token_from_cookies, CSRF_FIELD};
+use std::{fmt::Display, ops::Deref, sync::Arc};
+use tachys::html::element::input;
+
+/// A random token that proves a request to a server function was made by a page of this
+/// site, which protects server functions against cross-site request forgery (CSRF).
+///
+/// When the `csrf` feature is enabled on one of the server integrations, the token is read from
+/// the request's cookies during server rendering, or generated and set as a cookie if it has none.
+/// `<ActionForm/>`, `<MultiActionForm/>` and the router's `<Form/>` add it to their submissions
+/// with a hidden input, and the server function client sends it as a header.
+///
+/// The server checks the token once protection is turned on with
+/// [`enable_csrf_protection`](server_fn::csrf::enable_csrf_protection):
+///
+/// ```rust,ignore
+/// #[tokio::main]
+/// async fn main() {
+///     server_fn::csrf::enable_csrf_protection();
+///     // build and serve the app as usual
+/// }
+/// ```
+///
+/// Forms that aren't built with these components can include the token with [`csrf_field`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CsrfToken(Arc<str>);
+
+impl Deref for CsrfToken {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Display for CsrfToken {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+const TOKEN_ENGINE: engine::GeneralPurpose =
+    engine::GeneralPurpose::new(&alphabet::URL_SAFE, general_purpose::NO_PAD);
+
+impl CsrfToken {
+    /// Generates a new token from 32 bytes (256 bits) of random data.
+    pub fn new() -> Self {
+        let mut thread_rng = thread_rng();
+        let mut bytes = [0; 32];
+        thread_rng.fill_bytes(&mut bytes);
+        CsrfToken(TOKEN_ENGINE.encode(bytes).into())
+    }
+
+    // only tokens we could have generated are reused, so a cookie can't inject anything
+    // into the page
+    fn from_cookie(token: &str) -> Option<Self> {
+        token
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+            .then(|| CsrfToken(token.into()))
+    }
+}
+
+impl Default for CsrfToken {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Accesses the CSRF token for the current request.
+///
+/// On the server, this is the token provided by the server integration. In the browser, it is
+/// read from the cookie the server set.
+pub fn use_csrf_token() -> Option<CsrfToken> {
+    use_context::<CsrfToken>().or_else(|| {
+        server_fn::csrf::client_token()
+            .and_then(|token| CsrfToken::from_cookie(&token))
+    })
+}
+
+/// Provides the CSRF token for the current request via context, reusing the token in the
+/// request's `Cookie` header if it has one.
+///
+/// If a new token was generated, returns the `Set-Cookie` header that should be sent with the
+/// response.
+pub fn provide_csrf_token(cookies: Option<&str>) -> Option<String> {
+    match cookies
+        .and_then(token_from_cookies)
+        .and_then(CsrfToken::from_cookie)
+    {
+        Some(token) => {
+            provide_context(token);
+            None
+        }
+        None => {
+            let token = CsrfToken::new();
+            let header = set_cookie_header(&token);
+            provide_context(token);
+            Some(header)
+        }
+    }
+}
+
+/// A hidden input that submits the CSRF token with a form, if there is one.
+pub fn csrf_field() -> impl IntoView {
+    use_csrf_token().map(|token| {
+        input()
+            .r#type("hidden")
+            .name(CSRF_FIELD)
+            .value(token.to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reactive_graph::owner::Owner;
+
+    #[test]
+    fn new_tokens_are_random_and_url_safe() {
+        let a = CsrfToken::new();
+        let b = CsrfToken::new();
+        assert_ne!(a, b);
+        // 32 bytes of base64 without padding
+        assert_eq!(a.len(), 43);
+        assert_eq!(CsrfToken::from_cookie(&a), Some(a));
+    }
+
+    #[test]
+    fn rejects_cookies_we_could_not_have_issued() {
+        assert!(CsrfToken::from_cookie("abc-DEF_123").is_some());
+        assert!(CsrfToken::from_cookie("\"><script>").is_none());
+        assert!(CsrfToken::from_cookie("a b").is_none());
+        assert!(CsrfToken::from_cookie("a=b").is_none());
+    }
+
+    #[test]
+    fn reuses_the_token_in_the_cookie() {
+        let owner = Owner::new();
+        owner.with(|| {
+            let header =
+                provide_csrf_token(Some("theme=dark; leptos_csrf=abc"));
+            assert_eq!(header, None);
+            assert_eq!(use_context::<CsrfToken>().as_deref(), Some("abc"));
+        });
+    }
+
+    #[test]
+    fn sets_a_new_token_if_the_cookie_is_missing_or_malformed() {
+        for cookies in [None, Some("theme=dark"), Some("leptos_csrf=<b>")] {
+            let owner = Owner::new();
+            owner.with(|| {
+                let header = provide_csrf_token(cookies)
+                    .expect("a new token should be set");
+                let token = use_context::<CsrfToken>().unwrap();
+                assert_eq!(token_from_cookies(&header), Some(&*token));
+            });
+        }
+    }
+}
@@ -1,9 +1,12 @@
+#[cfg(feature = "csrf")]
+use crate::csrf::csrf_field;
 use crate::{children::Children, component, prelude::*, IntoView};
 use leptos_dom::helpers::window;
 use leptos_server::{ServerAction, ServerMultiAction};
 use serde::de::DeserializeOwned;
 use server_fn::{
-    client::Client, codec::PostUrl, request::ClientReq, ServerFn, ServerFnError,
+    client::Client, codec::PostUrl, error::ValidationErrors,
+    request::ClientReq, ServerFn, ServerFnError,
 };
 use tachys::{
     either::Either,
@@ -69,6 +72,11 @@ use web_sys::{
 ///     Ok(())
 /// }
 /// ```
+///
+/// ## Validation Errors
+/// If the server function fails with [`ServerFnError::Validation`], the errors for each field
+/// are available to the form's children through [`use_form_validation`], both after a
+/// submission with WASM and after the redirect back to the page without it.
 #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
 #[component]
 pub fn ActionForm<ServFn>(
@@ -104,6 +112,15 @@ where
     let version = action.version();
     let value = action.value();
 
+    provide_context(FormValidation::new(Signal::derive(move || {
+        value.with(|value| match value {
+            Some(Err(ServerFnError::Validation(errors))) => {
+                Some(errors.clone())
+            }
+            _ => None,
+        })
+    })));
+
     let on_submit = {
         move |ev: SubmitEvent| {
             if ev.default_prevented() {
@@ -134,7 +151,7 @@ where
         .action(ServFn::url())
         .method("post")
         .on(submit, on_submit)
-        .child(children());
+        .child((csrf_field(), children()));
     if let Some(node_ref) = node_ref {
         Either::Left(action_form.node_ref(node_ref))
     } else {
@@ -199,7 +216,7 @@ where
         .method("post")
         .attr("method", "post")
         .on(submit, on_submit)
-        .child(children());
+        .child((csrf_field(), children()));
     if let Some(node_ref) = node_ref {
         Either::Left(action_form.node_ref(node_ref))
     } else {
@@ -207,6 +224,79 @@ where
     }
 }
 
+/// The field-level validation errors from the last submission of an `<ActionForm/>` or a
+/// router `<Form/>`, which the form provides to its children as context.
+///
+/// ```rust,ignore
+/// #[component]
+/// fn EmailInput() -> impl IntoView {
+///     let validation = use_form_validation().expect("inside a form");
+///     let error = validation.error("email");
+///     view! {
+///         <input type="email" name="email" value=validation.value("email")/>
+///         <Show when=move || error.get().is_some()>
+///             <p class="error">{error}</p>
+///         </Show>
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct FormValidation(Signal<Option<ValidationErrors>>);
+
+impl FormValidation {
+    /// Wraps a signal that holds the validation errors of the last submission, if it failed
+    /// validation.
+    pub fn new(errors: impl Into<Signal<Option<ValidationErrors>>>) -> Self {
+        Self(errors.into())
+    }
+
+    /// All the validation errors of the last submission, or `None` if it did not fail
+    /// validation.
+    pub fn errors(&self) -> Signal<Option<ValidationErrors>> {
+        self.0
+    }
+
+    /// The first error message for the field with the given name.
+    pub fn error(&self, field: impl Into<String>) -> Signal<Option<String>> {
+        let errors = self.0;
+        let field = field.into();
+        Signal::derive(move || {
+            errors.with(|errors| {
+                errors
+                    .as_ref()
+                    .and_then(|errors| errors.first(&field))
+                    .map(ToOwned::to_owned)
+            })
+        })
+    }
+
+    /// The value that was submitted for the field with the given name, if the form was
+    /// submitted without JS/WASM and failed validation.
+    ///
+    /// This can be used to fill in the form again after the redirect back to the page.
+    pub fn value(&self, field: impl Into<String>) -> Signal<Option<String>> {
+        let errors = self.0;
+        let field = field.into();
+        Signal::derive(move || {
+            errors.with(|errors| {
+                errors
+                    .as_ref()
+                    .and_then(|errors| errors.value(&field))
+                    .map(ToOwned::to_owned)
+            })
+        })
+    }
+}
+
+/// Returns the [`FormValidation`] of the enclosing `<ActionForm/>` or router `<Form/>`, if any.
+pub fn use_form_validation() -> Option<FormValidation> {
+    use_context()
+}
+
+// without CSRF protection, forms have no token to submit
+#[cfg(not(feature = "csrf"))]
+fn csrf_field() {}
+
 /// Resolves a redirect location to an (absolute) URL.
 pub(crate) fn resolve_redirect_url(loc: &str) -> Option<web_sys::Url> {
     let origin = match window().location().origin() {
@@ -157,6 +157,8 @@ pub mod prelude {
     // In the future, maybe we should remove this blanket export
     // However, it is definitely useful relative to looking up every struct etc.
     mod export_types {
+        #[cfg(feature = "csrf")]
+        pub use crate::csrf::*;
         #[cfg(feature = "nonce")]
         pub use crate::nonce::*;
         pub use crate::{
@@ -225,6 +227,14 @@ pub mod hydration;
 #[cfg(feature = "nonce")]
 pub mod nonce;
 
+/// Tokens that protect server functions and forms against cross-site request forgery.
+#[cfg(feature = "csrf")]
+pub mod csrf;
+
+/// Reading and setting cookies from server functions and during server rendering.
+#[cfg(feature = "cookies")]
+pub mod cookies;
+
 /// Components to load asynchronous data.
 pub mod suspense {
     pub use crate::{suspense_component::*, transition::*};
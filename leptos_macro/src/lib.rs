@@ -820,9 +820,27 @@ pub fn slot(args: proc_macro::TokenStream, s: TokenStream) -> TokenStream {
 ///    your prefix must begin with `/`. Otherwise your function won't be found.
 /// - `endpoint`: specifies the exact path at which the server function handler will be mounted,
 ///   relative to the prefix (defaults to the function name followed by unique hash)
-/// - `input`: the encoding for the arguments (defaults to `PostUrl`)
+/// - `version`: an API version number, which is added to the path after the prefix (`/api/v2/...`);
+///   the default path of a versioned server fn is hashed from its module and name only, so it
+///   stays the same between builds and older clients can keep calling it (see
+///   `server_fn::version` for how the server tells outdated clients to reload)
+/// - `alias_version`: a previous version that the server fn also serves, at that version's path,
+///   for when the change is compatible with the clients that still call it
+/// - `input`: the encoding for the arguments (defaults to `PostUrl`); a server fn that uses the
+///   `Protobuf` input encoding must take exactly one argument, which is sent as the message
 /// - `output`: the encoding for the response (defaults to `Json`)
+/// - `protocol`: a protocol that replaces both the `input` and `output` encodings, such as
+///   `Websocket<In, Out>`, which opens a websocket connection that streams `In` messages to the
+///   server and `Out` messages back to the client (requires the `websocket` feature)
 /// - `client`: a custom `Client` implementation that will be used for this server fn
+/// - `policy`: an expression for the `CallPolicy` (timeout, retries, and backoff) used when calling
+///   this server fn from the client (defaults to the global policy set with `set_call_policy`)
+/// - `body_limits`: an expression for the `BodyLimits` on the size of the request body this
+///   server fn accepts (defaults to the global limits set with `set_body_limits`)
+/// - `cache`: an expression for the `CachePolicy` that sets the `Cache-Control` and `ETag`
+///   headers of responses to a `GET` server fn; with an `ETag`, the client reuses its last
+///   decoded value when the server responds `304 Not Modified`, which requires the return
+///   type to be `Clone`
 /// - `encoding`: (legacy, may be deprecated in future) specifies the encoding, which may be one
 ///   of the following (not case sensitive)
 ///     - `"Url"`: `POST` request with URL-encoded arguments and JSON response
@@ -879,6 +897,13 @@ pub fn slot(args: proc_macro::TokenStream, s: TokenStream) -> TokenStream {
 /// pub async fn with_default_value(#[server(default)] values: Vec<u32>) /* etc. */
 /// ```
 ///
+/// ## OpenAPI Documents
+///
+/// With the `openapi` feature enabled, each server function is also described in the document
+/// returned by [`openapi_document`](../server_fn/openapi/fn.openapi_document.html), using its
+/// doc comments as the operation description. Argument and return types that implement
+/// `schemars::JsonSchema` are described by their schema.
+///
 /// ## Important Notes
 /// - **Server functions must be `async`.** Even if the work being done inside the function body
 ///   can run synchronously on the server, from the client’s perspective it involves an asynchronous
@@ -888,7 +913,9 @@ pub fn slot(args: proc_macro::TokenStream, s: TokenStream) -> TokenStream {
 ///   network call are fallible.
 ///     - [`ServerFnError`](../server_fn/error/enum.ServerFnError.html) can be generic over some custom error type. If so, that type should implement
 ///       [`FromStr`](std::str::FromStr) and [`Display`](std::fmt::Display), but does not need to implement [`Error`](std::error::Error). This is so the value
-///       can be easily serialized and deserialized along with the result.
+///       can be easily serialized and deserialized along with the result. It can also implement
+///       [`ServerFnErrorStatus`](../server_fn/error/trait.ServerFnErrorStatus.html) to choose
+///       the HTTP status code and headers of the error response, which is `500` otherwise.
 /// - **Server functions are part of the public API of your application.** A server function is an
 ///   ad hoc HTTP API endpoint, not a magic formula. Any server function can be accessed by any HTTP
 ///   client. You should take care to sanitize any data being returned from the function to ensure it
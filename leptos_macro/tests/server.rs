@@ -128,4 +128,139 @@ pub mod tests {
             TypeId::of::<codec::PostUrl>()
         );
     }
+    #[test]
+    fn server_protocol() {
+        #[server(protocol = codec::Websocket<String, String>)]
+        pub async fn my_server_action(
+            input: codec::BoxedStream<String>,
+        ) -> Result<codec::BoxedStream<String>, ServerFnError> {
+            Ok(input)
+        }
+        assert_eq!(
+            <MyServerAction as ServerFn>::PATH
+                .trim_end_matches(char::is_numeric),
+            "/api/my_server_action"
+        );
+        assert_eq!(
+            TypeId::of::<<MyServerAction as ServerFn>::InputEncoding>(),
+            TypeId::of::<codec::Websocket<String, String>>()
+        );
+        assert_eq!(
+            TypeId::of::<<MyServerAction as ServerFn>::OutputEncoding>(),
+            TypeId::of::<codec::Websocket<String, String>>()
+        );
+    }
+
+    #[test]
+    fn server_policy() {
+        use leptos::server_fn::client::{get_call_policy, CallPolicy};
+        use std::time::Duration;
+
+        #[server(
+            input = codec::GetUrl,
+            policy = CallPolicy::new()
+                .with_timeout(Duration::from_secs(5))
+                .with_retries(3)
+        )]
+        pub async fn my_server_action() -> Result<(), ServerFnError> {
+            Ok(())
+        }
+        let policy = <MyServerAction as ServerFn>::call_policy();
+        assert_eq!(policy.timeout(), Some(Duration::from_secs(5)));
+        assert_eq!(policy.max_retries(), 3);
+
+        #[server]
+        pub async fn default_policy() -> Result<(), ServerFnError> {
+            Ok(())
+        }
+        assert_eq!(
+            <DefaultPolicy as ServerFn>::call_policy(),
+            get_call_policy()
+        );
+    }
+
+    #[test]
+    fn server_body_limits() {
+        use leptos::server_fn::request::{get_body_limits, BodyLimits};
+
+        #[server(body_limits = BodyLimits::new().with_max_body_size(1024))]
+        pub async fn my_server_action() -> Result<(), ServerFnError> {
+            Ok(())
+        }
+        let limits = <MyServerAction as ServerFn>::body_limits();
+        assert_eq!(limits.max_body_size(), Some(1024));
+        assert_eq!(limits.max_fields(), None);
+
+        #[server]
+        pub async fn default_limits() -> Result<(), ServerFnError> {
+            Ok(())
+        }
+        assert_eq!(
+            <DefaultLimits as ServerFn>::body_limits(),
+            get_body_limits()
+        );
+    }
+
+    #[test]
+    fn server_cache() {
+        use leptos::server_fn::cache::CachePolicy;
+        use std::time::Duration;
+
+        #[server(
+            input = codec::GetUrl,
+            cache = CachePolicy::new()
+                .with_max_age(Duration::from_secs(60))
+                .with_etag()
+        )]
+        pub async fn my_server_action() -> Result<String, ServerFnError> {
+            Ok("hello".into())
+        }
+        let policy = <MyServerAction as ServerFn>::cache_policy();
+        assert_eq!(policy.cache_control().as_deref(), Some("max-age=60"));
+        assert!(policy.etag());
+
+        #[server]
+        pub async fn not_cached() -> Result<(), ServerFnError> {
+            Ok(())
+        }
+        assert_eq!(<NotCached as ServerFn>::cache_policy(), CachePolicy::new());
+    }
+
+    #[test]
+    fn server_version() {
+        #[server(version = 2)]
+        pub async fn my_server_action() -> Result<(), ServerFnError> {
+            Ok(())
+        }
+        assert!(<MyServerAction as ServerFn>::PATH
+            .starts_with("/api/v2/my_server_action"));
+
+        #[server(version = 3, endpoint = "my_endpoint")]
+        pub async fn versioned_endpoint() -> Result<(), ServerFnError> {
+            Ok(())
+        }
+        assert_eq!(
+            <VersionedEndpoint as ServerFn>::PATH,
+            "/api/v3/my_endpoint"
+        );
+    }
+
+    #[test]
+    fn server_versions_differ_only_in_version() {
+        let v1 = {
+            #[server(version = 1)]
+            pub async fn get_user() -> Result<(), ServerFnError> {
+                Ok(())
+            }
+            <GetUser as ServerFn>::PATH
+        };
+        let v2 = {
+            #[server(version = 2, alias_version = 1)]
+            pub async fn get_user() -> Result<(), ServerFnError> {
+                Ok(())
+            }
+            <GetUser as ServerFn>::PATH
+        };
+        assert_eq!(v1.replacen("/v1/", "/v2/", 1), v2);
+    }
 }
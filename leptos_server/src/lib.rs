@@ -15,6 +15,10 @@ pub use once_resource::*;
 mod resource;
 pub use resource::*;
 mod shared;
+#[cfg(feature = "upload")]
+mod upload;
+#[cfg(feature = "upload")]
+pub use upload::*;
 
 use base64::{engine::general_purpose::STANDARD_NO_PAD, DecodeError, Engine};
 pub use shared::*;
@@ -0,0 +1,158 @@
+use any_spawner::Executor;
+use or_poisoned::OrPoisoned;
+use reactive_graph::{
+    signal::{ArcReadSignal, ArcRwSignal},
+    traits::Set,
+};
+use server_fn::{
+    client::{abortable, AbortHandle},
+    upload::{upload, UploadChunk, UploadOptions, UploadSource, UploadStatus},
+    ServerFn, ServerFnError,
+};
+use std::sync::{Arc, Mutex};
+
+/// Uploads files in resumable chunks to the server function `S`, tracking the progress of the
+/// upload in signals.
+///
+/// `S` should use the `Chunked` input encoding and save each chunk with
+/// [`save_chunk`](server_fn::upload::save_chunk):
+///
+/// ```rust,ignore
+/// let upload = ServerUpload::<UploadVideo>::new();
+///
+/// let on_change = {
+///     let upload = upload.clone();
+///     move |ev: Event| {
+///         if let Some(file) = event_target::<HtmlInputElement>(&ev)
+///             .files()
+///             .and_then(|files| files.get(0))
+///         {
+///             upload.upload(file);
+///         }
+///     }
+/// };
+///
+/// let progress = upload.progress();
+/// view! {
+///     <input type="file" on:change=on_change/>
+///     <progress
+///         max=move || progress.get().total()
+///         value=move || progress.get().received()
+///     />
+/// }
+/// ```
+pub struct ServerUpload<S>
+where
+    S: ServerFn + 'static,
+{
+    options: UploadOptions,
+    progress: ArcRwSignal<UploadStatus>,
+    pending: ArcRwSignal<bool>,
+    value: ArcRwSignal<Option<Result<UploadStatus, ServerFnError<S::Error>>>>,
+    abort: Arc<Mutex<Option<AbortHandle>>>,
+}
+
+impl<S> Clone for ServerUpload<S>
+where
+    S: ServerFn + 'static,
+{
+    fn clone(&self) -> Self {
+        Self {
+            options: self.options,
+            progress: self.progress.clone(),
+            pending: self.pending.clone(),
+            value: self.value.clone(),
+            abort: Arc::clone(&self.abort),
+        }
+    }
+}
+
+impl<S> Default for ServerUpload<S>
+where
+    S: ServerFn<Output = UploadStatus> + From<UploadChunk> + 'static,
+    S::Error: Send + Sync + 'static,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> ServerUpload<S>
+where
+    S: ServerFn<Output = UploadStatus> + From<UploadChunk> + 'static,
+    S::Error: Send + Sync + 'static,
+{
+    /// Creates an upload with the default [`UploadOptions`].
+    pub fn new() -> Self {
+        Self::with_options(UploadOptions::default())
+    }
+
+    /// Creates an upload that sends files with the given options.
+    pub fn with_options(options: UploadOptions) -> Self {
+        Self {
+            options,
+            progress: ArcRwSignal::new(UploadStatus::default()),
+            pending: ArcRwSignal::new(false),
+            value: ArcRwSignal::new(None),
+            abort: Default::default(),
+        }
+    }
+
+    /// Starts uploading `source`, cancelling any upload that is already in progress.
+    ///
+    /// If the server already has part of this file from an earlier upload, only the rest of
+    /// it is sent.
+    pub fn upload(&self, source: impl UploadSource + 'static) {
+        self.cancel();
+        let options = self.options;
+        let progress = self.progress.clone();
+        let (call, handle) = abortable(async move {
+            upload::<S>(&source, options, |status| progress.set(status)).await
+        });
+        *self.abort.lock().or_poisoned() = Some(handle.clone());
+        self.pending.set(true);
+
+        let pending = self.pending.clone();
+        let value = self.value.clone();
+        let abort = Arc::clone(&self.abort);
+        Executor::spawn_local(async move {
+            let res = call.await;
+            // a newer upload may have replaced this one
+            if !handle.is_aborted() {
+                *abort.lock().or_poisoned() = None;
+                pending.set(false);
+                value.set(Some(res));
+            }
+        });
+    }
+
+    /// Cancels the upload in progress, if any.
+    ///
+    /// The part of the file that the server has already received is kept, so uploading the
+    /// same file again resumes where it stopped.
+    pub fn cancel(&self) {
+        if let Some(handle) = self.abort.lock().or_poisoned().take() {
+            handle.abort();
+            self.pending.set(false);
+        }
+    }
+
+    /// How much of the current file the server has received.
+    pub fn progress(&self) -> ArcReadSignal<UploadStatus> {
+        self.progress.read_only()
+    }
+
+    /// Whether an upload is in progress.
+    pub fn pending(&self) -> ArcReadSignal<bool> {
+        self.pending.read_only()
+    }
+
+    /// The result of the most recent upload that finished, either with the final status of
+    /// the file or with an error.
+    pub fn value(
+        &self,
+    ) -> ArcReadSignal<Option<Result<UploadStatus, ServerFnError<S::Error>>>>
+    {
+        self.value.read_only()
+    }
+}
@@ -1,4 +1,6 @@
 pub use super::{form::*, link::*};
+#[cfg(not(feature = "ssr"))]
+use crate::location::HashUrl;
 #[cfg(feature = "ssr")]
 use crate::location::RequestUrl;
 pub use crate::nested_router::Outlet;
@@ -6,15 +8,25 @@ use crate::{
     flat_router::FlatRoutesView,
     hooks::use_navigate,
     location::{
-        BrowserUrl, Location, LocationChange, LocationProvider, State, Url,
+        BrowserUrl, ClientLocation, Location, LocationChange, LocationProvider,
+        MemoryUrl, State, Url,
     },
-    navigate::NavigateOptions,
-    nested_router::NestedRoutesView,
+    navigate::{NavigateOptions, NavigationBlockers},
+    nested_router::{prefetch_route, NestedRoutesView},
+    params::ParamsMap,
     resolve_path::resolve_path,
-    ChooseView, MatchNestedRoutes, NestedRoute, RouteDefs, SsrMode,
+    ChooseView, MatchNestedRoutes, NestedRoute, RouteDefs, RouteLoader,
+    SsrMode,
 };
+use any_spawner::Executor;
 use either_of::EitherOf3;
-use leptos::{children, prelude::*};
+use futures::future::join_all;
+use leptos::{
+    children,
+    prelude::*,
+    server_fn::redirect::{resolve_redirect, RedirectHook},
+};
+use or_poisoned::OrPoisoned;
 use reactive_graph::{
     owner::{provide_context, use_context, Owner},
     signal::ArcRwSignal,
@@ -25,7 +37,7 @@ use std::{
     borrow::Cow,
     fmt::{Debug, Display},
     mem,
-    sync::Arc,
+    sync::{Arc, Mutex},
     time::Duration,
 };
 use tachys::view::any_view::AnyView;
@@ -59,6 +71,15 @@ pub fn Router<Chil>(
     /// A signal that will be set while the navigation process is underway.
     #[prop(optional, into)]
     set_is_routing: Option<SignalSetter<bool>>,
+    /// If `true`, the route is kept in the hash of the URL (`/#/users/1`) rather than its path,
+    /// so that the app can be served from a single page by a static host. See
+    /// [`HashUrl`](crate::location::HashUrl). Defaults to `false`.
+    #[prop(optional)]
+    hash: bool,
+    /// If set, the router keeps its history in memory rather than in the browser's URL, which
+    /// allows it to run without a browser. See [`MemoryUrl`].
+    #[prop(optional)]
+    memory: Option<MemoryUrl>,
     // TODO trailing slashes
     ///// How trailing slashes should be handled in [`Route`] paths.
     //#[prop(optional)]
@@ -71,27 +92,69 @@ pub fn Router<Chil>(
 where
     Chil: IntoView,
 {
-    #[cfg(feature = "ssr")]
-    let (location_provider, current_url, redirect_hook) = {
-        let req = use_context::<RequestUrl>().expect("no RequestUrl provided");
-        let parsed = req.parse().expect("could not parse RequestUrl");
-        let current_url = ArcRwSignal::new(parsed);
+    // provided before the location is initialized, so that it can check them
+    let blockers = NavigationBlockers::default();
+    provide_context(blockers.clone());
+
+    let memory = memory.map(|memory| {
+        memory.init(base.clone());
+        provide_context(memory.clone());
+        let current_url = memory.as_url().clone();
+        let redirect_base = base.clone();
+        let redirect_hook: RedirectHook = Box::new(move |loc: &str| {
+            MemoryUrl::redirect(&resolve_redirect(
+                redirect_base.as_deref().unwrap_or_default(),
+                loc,
+            ))
+        });
 
-        (None, current_url, Box::new(move |_: &str| {}))
-    };
+        (
+            Some(ClientLocation::Memory(memory)),
+            current_url,
+            redirect_hook,
+        )
+    });
 
-    #[cfg(not(feature = "ssr"))]
-    let (location_provider, current_url, redirect_hook) = {
-        let location =
-            BrowserUrl::new().expect("could not access browser navigation"); // TODO options here
-        location.init(base.clone());
-        provide_context(location.clone());
-        let current_url = location.as_url().clone();
+    #[cfg(feature = "ssr")]
+    let (location_provider, current_url, redirect_hook) = memory
+        .unwrap_or_else(|| {
+            let req =
+                use_context::<RequestUrl>().expect("no RequestUrl provided");
+            let parsed = req.parse().expect("could not parse RequestUrl");
+            let current_url = ArcRwSignal::new(parsed);
+            let redirect_hook: RedirectHook = Box::new(move |_: &str| {});
+
+            (None, current_url, redirect_hook)
+        });
 
-        let redirect_hook = Box::new(|loc: &str| BrowserUrl::redirect(loc));
+    #[cfg(not(feature = "ssr"))]
+    let (location_provider, current_url, redirect_hook) = memory
+        .unwrap_or_else(|| {
+            let location = if hash {
+                let location = HashUrl::new()
+                    .expect("could not access browser navigation");
+                provide_context(location.clone());
+                ClientLocation::Hash(location)
+            } else {
+                let location = BrowserUrl::new()
+                    .expect("could not access browser navigation"); // TODO options here
+                provide_context(location.clone());
+                ClientLocation::Browser(location)
+            };
+            location.init(base.clone());
+            let current_url = location.as_url().clone();
+
+            // relative redirects are resolved against the router's base
+            let redirect_base = base.clone();
+            let redirect_hook: RedirectHook = Box::new(move |loc: &str| {
+                BrowserUrl::redirect(&resolve_redirect(
+                    redirect_base.as_deref().unwrap_or_default(),
+                    loc,
+                ))
+            });
 
-        (Some(location), current_url, redirect_hook)
-    };
+            (Some(location), current_url, redirect_hook)
+        });
     // provide router context
     let state = ArcRwSignal::new(State::new(None));
     let location = Location::new(current_url.read_only(), state.read_only());
@@ -107,6 +170,9 @@ where
         set_is_routing,
         query_mutations: Default::default(),
         location_provider,
+        hash,
+        blockers,
+        prefetcher: Default::default(),
     });
 
     let children = children.into_inner();
@@ -122,9 +188,14 @@ pub(crate) struct RouterContext {
     pub set_is_routing: Option<SignalSetter<bool>>,
     pub query_mutations:
         ArcStoredValue<Vec<(Oco<'static, str>, Option<String>)>>,
-    pub location_provider: Option<BrowserUrl>,
+    pub location_provider: Option<ClientLocation>,
+    pub hash: bool,
+    pub blockers: NavigationBlockers,
+    pub prefetcher: ArcStoredValue<Option<Prefetcher>>,
 }
 
+type Prefetcher = Arc<dyn Fn(&Url) + Send + Sync>;
+
 impl RouterContext {
     pub fn navigate(&self, path: &str, options: NavigateOptions) {
         let current = self.current_url.read_untracked();
@@ -139,7 +210,8 @@ impl RouterContext {
             resolve_path("", path, None)
         };
 
-        let mut url = match resolved_to.map(|to| BrowserUrl::parse(&to)) {
+        let parsed = resolved_to.map(|to| self.parse(&to, current.origin()));
+        let mut url = match parsed {
             Some(Ok(url)) => url,
             Some(Err(e)) => {
                 leptos::logging::error!("Error parsing URL: {e:?}");
@@ -168,10 +240,38 @@ impl RouterContext {
         }
 
         if url.origin() != current.origin() {
-            window().location().set_href(path).unwrap();
+            if matches!(self.location_provider, Some(ClientLocation::Memory(_)))
+            {
+                leptos::logging::error!(
+                    "A router that keeps its history in memory can't navigate \
+                     to another origin: {path}"
+                );
+            } else {
+                window().location().set_href(path).unwrap();
+            }
             return;
         }
+        drop(current);
 
+        if self.blockers.is_blocking() {
+            let this = self.clone();
+            let change = LocationChange {
+                value: url.to_full_path(),
+                replace: options.replace,
+                scroll: options.scroll,
+                state: options.state.clone(),
+            };
+            Executor::spawn_local(async move {
+                if this.blockers.allows(&change).await {
+                    this.complete_navigation(url, options);
+                }
+            });
+        } else {
+            self.complete_navigation(url, options);
+        }
+    }
+
+    fn complete_navigation(&self, url: Url, options: NavigateOptions) {
         // update state signal, if necessary
         if options.state != self.state.get_untracked() {
             self.state.set(options.state.clone());
@@ -179,8 +279,7 @@ impl RouterContext {
 
         // update URL signal, if necessary
         let value = url.to_full_path();
-        if current != url {
-            drop(current);
+        if *self.current_url.read_untracked() != url {
             self.current_url.set(url);
         }
 
@@ -194,6 +293,64 @@ impl RouterContext {
         }
     }
 
+    fn parse(&self, to: &str, origin: &str) -> Result<Url, String> {
+        match &self.location_provider {
+            // a memory router may not be running in a browser
+            Some(ClientLocation::Memory(_)) => {
+                MemoryUrl::parse_with_base(to, origin)
+                    .map_err(|e| format!("{e:?}"))
+            }
+            _ => BrowserUrl::parse(to).map_err(|e| format!("{e:?}")),
+        }
+    }
+
+    /// Prefetches the route at `path`, which has already been resolved, if it is matched by the
+    /// [`Routes`] or [`FlatRoutes`] of this router.
+    pub fn prefetch(&self, path: &str) {
+        let Some(prefetcher) = self.prefetcher.get_value() else {
+            return;
+        };
+        let origin = self.current_url.read_untracked().origin().to_string();
+        match self.parse(path, &origin) {
+            Ok(url) => prefetcher(&url),
+            Err(e) => leptos::logging::error!("Error parsing URL: {e:?}"),
+        }
+    }
+
+    fn set_prefetcher<Defs>(&self, routes: RouteDefs<Defs>, owner: Owner)
+    where
+        Defs: MatchNestedRoutes + Send + 'static,
+    {
+        let routes = Mutex::new(routes);
+        let prefetcher: Prefetcher = Arc::new(move |url: &Url| {
+            let Some(route) =
+                routes.lock().or_poisoned().match_route(url.path())
+            else {
+                return;
+            };
+            let owner = owner.child();
+            let mut preloads = Vec::new();
+            prefetch_route(
+                route,
+                url,
+                ParamsMap::default(),
+                String::new(),
+                &owner,
+                &mut preloads,
+            );
+            Executor::spawn_local(async move {
+                join_all(preloads).await;
+                // the views and loaders only needed their owner while they were prefetching
+                drop(owner);
+            });
+        });
+        self.prefetcher.set_value(Some(prefetcher));
+        Owner::on_cleanup({
+            let prefetcher = self.prefetcher.clone();
+            move || prefetcher.set_value(None)
+        });
+    }
+
     pub fn resolve_path<'a>(
         &'a self,
         path: &'a str,
@@ -230,14 +387,15 @@ where
     FallbackFn: FnOnce() -> Fallback + Clone + Send + 'static,
     Fallback: IntoView + 'static,
 {
-    let location = use_context::<BrowserUrl>();
+    let router = use_context::<RouterContext>()
+        .expect("<Routes> should be used inside a <Router> component");
     let RouterContext {
         current_url,
         base,
         set_is_routing,
+        location_provider: location,
         ..
-    } = use_context()
-        .expect("<Routes> should be used inside a <Router> component");
+    } = router.clone();
     let base = base.map(|base| {
         let mut base = Oco::from(base);
         base.upgrade_inplace();
@@ -249,6 +407,7 @@ where
     );
     let outer_owner =
         Owner::current().expect("creating Routes, but no Owner was found");
+    router.set_prefetcher(routes.clone(), outer_owner.clone());
     move || {
         current_url.track();
         outer_owner.with(|| {
@@ -283,14 +442,15 @@ where
     FallbackFn: FnOnce() -> Fallback + Clone + Send + 'static,
     Fallback: IntoView + 'static,
 {
-    let location = use_context::<BrowserUrl>();
+    let router = use_context::<RouterContext>()
+        .expect("<FlatRoutes> should be used inside a <Router> component");
     let RouterContext {
         current_url,
         base,
         set_is_routing,
+        location_provider: location,
         ..
-    } = use_context()
-        .expect("<FlatRoutes> should be used inside a <Router> component");
+    } = router.clone();
 
     // TODO base
     #[allow(unused)]
@@ -306,6 +466,7 @@ where
 
     let outer_owner =
         Owner::current().expect("creating Router, but no Owner was found");
+    router.set_prefetcher(routes.clone(), outer_owner.clone());
 
     move || {
         current_url.track();
@@ -339,11 +500,15 @@ pub fn Route<Segments, View>(
     /// Defaults to out-of-order streaming.
     #[prop(optional)]
     ssr: SsrMode,
+    /// Loads data for this route, at the same time as any other matched routes, before it is
+    /// displayed. The data can be read with [`use_route_data`](crate::hooks::use_route_data).
+    #[prop(optional)]
+    loader: Option<RouteLoader>,
 ) -> NestedRoute<Segments, (), (), View>
 where
     View: ChooseView,
 {
-    NestedRoute::new(path, view).ssr_mode(ssr)
+    NestedRoute::new(path, view).ssr_mode(ssr).loader(loader)
 }
 
 /// Describes a portion of the nested layout of the app, specifying the route it should match
@@ -363,12 +528,19 @@ pub fn ParentRoute<Segments, View, Children>(
     /// Defaults to out-of-order streaming.
     #[prop(optional)]
     ssr: SsrMode,
+    /// Loads data for this route, at the same time as its matched child routes, before it is
+    /// displayed. The data can be read with [`use_route_data`](crate::hooks::use_route_data).
+    #[prop(optional)]
+    loader: Option<RouteLoader>,
 ) -> NestedRoute<Segments, Children, (), View>
 where
     View: ChooseView,
 {
     let children = children.into_inner();
-    NestedRoute::new(path, view).ssr_mode(ssr).child(children)
+    NestedRoute::new(path, view)
+        .ssr_mode(ssr)
+        .loader(loader)
+        .child(children)
 }
 
 /// Describes a route that is guarded by a certain condition. This works the same way as
@@ -4,7 +4,18 @@ use crate::{
     location::{BrowserUrl, LocationProvider},
     NavigateOptions,
 };
-use leptos::{ev, html::form, logging::*, prelude::*, task::spawn_local};
+#[cfg(feature = "csrf")]
+use leptos::csrf::csrf_field;
+use leptos::{
+    ev,
+    form::FormValidation,
+    html::form,
+    logging::*,
+    prelude::*,
+    server::ServerActionError,
+    server_fn::error::{NoCustomError, ServerFnErrorSerde, ValidationErrors},
+    task::spawn_local,
+};
 use std::{error::Error, sync::Arc};
 use wasm_bindgen::{JsCast, UnwrapThrowExt};
 use web_sys::{FormData, RequestRedirect, Response};
@@ -13,8 +24,48 @@ type OnFormData = Arc<dyn Fn(&FormData)>;
 type OnResponse = Arc<dyn Fn(&Response)>;
 type OnError = Arc<dyn Fn(&gloo_net::Error)>;
 
+// sends the CSRF token in a header, because it can't be read from a multipart body
+#[cfg(feature = "csrf")]
+fn with_csrf_header(
+    req: gloo_net::http::RequestBuilder,
+) -> gloo_net::http::RequestBuilder {
+    match leptos::server_fn::csrf::client_token() {
+        Some(token) => req.header(leptos::server_fn::csrf::CSRF_HEADER, &token),
+        None => req,
+    }
+}
+
+#[cfg(not(feature = "csrf"))]
+fn with_csrf_header(
+    req: gloo_net::http::RequestBuilder,
+) -> gloo_net::http::RequestBuilder {
+    req
+}
+
+#[cfg(not(feature = "csrf"))]
+fn csrf_field() {}
+
+// a server function that rejects the submission responds with the errors in its body
+async fn validation_errors(resp: &Response) -> Option<ValidationErrors> {
+    if resp.ok() {
+        return None;
+    }
+    let body = gloo_net::http::Response::from(resp.clone().ok()?)
+        .text()
+        .await
+        .ok()?;
+    match ServerFnError::<NoCustomError>::de(&body) {
+        ServerFnError::Validation(errors) => Some(errors),
+        _ => None,
+    }
+}
+
 /// An HTML [`form`](https://developer.mozilla.org/en-US/docs/Web/HTML/Element/form) progressively
 /// enhanced to use client-side routing.
+///
+/// If the form posts to a server function that fails with [`ServerFnError::Validation`], the
+/// errors for each field are available to its children through
+/// [`use_form_validation`](leptos::form::use_form_validation).
 #[component]
 pub fn Form<A>(
     /// [`method`](https://developer.mozilla.org/en-US/docs/Web/HTML/Element/form#attr-method)
@@ -62,7 +113,7 @@ where
         action: &str,
         form_data: FormData,
     ) -> Result<gloo_net::http::Response, gloo_net::Error> {
-        gloo_net::http::Request::post(action)
+        with_csrf_header(gloo_net::http::Request::post(action))
             .header("Accept", "application/json")
             .redirect(RequestRedirect::Follow)
             .body(form_data)?
@@ -75,7 +126,7 @@ where
         enctype: &str,
         params: web_sys::UrlSearchParams,
     ) -> Result<gloo_net::http::Response, gloo_net::Error> {
-        gloo_net::http::Request::post(action)
+        with_csrf_header(gloo_net::http::Request::post(action))
             .header("Accept", "application/json")
             .header("Content-Type", enctype)
             .redirect(RequestRedirect::Follow)
@@ -94,10 +145,12 @@ where
         on_form_data: Option<OnFormData>,
         on_response: Option<OnResponse>,
         on_error: Option<OnError>,
+        validation: RwSignal<Option<ValidationErrors>>,
         children: Children,
         noscroll: bool,
         replace: bool,
     ) -> impl IntoView {
+        provide_context(FormValidation::new(validation));
         let action_version = version;
         let navigate = has_router.then(use_navigate);
         let on_submit = {
@@ -146,6 +199,8 @@ where
                             }
                             Ok(resp) => {
                                 let resp = web_sys::Response::from(resp);
+                                validation
+                                    .try_set(validation_errors(&resp).await);
                                 if let Some(version) = action_version {
                                     version.update(|n| *n += 1);
                                 }
@@ -222,6 +277,8 @@ where
                             }
                             Ok(resp) => {
                                 let resp = web_sys::Response::from(resp);
+                                validation
+                                    .try_set(validation_errors(&resp).await);
                                 if let Some(version) = action_version {
                                     version.update(|n| *n += 1);
                                 }
@@ -298,13 +355,15 @@ where
         };
 
         let method = method.unwrap_or("get");
+        // the token would end up in the URL of a GET submission
+        let csrf_field = (method == "post").then(csrf_field);
 
         form()
             .attr("method", method)
             .attr("action", move || action.get())
             .attr("enctype", enctype)
             .on(ev::submit, on_submit)
-            .child(children())
+            .child((csrf_field, children()))
     }
 
     let has_router = has_router();
@@ -313,6 +372,24 @@ where
     } else {
         ArcMemo::new(move |_| Some(action.to_href()()))
     };
+    // errors from a submission without JS/WASM are passed back in the URL
+    let validation = RwSignal::new(
+        use_context::<ServerActionError>()
+            .filter(|error| {
+                action.with_untracked(|action| {
+                    action
+                        .as_deref()
+                        .and_then(|action| action.split('?').next())
+                        == Some(error.path())
+                })
+            })
+            .and_then(|error| {
+                match ServerFnError::<NoCustomError>::de(error.err()) {
+                    ServerFnError::Validation(errors) => Some(errors),
+                    _ => None,
+                }
+            }),
+    );
     inner(
         has_router,
         method,
@@ -323,6 +400,7 @@ where
         on_form_data,
         on_response,
         on_error,
+        validation,
         children,
         noscroll,
         replace,
@@ -1,18 +1,22 @@
 use crate::{
     components::RouterContext,
-    location::{Location, Url},
+    location::{Location, LocationChange, Url},
+    matching::loader::RouteData,
     navigate::NavigateOptions,
     params::{Params, ParamsError, ParamsMap},
 };
-use leptos::{leptos_dom::helpers::request_animation_frame, oco::Oco};
+use leptos::{
+    leptos_dom::helpers::request_animation_frame, oco::Oco, server::Resource,
+};
 use reactive_graph::{
     computed::{ArcMemo, Memo},
-    owner::{expect_context, use_context},
+    owner::{expect_context, on_cleanup, use_context},
     signal::{ArcRwSignal, ReadSignal},
     traits::{Get, GetUntracked, ReadUntracked, With, WriteValue},
     wrappers::write::SignalSetter,
 };
 use std::{
+    future::Future,
     str::FromStr,
     sync::atomic::{AtomicBool, Ordering},
 };
@@ -278,6 +282,71 @@ pub fn use_navigate() -> impl Fn(&str, NavigateOptions) + Clone {
     move |path: &str, options: NavigateOptions| cx.navigate(path, options)
 }
 
+/// Blocks navigations away from the current page while `when` returns `true`, so that unsaved
+/// changes aren't lost.
+///
+/// While it is blocking, each navigation made by the router (by clicking a link, submitting a
+/// [`Form`](crate::components::Form), calling the function returned by [`use_navigate`], or
+/// using the browser's back and forward buttons) is passed to `confirm`, and only continues if
+/// it resolves to `true`. `confirm` can be async, so it can wait for the user to answer a
+/// dialog. Leaving the app entirely, by reloading or closing the page or following a link to
+/// another site, shows the browser's own prompt instead, because browsers don't allow pages to
+/// delay it.
+///
+/// ```rust,ignore
+/// #[component]
+/// fn EditUser() -> impl IntoView {
+///     let (dirty, set_dirty) = signal(false);
+///     use_navigation_blocker(
+///         move || dirty.get(),
+///         |change: LocationChange| async move {
+///             window()
+///                 .confirm_with_message(&format!(
+///                     "Leave for {} without saving?",
+///                     change.value
+///                 ))
+///                 .unwrap_or(false)
+///         },
+///     );
+///     // ...
+/// }
+/// ```
+///
+/// The blocker is removed when the component that called this is unmounted.
+#[track_caller]
+pub fn use_navigation_blocker<F, Fut>(
+    when: impl Fn() -> bool + Send + Sync + 'static,
+    confirm: F,
+) where
+    F: Fn(LocationChange) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = bool> + 'static,
+{
+    let RouterContext { blockers, .. } = use_context()
+        .expect("You cannot call `use_navigation_blocker` outside a <Router>.");
+    let id = blockers.add(when, confirm);
+    on_cleanup(move || blockers.remove(id));
+}
+
+/// Returns the data loaded by the [`RouteLoader`](crate::RouteLoader) of the current route, or
+/// of the closest parent route whose loader returns a `T`.
+///
+/// On the client, the data has already loaded by the time the route is displayed after a
+/// navigation. During server rendering and hydration, it should be read inside a
+/// `<Suspense/>`.
+#[track_caller]
+pub fn use_route_data<T>() -> Resource<T>
+where
+    T: Send + Sync + 'static,
+{
+    use_context::<RouteData<T>>()
+        .expect(
+            "Tried to access route data outside a <Route> with a loader that \
+             returns this type.",
+        )
+        .0
+        .into()
+}
+
 /// Returns a reactive string that contains the route that was matched for
 /// this [`Route`](crate::components::Route).
 #[track_caller]
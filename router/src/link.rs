@@ -1,7 +1,24 @@
-use crate::{components::RouterContext, hooks::use_resolved_path};
-use leptos::{children::Children, oco::Oco, prelude::*};
-use reactive_graph::{computed::ArcMemo, owner::use_context};
-use std::{borrow::Cow, rc::Rc};
+use crate::{
+    components::RouterContext,
+    hooks::{use_navigate, use_resolved_path},
+    location::ClientLocation,
+    NavigateOptions,
+};
+use js_sys::Array;
+use leptos::{children::Children, ev::MouseEvent, oco::Oco, prelude::*};
+use or_poisoned::OrPoisoned;
+use reactive_graph::{
+    computed::ArcMemo,
+    owner::{on_cleanup, use_context},
+};
+use send_wrapper::SendWrapper;
+use std::{
+    borrow::Cow,
+    rc::Rc,
+    sync::{Arc, Mutex},
+};
+use wasm_bindgen::{closure::Closure, JsCast};
+use web_sys::{IntersectionObserver, IntersectionObserverEntry};
 
 /// Describes a value that is either a static or a reactive URL, i.e.,
 /// a [`String`], a [`&str`], or a reactive `Fn() -> String`.
@@ -55,6 +72,25 @@ where
     }
 }
 
+/// When an [`A`] should prefetch the route it links to, so that following it is instant.
+///
+/// Prefetching a route loads the code of any [lazy routes](crate::Lazy) it matches, and starts
+/// their [loaders](crate::RouteLoader), whose data is then used by the next navigation to it.
+/// It only happens in the browser, and each link prefetches its route once.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Prefetch {
+    /// Never prefetch the route.
+    #[default]
+    Never,
+    /// Prefetch the route when the user shows that they might follow the link, by hovering
+    /// over it, focusing it, or starting to touch it.
+    Intent,
+    /// Prefetch the route once the link is scrolled into view.
+    Visible,
+    /// Prefetch the route as soon as the link is displayed.
+    Eager,
+}
+
 /// An HTML [`a`](https://developer.mozilla.org/en-US/docs/Web/HTML/Element/a)
 /// progressively enhanced to use client-side routing.
 ///
@@ -76,6 +112,13 @@ where
 /// Previously, this component took these as component props. Now, they can be added using the
 /// `prop:` syntax, and will be added directly to the DOM. They can work with either `<a>` elements
 /// or the `<A/>` component.
+///
+/// When the [`Router`](crate::components::Router) keeps the route in the hash of the URL, the
+/// link's `href` points to the route's hash (`#/users/1`), so that it still works when it is
+/// opened in a new tab.
+///
+/// Setting `prefetch` starts loading the route it links to before it is followed; see
+/// [`Prefetch`].
 #[component]
 pub fn A<H>(
     /// Used to calculate the link's `href` attribute. Will be resolved relative
@@ -95,6 +138,9 @@ pub fn A<H>(
     /// If `true`, the router will scroll to the top of the window at the end of navigation. Defaults to `true`.
     #[prop(default = true)]
     scroll: bool,
+    /// When to prefetch the route the link points to. Defaults to [`Prefetch::Never`].
+    #[prop(optional)]
+    prefetch: Prefetch,
     /// The nodes or elements to be shown inside the link.
     children: Children,
 ) -> impl IntoView
@@ -108,9 +154,16 @@ where
         children: Children,
         strict_trailing_slash: bool,
         scroll: bool,
+        prefetch: Prefetch,
     ) -> impl IntoView {
-        let RouterContext { current_url, .. } =
-            use_context().expect("tried to use <A/> outside a <Router/>.");
+        let router = use_context::<RouterContext>()
+            .expect("tried to use <A/> outside a <Router/>.");
+        let RouterContext {
+            current_url,
+            hash,
+            location_provider,
+            ..
+        } = router.clone();
         let is_active = {
             let href = href.clone();
             move || {
@@ -128,12 +181,108 @@ where
             }
         };
 
+        // a router that keeps its history in memory doesn't listen for clicks on the whole
+        // page, so its links navigate by themselves
+        let memory =
+            matches!(location_provider, Some(ClientLocation::Memory(_)));
+        let on_click = {
+            let href = href.clone();
+            let has_target = target.is_some();
+            let navigate = use_navigate();
+            move |ev: MouseEvent| {
+                if !memory
+                    || has_target
+                    || ev.default_prevented()
+                    || ev.button() != 0
+                    || ev.meta_key()
+                    || ev.alt_key()
+                    || ev.ctrl_key()
+                    || ev.shift_key()
+                {
+                    return;
+                }
+                if let Some(href) = href.get_untracked() {
+                    ev.prevent_default();
+                    navigate(
+                        &href,
+                        NavigateOptions {
+                            resolve: false,
+                            scroll,
+                            ..Default::default()
+                        },
+                    );
+                }
+            }
+        };
+
+        let node_ref = NodeRef::<leptos::html::A>::new();
+        let prefetch_route = {
+            let href = href.clone();
+            let prefetched = Arc::new(Mutex::new(None::<String>));
+            move || {
+                let Some(href) = href.get_untracked() else {
+                    return;
+                };
+                let mut prefetched = prefetched.lock().or_poisoned();
+                if prefetched.as_deref() != Some(href.as_str()) {
+                    router.prefetch(&href);
+                    *prefetched = Some(href);
+                }
+            }
+        };
+        let on_intent = {
+            let prefetch_route = prefetch_route.clone();
+            move || {
+                if prefetch == Prefetch::Intent {
+                    prefetch_route();
+                }
+            }
+        };
+        match prefetch {
+            Prefetch::Eager => {
+                let href = href.clone();
+                let prefetch_route = prefetch_route.clone();
+                Effect::new(move |_| {
+                    href.track();
+                    prefetch_route();
+                });
+            }
+            Prefetch::Visible => {
+                Effect::new(move |_| {
+                    if let Some(a) = node_ref.get() {
+                        prefetch_when_visible(&a, prefetch_route.clone());
+                    }
+                });
+            }
+            Prefetch::Never | Prefetch::Intent => {}
+        }
+
         view! {
             <a
-                href=move || href.get().unwrap_or_default()
+                node_ref=node_ref
+                href=move || {
+                    let href = href.get().unwrap_or_default();
+                    // a hash router only loads the page from a single path, so a link that
+                    // is opened in a new tab has to point to the route in the hash
+                    if hash && href.starts_with('/') {
+                        format!("#{href}")
+                    } else {
+                        href
+                    }
+                }
                 target=target
                 aria-current=move || if is_active() { Some("page") } else { None }
                 data-noscroll=!scroll
+                on:click=on_click
+                on:mouseenter={
+                    let on_intent = on_intent.clone();
+                    move |_| on_intent()
+                }
+                on:focus={
+                    let on_intent = on_intent.clone();
+                    move |_| on_intent()
+                }
+                on:touchstart=move |_| on_intent()
             >
 
                 {children()}
@@ -142,7 +291,42 @@ where
     }
 
     let href = use_resolved_path(move || href.to_href()());
-    inner(href, target, exact, children, strict_trailing_slash, scroll)
+    inner(
+        href,
+        target,
+        exact,
+        children,
+        strict_trailing_slash,
+        scroll,
+        prefetch,
+    )
+}
+
+// calls `prefetch` the first time `el` intersects the viewport, until the effect that called
+// this is cleaned up
+fn prefetch_when_visible(el: &web_sys::Element, prefetch: impl Fn() + 'static) {
+    let on_intersect = Closure::wrap(Box::new(
+        move |entries: Array, observer: IntersectionObserver| {
+            let visible = entries.iter().any(|entry| {
+                entry
+                    .unchecked_into::<IntersectionObserverEntry>()
+                    .is_intersecting()
+            });
+            if visible {
+                observer.disconnect();
+                prefetch();
+            }
+        },
+    )
+        as Box<dyn Fn(Array, IntersectionObserver)>);
+    let Ok(observer) =
+        IntersectionObserver::new(on_intersect.as_ref().unchecked_ref())
+    else {
+        return;
+    };
+    observer.observe(el);
+    let observer = SendWrapper::new((observer, on_intersect));
+    on_cleanup(move || observer.0.disconnect());
 }
 
 // Test if `href` is active for `location`.  Assumes _both_ `href` and `location` begin with a `'/'`.
@@ -0,0 +1,197 @@
+use super::{
+    block_unload, guard_history_navigation, handle_anchor_click,
+    is_navigating_back, navigate_from_click, push_history, BrowserUrl,
+    LocationChange, LocationProvider, Url,
+};
+use core::fmt;
+use futures::channel::oneshot;
+use leptos::prelude::*;
+use or_poisoned::OrPoisoned;
+use reactive_graph::{
+    signal::ArcRwSignal,
+    traits::{ReadUntracked, Set},
+};
+use std::{
+    borrow::Cow,
+    sync::{Arc, Mutex},
+};
+use tachys::dom::window;
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use web_sys::Event;
+
+/// A location provider that keeps the current route in the hash of the URL, as in
+/// `/#/users/1?tab=posts`, rather than in its path.
+///
+/// The page itself is always loaded from the same path, so the app can be served by a static
+/// host or an embedded webview that can't serve it from every route. Use it by setting
+/// `hash` on the [`Router`](crate::components::Router).
+///
+/// Links can point either to the route (`/users/1`) or to its hash (`#/users/1`). Because the
+/// hash holds the route, it can't also be used to scroll to an element on the page.
+#[derive(Clone)]
+pub struct HashUrl {
+    url: ArcRwSignal<Url>,
+    pub(crate) pending_navigation: Arc<Mutex<Option<oneshot::Sender<()>>>>,
+    pub(crate) path_stack: ArcStoredValue<Vec<Url>>,
+    pub(crate) is_back: ArcRwSignal<bool>,
+}
+
+impl fmt::Debug for HashUrl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HashUrl").finish_non_exhaustive()
+    }
+}
+
+impl LocationProvider for HashUrl {
+    type Error = JsValue;
+
+    fn new() -> Result<Self, JsValue> {
+        let url = ArcRwSignal::new(Self::current()?);
+        let path_stack = ArcStoredValue::new(
+            Self::current().map(|n| vec![n]).unwrap_or_default(),
+        );
+        Ok(Self {
+            url,
+            pending_navigation: Default::default(),
+            path_stack,
+            is_back: Default::default(),
+        })
+    }
+
+    fn as_url(&self) -> &ArcRwSignal<Url> {
+        &self.url
+    }
+
+    fn current() -> Result<Url, Self::Error> {
+        let location = window().location();
+        let hash = location.hash()?;
+        // a hash that isn't a route (like `#top`) is treated as the root route
+        let route = hash
+            .strip_prefix('#')
+            .filter(|route| route.starts_with('/'))
+            .unwrap_or("/");
+        BrowserUrl::parse_with_base(route, &location.origin()?)
+    }
+
+    fn parse(url: &str) -> Result<Url, Self::Error> {
+        let base = window().location().origin()?;
+        Self::parse_with_base(url, &base)
+    }
+
+    fn parse_with_base(url: &str, base: &str) -> Result<Url, Self::Error> {
+        let url = BrowserUrl::parse_with_base(url, base)?;
+        // a link to `#/route` goes to the route in its hash, rather than to its path
+        match url.hash().strip_prefix('#') {
+            Some(route) if route.starts_with('/') => {
+                BrowserUrl::parse_with_base(route, url.origin())
+            }
+            _ => Ok(url),
+        }
+    }
+
+    fn init(&self, base: Option<Cow<'static, str>>) {
+        let window = window();
+        let navigate = {
+            let this = self.clone();
+            move |new_url: Url, loc| {
+                navigate_from_click(
+                    &this,
+                    &this.pending_navigation,
+                    new_url,
+                    loc,
+                )
+            }
+        };
+
+        let handle_anchor_click =
+            handle_anchor_click(base, Self::parse_with_base, navigate);
+        let closure = Closure::wrap(Box::new(move |ev: Event| {
+            if let Err(e) = handle_anchor_click(ev) {
+                #[cfg(feature = "tracing")]
+                tracing::error!("{e:?}");
+                #[cfg(not(feature = "tracing"))]
+                web_sys::console::error_1(&e);
+            }
+        }) as Box<dyn FnMut(Event)>)
+        .into_js_value();
+        window
+            .add_event_listener_with_callback(
+                "click",
+                closure.as_ref().unchecked_ref(),
+            )
+            .expect(
+                "couldn't add `click` listener to `window` to handle `<a>` \
+                 clicks",
+            );
+
+        // handle hashchange event (forward/back navigation, or the hash being edited)
+        let cb = {
+            let url = self.url.clone();
+            let path_stack = self.path_stack.clone();
+            let is_back = self.is_back.clone();
+            move || match Self::current() {
+                Ok(new_url) => {
+                    // navigating with `complete_navigation` doesn't fire `hashchange`, so
+                    // this is either a history navigation or a change made by the user
+                    if new_url == *url.read_untracked() {
+                        return;
+                    }
+                    let is_navigating_back =
+                        is_navigating_back(&path_stack.read_value(), &new_url);
+
+                    is_back.set(is_navigating_back);
+
+                    url.set(new_url);
+                }
+                Err(e) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::error!("{e:?}");
+                    #[cfg(not(feature = "tracing"))]
+                    web_sys::console::error_1(&e);
+                }
+            }
+        };
+        let to = || Some(Self::current().ok()?.to_full_path());
+        let cb = guard_history_navigation(to, cb);
+        let closure =
+            Closure::wrap(Box::new(cb) as Box<dyn Fn()>).into_js_value();
+        window
+            .add_event_listener_with_callback(
+                "hashchange",
+                closure.as_ref().unchecked_ref(),
+            )
+            .expect("couldn't add `hashchange` listener to `window`");
+
+        block_unload();
+    }
+
+    fn ready_to_complete(&self) {
+        if let Some(tx) = self.pending_navigation.lock().or_poisoned().take() {
+            _ = tx.send(());
+        }
+    }
+
+    fn complete_navigation(&self, loc: &LocationChange) {
+        push_history(&format!("#{}", loc.value), loc);
+
+        // add this URL to the "path stack" for detecting back navigations, and
+        // unset "navigating back" state
+        if let Ok(url) = Self::current() {
+            self.path_stack.write_value().push(url);
+            self.is_back.set(false);
+        }
+
+        if loc.scroll {
+            window().scroll_to_with_x_and_y(0.0, 0.0);
+        }
+    }
+
+    fn redirect(loc: &str) {
+        // navigating through the router already updates the hash
+        BrowserUrl::redirect(loc)
+    }
+
+    fn is_back(&self) -> ReadSignal<bool> {
+        self.is_back.read_only().into()
+    }
+}
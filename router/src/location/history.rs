@@ -1,4 +1,7 @@
-use super::{handle_anchor_click, LocationChange, LocationProvider, Url};
+use super::{
+    block_unload, guard_history_navigation, handle_anchor_click,
+    is_navigating_back, push_history, LocationChange, LocationProvider, Url,
+};
 use crate::{hooks::use_navigate, params::ParamsMap};
 use core::fmt;
 use futures::channel::oneshot;
@@ -12,6 +15,7 @@ use reactive_graph::{
 use std::{
     borrow::Cow,
     boxed::Box,
+    future::Future,
     string::String,
     sync::{Arc, Mutex},
 };
@@ -118,42 +122,14 @@ impl LocationProvider for BrowserUrl {
     fn init(&self, base: Option<Cow<'static, str>>) {
         let window = window();
         let navigate = {
-            let url = self.url.clone();
-            let pending = Arc::clone(&self.pending_navigation);
             let this = self.clone();
             move |new_url: Url, loc| {
-                let same_path = {
-                    let curr = url.read_untracked();
-                    curr.origin() == new_url.origin()
-                        && curr.path() == new_url.path()
-                };
-
-                url.set(new_url.clone());
-                if same_path {
-                    this.complete_navigation(&loc);
-                }
-                let pending = Arc::clone(&pending);
-                let (tx, rx) = oneshot::channel::<()>();
-                if !same_path {
-                    *pending.lock().or_poisoned() = Some(tx);
-                }
-                let url = url.clone();
-                let this = this.clone();
-                async move {
-                    if !same_path {
-                        // if it has been canceled, ignore
-                        // otherwise, complete navigation -- i.e., set URL in address bar
-                        if rx.await.is_ok() {
-                            // only update the URL in the browser if this is still the current URL
-                            // if we've navigated to another page in the meantime, don't update the
-                            // browser URL
-                            let curr = url.read_untracked();
-                            if curr == new_url {
-                                this.complete_navigation(&loc);
-                            }
-                        }
-                    }
-                }
+                navigate_from_click(
+                    &this,
+                    &this.pending_navigation,
+                    new_url,
+                    loc,
+                )
             }
         };
 
@@ -185,10 +161,8 @@ impl LocationProvider for BrowserUrl {
             let is_back = self.is_back.clone();
             move || match Self::current() {
                 Ok(new_url) => {
-                    let stack = path_stack.read_value();
-                    let is_navigating_back = stack.len() == 1
-                        || (stack.len() >= 2
-                            && stack.get(stack.len() - 2) == Some(&new_url));
+                    let is_navigating_back =
+                        is_navigating_back(&path_stack.read_value(), &new_url);
 
                     is_back.set(is_navigating_back);
 
@@ -202,6 +176,8 @@ impl LocationProvider for BrowserUrl {
                 }
             }
         };
+        let to = || Some(Self::current().ok()?.to_full_path());
+        let cb = guard_history_navigation(to, cb);
         let closure =
             Closure::wrap(Box::new(cb) as Box<dyn Fn()>).into_js_value();
         window
@@ -210,6 +186,8 @@ impl LocationProvider for BrowserUrl {
                 closure.as_ref().unchecked_ref(),
             )
             .expect("couldn't add `popstate` listener to `window`");
+
+        block_unload();
     }
 
     fn ready_to_complete(&self) {
@@ -219,23 +197,7 @@ impl LocationProvider for BrowserUrl {
     }
 
     fn complete_navigation(&self, loc: &LocationChange) {
-        let history = window().history().unwrap();
-
-        if loc.replace {
-            history
-                .replace_state_with_url(
-                    &loc.state.to_js_value(),
-                    "",
-                    Some(&loc.value),
-                )
-                .unwrap();
-        } else {
-            // push the "forward direction" marker
-            let state = &loc.state.to_js_value();
-            history
-                .push_state_with_url(state, "", Some(&loc.value))
-                .unwrap();
-        }
+        push_history(&loc.value, loc);
 
         // add this URL to the "path stack" for detecting back navigations, and
         // unset "navigating back" state
@@ -271,6 +233,46 @@ impl LocationProvider for BrowserUrl {
     }
 }
 
+/// Sets the URL to `new_url` after a click on a link, and returns a future that updates the
+/// browser's URL once the navigation is complete.
+pub(crate) fn navigate_from_click<L: LocationProvider>(
+    this: &L,
+    pending: &Arc<Mutex<Option<oneshot::Sender<()>>>>,
+    new_url: Url,
+    loc: LocationChange,
+) -> impl Future<Output = ()> {
+    let url = this.as_url().clone();
+    let same_path = {
+        let curr = url.read_untracked();
+        curr.origin() == new_url.origin() && curr.path() == new_url.path()
+    };
+
+    url.set(new_url.clone());
+    if same_path {
+        this.complete_navigation(&loc);
+    }
+    let (tx, rx) = oneshot::channel::<()>();
+    if !same_path {
+        *pending.lock().or_poisoned() = Some(tx);
+    }
+    let this = this.clone();
+    async move {
+        if !same_path {
+            // if it has been canceled, ignore
+            // otherwise, complete navigation -- i.e., set URL in address bar
+            if rx.await.is_ok() {
+                // only update the URL in the browser if this is still the current URL
+                // if we've navigated to another page in the meantime, don't update the
+                // browser URL
+                let curr = url.read_untracked();
+                if curr == new_url {
+                    this.complete_navigation(&loc);
+                }
+            }
+        }
+    }
+}
+
 fn search_params_from_web_url(
     params: &web_sys::UrlSearchParams,
 ) -> Result<ParamsMap, JsValue> {
@@ -0,0 +1,290 @@
+use super::{LocationChange, LocationProvider, Url};
+use crate::{hooks::use_navigate, params::ParamsMap};
+use core::fmt;
+use or_poisoned::OrPoisoned;
+use reactive_graph::{
+    signal::{ArcRwSignal, ReadSignal},
+    traits::Set,
+};
+use std::{
+    borrow::Cow,
+    sync::{Arc, Mutex},
+};
+
+/// A location provider that keeps its history in memory, rather than in the browser's URL.
+///
+/// It doesn't need a browser, so it can be used to test routed components with `cargo test`,
+/// in desktop shells that have no address bar, or to give a widget its own router that runs
+/// alongside the one for the page. Use it by passing it to the
+/// [`Router`](crate::components::Router) as `memory`:
+///
+/// ```rust,ignore
+/// let memory = MemoryUrl::with_entries(["/", "/users", "/users/1"], 1).unwrap();
+/// view! {
+///     <Router memory=memory.clone()>
+///         // ...
+///     </Router>
+/// }
+///
+/// // later
+/// memory.forward();
+/// assert_eq!(memory.entries()[memory.index()], "/users/1");
+/// ```
+///
+/// Navigations made through the router, or by clicking an [`A`](crate::components::A), are
+/// added to the history as they would be in the browser.
+#[derive(Clone)]
+pub struct MemoryUrl {
+    url: ArcRwSignal<Url>,
+    history: Arc<Mutex<MemoryHistory>>,
+    is_back: ArcRwSignal<bool>,
+}
+
+#[derive(Debug)]
+struct MemoryHistory {
+    entries: Vec<String>,
+    index: usize,
+}
+
+impl fmt::Debug for MemoryUrl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MemoryUrl")
+            .field("history", &self.history)
+            .finish_non_exhaustive()
+    }
+}
+
+impl MemoryUrl {
+    /// Creates a history with the given entries, starting at the entry at `index`.
+    ///
+    /// An empty history starts at `/`, and an `index` past the end starts at the last entry.
+    pub fn with_entries<I>(
+        entries: I,
+        index: usize,
+    ) -> Result<Self, url::ParseError>
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        let mut entries =
+            entries.into_iter().map(Into::into).collect::<Vec<_>>();
+        if entries.is_empty() {
+            entries.push("/".to_string());
+        }
+        let index = index.min(entries.len() - 1);
+        let url = Self::parse(&entries[index])?;
+        Ok(Self {
+            url: ArcRwSignal::new(url),
+            history: Arc::new(Mutex::new(MemoryHistory { entries, index })),
+            is_back: Default::default(),
+        })
+    }
+
+    /// The entries in the history, oldest first.
+    pub fn entries(&self) -> Vec<String> {
+        self.history.lock().or_poisoned().entries.clone()
+    }
+
+    /// The position of the current entry in [`entries`](Self::entries).
+    pub fn index(&self) -> usize {
+        self.history.lock().or_poisoned().index
+    }
+
+    /// Whether there is an entry before the current one.
+    pub fn can_go_back(&self) -> bool {
+        self.index() > 0
+    }
+
+    /// Whether there is an entry after the current one.
+    pub fn can_go_forward(&self) -> bool {
+        let history = self.history.lock().or_poisoned();
+        history.index + 1 < history.entries.len()
+    }
+
+    /// Goes to the previous entry, if any.
+    pub fn back(&self) {
+        self.go(-1);
+    }
+
+    /// Goes to the next entry, if any.
+    pub fn forward(&self) {
+        self.go(1);
+    }
+
+    /// Moves `delta` entries back (if negative) or forward (if positive) in the history,
+    /// stopping at its first or last entry.
+    pub fn go(&self, delta: isize) {
+        let entry = {
+            let mut history = self.history.lock().or_poisoned();
+            let last = history.entries.len() - 1;
+            let index = history.index.saturating_add_signed(delta).min(last);
+            if index == history.index {
+                return;
+            }
+            history.index = index;
+            history.entries[index].clone()
+        };
+        match Self::parse(&entry) {
+            Ok(url) => {
+                self.is_back.set(delta < 0);
+                self.url.set(url);
+            }
+            Err(e) => {
+                leptos::logging::error!("Error parsing URL {entry:?}: {e:?}");
+            }
+        }
+    }
+}
+
+impl LocationProvider for MemoryUrl {
+    type Error = url::ParseError;
+
+    fn new() -> Result<Self, Self::Error> {
+        Self::with_entries(["/"], 0)
+    }
+
+    fn as_url(&self) -> &ArcRwSignal<Url> {
+        &self.url
+    }
+
+    /// There is no global location in memory, so this is always `/`. The current location of a
+    /// particular history is in [`as_url`](Self::as_url).
+    fn current() -> Result<Url, Self::Error> {
+        Self::parse("/")
+    }
+
+    fn init(&self, _base: Option<Cow<'static, str>>) {}
+
+    fn ready_to_complete(&self) {}
+
+    fn complete_navigation(&self, loc: &LocationChange) {
+        let mut history = self.history.lock().or_poisoned();
+        let index = history.index;
+        if loc.replace {
+            history.entries[index] = loc.value.clone();
+        } else {
+            history.entries.truncate(index + 1);
+            history.entries.push(loc.value.clone());
+            history.index += 1;
+        }
+        drop(history);
+        self.is_back.set(false);
+    }
+
+    fn parse_with_base(url: &str, base: &str) -> Result<Url, Self::Error> {
+        let base = url::Url::parse(base)?;
+        let url = url::Url::options().base_url(Some(&base)).parse(url)?;
+
+        let search_params = url
+            .query_pairs()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect::<ParamsMap>();
+
+        Ok(Url {
+            origin: url.origin().unicode_serialization(),
+            path: url.path().to_string(),
+            search: url.query().unwrap_or_default().to_string(),
+            search_params,
+            hash: url
+                .fragment()
+                .map(|hash| format!("#{hash}"))
+                .unwrap_or_default(),
+        })
+    }
+
+    fn redirect(loc: &str) {
+        let navigate = use_navigate();
+        navigate(loc, Default::default());
+    }
+
+    fn is_back(&self) -> ReadSignal<bool> {
+        self.is_back.read_only().into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MemoryUrl;
+    use crate::location::{LocationChange, LocationProvider};
+    use reactive_graph::traits::GetUntracked;
+
+    fn push(memory: &MemoryUrl, path: &str) {
+        memory.complete_navigation(&LocationChange {
+            value: path.to_string(),
+            replace: false,
+            ..Default::default()
+        });
+    }
+
+    fn current_path(memory: &MemoryUrl) -> String {
+        memory.as_url().get_untracked().path().to_string()
+    }
+
+    #[test]
+    fn starts_at_the_given_entry() {
+        let memory = MemoryUrl::with_entries(["/", "/a", "/b"], 1).unwrap();
+        assert_eq!(memory.index(), 1);
+        assert_eq!(current_path(&memory), "/a");
+        assert!(memory.can_go_back());
+        assert!(memory.can_go_forward());
+    }
+
+    #[test]
+    fn empty_history_starts_at_root() {
+        let memory = MemoryUrl::with_entries(Vec::<String>::new(), 3).unwrap();
+        assert_eq!(memory.entries(), vec!["/"]);
+        assert_eq!(memory.index(), 0);
+        assert!(!memory.can_go_back());
+        assert!(!memory.can_go_forward());
+    }
+
+    #[test]
+    fn back_and_forward_move_through_entries() {
+        let memory = MemoryUrl::with_entries(["/", "/a", "/b"], 2).unwrap();
+        memory.back();
+        assert_eq!(current_path(&memory), "/a");
+        memory.back();
+        assert_eq!(current_path(&memory), "/");
+        memory.back();
+        assert_eq!(memory.index(), 0);
+        memory.forward();
+        assert_eq!(current_path(&memory), "/a");
+        memory.go(10);
+        assert_eq!(current_path(&memory), "/b");
+        memory.go(-10);
+        assert_eq!(current_path(&memory), "/");
+    }
+
+    #[test]
+    fn navigating_drops_forward_entries() {
+        let memory = MemoryUrl::with_entries(["/", "/a", "/b"], 2).unwrap();
+        memory.go(-2);
+        push(&memory, "/c");
+        assert_eq!(memory.entries(), vec!["/", "/c"]);
+        assert_eq!(memory.index(), 1);
+        assert!(!memory.can_go_forward());
+    }
+
+    #[test]
+    fn replacing_keeps_the_length() {
+        let memory = MemoryUrl::with_entries(["/", "/a"], 1).unwrap();
+        memory.complete_navigation(&LocationChange {
+            value: "/b?x=1".to_string(),
+            replace: true,
+            ..Default::default()
+        });
+        assert_eq!(memory.entries(), vec!["/", "/b?x=1"]);
+        assert_eq!(memory.index(), 1);
+    }
+
+    // query params are decoded by calling into JS, except when rendering on the server
+    #[cfg(feature = "ssr")]
+    #[test]
+    fn parses_query_and_hash() {
+        let url = MemoryUrl::parse("/users/1?tab=posts#latest").unwrap();
+        assert_eq!(url.path(), "/users/1");
+        assert_eq!(url.search(), "tab=posts");
+        assert_eq!(url.search_params().get_str("tab"), Some("posts"));
+        assert_eq!(url.hash(), "#latest");
+    }
+}
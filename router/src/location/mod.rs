@@ -1,25 +1,29 @@
 #![allow(missing_docs)]
 
 use any_spawner::Executor;
-use core::fmt::Debug;
+use core::{cell::Cell, fmt::Debug};
 use js_sys::Reflect;
 use leptos::server::ServerActionError;
 use reactive_graph::{
     computed::Memo,
-    owner::provide_context,
+    owner::{provide_context, use_context},
     signal::{ArcRwSignal, ReadSignal},
     traits::With,
 };
 use send_wrapper::SendWrapper;
-use std::{borrow::Cow, future::Future};
+use std::{borrow::Cow, future::Future, rc::Rc};
 use tachys::dom::window;
-use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
 use web_sys::{Event, HtmlAnchorElement, MouseEvent};
 
+mod hash;
 mod history;
+mod memory;
 mod server;
-use crate::params::ParamsMap;
+use crate::{navigate::NavigationBlockers, params::ParamsMap};
+pub use hash::*;
 pub use history::*;
+pub use memory::*;
 pub use server::*;
 
 pub(crate) const BASE: &str = "https://leptos.dev";
@@ -234,6 +238,80 @@ pub trait LocationProvider: Clone + 'static {
     fn is_back(&self) -> ReadSignal<bool>;
 }
 
+/// The location provider used by a `<Router/>` outside of server rendering, which keeps the
+/// route in the path of the URL, in its hash, or in memory.
+///
+/// Only the methods that take `self` tell the two apart; the associated functions use
+/// [`BrowserUrl`].
+#[derive(Debug, Clone)]
+pub(crate) enum ClientLocation {
+    Browser(BrowserUrl),
+    // only a `<Router hash=true/>` rendering in the browser creates this
+    #[cfg_attr(feature = "ssr", allow(dead_code))]
+    Hash(HashUrl),
+    Memory(MemoryUrl),
+}
+
+impl LocationProvider for ClientLocation {
+    type Error = JsValue;
+
+    fn new() -> Result<Self, Self::Error> {
+        BrowserUrl::new().map(Self::Browser)
+    }
+
+    fn as_url(&self) -> &ArcRwSignal<Url> {
+        match self {
+            Self::Browser(location) => location.as_url(),
+            Self::Hash(location) => location.as_url(),
+            Self::Memory(location) => location.as_url(),
+        }
+    }
+
+    fn current() -> Result<Url, Self::Error> {
+        BrowserUrl::current()
+    }
+
+    fn init(&self, base: Option<Cow<'static, str>>) {
+        match self {
+            Self::Browser(location) => location.init(base),
+            Self::Hash(location) => location.init(base),
+            Self::Memory(location) => location.init(base),
+        }
+    }
+
+    fn ready_to_complete(&self) {
+        match self {
+            Self::Browser(location) => location.ready_to_complete(),
+            Self::Hash(location) => location.ready_to_complete(),
+            Self::Memory(location) => location.ready_to_complete(),
+        }
+    }
+
+    fn complete_navigation(&self, loc: &LocationChange) {
+        match self {
+            Self::Browser(location) => location.complete_navigation(loc),
+            Self::Hash(location) => location.complete_navigation(loc),
+            Self::Memory(location) => location.complete_navigation(loc),
+        }
+    }
+
+    fn parse_with_base(url: &str, base: &str) -> Result<Url, Self::Error> {
+        BrowserUrl::parse_with_base(url, base)
+    }
+
+    fn redirect(loc: &str) {
+        BrowserUrl::redirect(loc)
+    }
+
+    fn is_back(&self) -> ReadSignal<bool> {
+        match self {
+            Self::Browser(location) => location.is_back(),
+            Self::Hash(location) => location.is_back(),
+            Self::Memory(location) => location.is_back(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct State(Option<SendWrapper<JsValue>>);
 
@@ -276,6 +354,8 @@ where
     NavFut: Future<Output = ()> + 'static,
 {
     let router_base = router_base.unwrap_or_default();
+    let navigate = Rc::new(navigate);
+    let blockers = use_context::<NavigationBlockers>().unwrap_or_default();
 
     Box::new(move |ev: Event| {
         let ev = ev.unchecked_into::<MouseEvent>();
@@ -364,9 +444,180 @@ where
                 state: State::new(state),
             };
 
-            Executor::spawn_local(navigate(url, change));
+            if blockers.is_blocking() {
+                let blockers = blockers.clone();
+                let navigate = Rc::clone(&navigate);
+                Executor::spawn_local(async move {
+                    if blockers.allows(&change).await {
+                        navigate(url, change).await;
+                    }
+                });
+            } else {
+                Executor::spawn_local(navigate(url, change));
+            }
         }
 
         Ok(())
     })
 }
+
+/// Whether a history navigation to `new_url` went back, judging by the URLs visited so far.
+pub(crate) fn is_navigating_back(path_stack: &[Url], new_url: &Url) -> bool {
+    path_stack.len() == 1
+        || (path_stack.len() >= 2
+            && path_stack.get(path_stack.len() - 2) == Some(new_url))
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum HistoryGuard {
+    Check,
+    Ignore,
+    Allow,
+}
+
+thread_local! {
+    // the position of the current entry in the history, counted from where the app started
+    static HISTORY_INDEX: Cell<i32> = const { Cell::new(0) };
+}
+
+/// The value stored as `history.state` for each entry the router adds to the history, which
+/// keeps the position of the entry along with the state it was navigated to with, so that a
+/// history navigation can tell how far it went.
+fn history_state(index: i32, state: &JsValue) -> JsValue {
+    let value = js_sys::Object::new();
+    _ = Reflect::set(&value, &JsValue::from_str("index"), &index.into());
+    _ = Reflect::set(&value, &JsValue::from_str("state"), state);
+    value.into()
+}
+
+fn history_index(state: &JsValue) -> Option<i32> {
+    if !state.is_object() {
+        return None;
+    }
+    Reflect::get(state, &JsValue::from_str("index"))
+        .ok()?
+        .as_f64()
+        .map(|index| index as i32)
+}
+
+/// Adds an entry for `url` to the history, or replaces the current one, keeping track of its
+/// position.
+pub(crate) fn push_history(url: &str, loc: &LocationChange) {
+    let history = window().history().unwrap();
+    let index = HISTORY_INDEX.get() + i32::from(!loc.replace);
+    let state = history_state(index, &loc.state.to_js_value());
+    if loc.replace {
+        history
+            .replace_state_with_url(&state, "", Some(url))
+            .unwrap();
+    } else {
+        history.push_state_with_url(&state, "", Some(url)).unwrap();
+    }
+    HISTORY_INDEX.set(index);
+}
+
+// the position of the entry the browser is now on, which is marked as the entry after the
+// previous one if it was added by the browser rather than the router (like a change to the hash
+// made by the user)
+fn current_history_index() -> i32 {
+    let history = window().history().unwrap();
+    let state = history.state().unwrap_or(JsValue::NULL);
+    history_index(&state).unwrap_or_else(|| {
+        let index = HISTORY_INDEX.get() + 1;
+        _ = history.replace_state(&history_state(index, &state), "");
+        index
+    })
+}
+
+/// Wraps the handler for a history navigation (made with the back or forward button), so
+/// that it is only handled once any navigation blockers have allowed it.
+///
+/// The browser has already moved to the new entry when it tells us about it, so while the
+/// blockers decide, the navigation is undone by going back the other way, by as many entries
+/// as it moved according to the positions kept in `history.state`. If they allow it, the
+/// navigation is made again.
+///
+/// `to` returns the new location.
+pub(crate) fn guard_history_navigation(
+    to: impl Fn() -> Option<String> + 'static,
+    handler: impl Fn() + 'static,
+) -> impl Fn() + 'static {
+    // the entry the app starts on keeps its position if the page is reloaded
+    let history = window().history().unwrap();
+    let state = history.state().unwrap_or(JsValue::NULL);
+    match history_index(&state) {
+        Some(index) => HISTORY_INDEX.set(index),
+        None => {
+            _ = history.replace_state(&history_state(0, &state), "");
+        }
+    }
+
+    let blockers = use_context::<NavigationBlockers>().unwrap_or_default();
+    let guard = Rc::new(Cell::new(HistoryGuard::Check));
+    move || {
+        let index = current_history_index();
+        match guard.replace(HistoryGuard::Check) {
+            // undoing a navigation goes back to the entry that is already current
+            HistoryGuard::Ignore => return,
+            HistoryGuard::Allow => {
+                HISTORY_INDEX.set(index);
+                return handler();
+            }
+            HistoryGuard::Check => {}
+        }
+        let delta = index - HISTORY_INDEX.get();
+        if delta == 0 || !blockers.is_blocking() {
+            HISTORY_INDEX.set(index);
+            return handler();
+        }
+        let Some(value) = to() else {
+            HISTORY_INDEX.set(index);
+            return handler();
+        };
+
+        let history = window().history().unwrap();
+        guard.set(HistoryGuard::Ignore);
+        _ = history.go_with_delta(-delta);
+
+        let change = LocationChange {
+            value,
+            replace: false,
+            scroll: true,
+            state: State::new(None),
+        };
+        let blockers = blockers.clone();
+        let guard = Rc::clone(&guard);
+        Executor::spawn_local(async move {
+            if blockers.allows(&change).await {
+                guard.set(HistoryGuard::Allow);
+                _ = history.go_with_delta(delta);
+            }
+        });
+    }
+}
+
+/// Asks the browser to confirm before the page is closed or reloaded, or a link to another
+/// site is followed, while any navigation blocker is blocking.
+///
+/// Browsers only show their own message, so the blockers themselves are not called.
+pub(crate) fn block_unload() {
+    let blockers = use_context::<NavigationBlockers>().unwrap_or_default();
+    let closure = Closure::wrap(Box::new(move |ev: Event| {
+        if blockers.is_blocking() {
+            ev.prevent_default();
+            // some browsers only show the prompt if `returnValue` is set
+            _ = Reflect::set(
+                &ev,
+                &JsValue::from_str("returnValue"),
+                &JsValue::from_str(""),
+            );
+        }
+    }) as Box<dyn FnMut(Event)>)
+    .into_js_value();
+    window()
+        .add_event_listener_with_callback(
+            "beforeunload",
+            closure.as_ref().unchecked_ref(),
+        )
+        .expect("couldn't add `beforeunload` listener to `window`");
+}
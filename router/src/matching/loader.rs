@@ -0,0 +1,467 @@
+use super::ChooseView;
+use crate::{location::Url, params::ParamsMap};
+use any_spawner::Executor;
+use core::fmt;
+use futures::{channel::oneshot, future::join};
+use leptos::server::ArcResource;
+use or_poisoned::OrPoisoned;
+use reactive_graph::{
+    computed::ArcMemo,
+    graph::untrack,
+    owner::{provide_context, use_context, Owner},
+    signal::ArcRwSignal,
+    traits::{Get, With},
+};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+};
+use tachys::view::any_view::AnyView;
+
+type LoadFn = dyn Fn() -> Pin<Box<dyn Future<Output = ()>>> + Send + Sync;
+
+/// Loads the data for a route, given to a [`Route`](crate::components::Route) or
+/// [`ParentRoute`](crate::components::ParentRoute) as its `loader`.
+///
+/// When a navigation matches new routes, the loaders of all of them run at the same time, and
+/// the new views are only shown once every one of them has finished. This avoids the waterfall
+/// of a nested layout in which each level only starts to load its data once its parent has
+/// rendered. The data is read in the route's view with
+/// [`use_route_data`](crate::hooks::use_route_data):
+///
+/// ```rust,ignore
+/// async fn load_user(params: ParamsMap, _query: ParamsMap) -> Option<User> {
+///     get_user(params.get("id")?.parse().ok()?).await.ok()
+/// }
+///
+/// #[component]
+/// fn UserPage() -> impl IntoView {
+///     let user = use_route_data::<Option<User>>();
+///     view! {
+///         <Suspense>
+///             {move || Suspend::new(async move { user.await.map(|user| user.name) })}
+///         </Suspense>
+///     }
+/// }
+///
+/// view! {
+///     <Route
+///         path=path!("/users/:id")
+///         view=UserPage
+///         loader=RouteLoader::new(load_user)
+///     />
+/// }
+/// ```
+///
+/// The data is stored in a resource, so it is loaded on the server during server rendering,
+/// serialized to the browser, and reused while hydrating, in the same way as any other resource.
+/// On the server and while hydrating the view is rendered without waiting for it, so it should
+/// be read inside a `<Suspense/>`. If the params or query change without matching a different
+/// route, the data reloads in place.
+///
+/// A link that [prefetches](crate::components::Prefetch) its route starts the loader early, and
+/// the data it loads is used by the next navigation to the same params and query, as long as
+/// it happens within 30 seconds.
+#[derive(Clone)]
+pub struct RouteLoader(Arc<LoadFn>);
+
+impl RouteLoader {
+    /// Creates a loader from a function that is called with the params matched by the route
+    /// (including those of its parents) and the query of the URL.
+    pub fn new<T, Fut>(
+        loader: impl Fn(ParamsMap, ParamsMap) -> Fut + Send + Sync + 'static,
+    ) -> Self
+    where
+        T: Serialize + DeserializeOwned + Send + Sync + 'static,
+        Fut: Future<Output = T> + Send + 'static,
+    {
+        let loader = Arc::new(loader);
+        let prefetched =
+            Arc::new(Mutex::new(PrefetchCache::<oneshot::Receiver<T>>::new()));
+        Self(Arc::new(move || {
+            let params = use_context::<ArcMemo<ParamsMap>>();
+            let url = use_context::<ArcRwSignal<Url>>();
+            let key = move || {
+                let params = params.as_ref().map(Get::get);
+                let query = url
+                    .as_ref()
+                    .map(|url| url.with(|url| url.search_params().clone()));
+                (params.unwrap_or_default(), query.unwrap_or_default())
+            };
+
+            if use_context::<Prefetching>().is_some() {
+                let key = untrack(key);
+                let mut prefetched = prefetched.lock().or_poisoned();
+                if !prefetched.contains(&key, now()) {
+                    let (tx, data) = oneshot::channel();
+                    let load = loader(key.0.clone(), key.1.clone());
+                    Executor::spawn(async move {
+                        _ = tx.send(load.await);
+                    });
+                    prefetched.insert(key, data, now());
+                }
+                return Box::pin(async {});
+            }
+
+            let loader = Arc::clone(&loader);
+            let prefetched = Arc::clone(&prefetched);
+            let data = ArcResource::new(key, move |key| {
+                let data = prefetched.lock().or_poisoned().take(&key, now());
+                let loader = Arc::clone(&loader);
+                async move {
+                    match data {
+                        Some(data) => match data.await {
+                            Ok(data) => data,
+                            Err(_) => loader(key.0, key.1).await,
+                        },
+                        None => loader(key.0, key.1).await,
+                    }
+                }
+            });
+            provide_context(RouteData(data.clone()));
+
+            let wait = waits_for_data();
+            Box::pin(async move {
+                if wait {
+                    _ = data.by_ref().await;
+                }
+            })
+        }))
+    }
+}
+
+// prefetched data is only kept until it is used by a navigation, but links that are hovered
+// and never followed would otherwise pile up
+const MAX_PREFETCHED: usize = 8;
+
+// data prefetched long before the navigation that uses it may be out of date
+const PREFETCH_TTL_MS: f64 = 30_000.0;
+
+type LoaderKey = (ParamsMap, ParamsMap);
+
+/// The data prefetched by a loader, keyed by the params and query it was loaded for.
+struct PrefetchCache<D> {
+    entries: Vec<Prefetched<D>>,
+}
+
+struct Prefetched<D> {
+    key: LoaderKey,
+    data: D,
+    loaded_at: f64,
+}
+
+impl<D> PrefetchCache<D> {
+    fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+
+    fn expire(&mut self, now: f64) {
+        self.entries
+            .retain(|entry| now - entry.loaded_at < PREFETCH_TTL_MS);
+    }
+
+    fn contains(&mut self, key: &LoaderKey, now: f64) -> bool {
+        self.expire(now);
+        self.entries.iter().any(|entry| entry.key == *key)
+    }
+
+    /// Adds the data for `key`, dropping the oldest entry if the cache is full.
+    fn insert(&mut self, key: LoaderKey, data: D, now: f64) {
+        self.expire(now);
+        if self.entries.len() >= MAX_PREFETCHED {
+            self.entries.remove(0);
+        }
+        self.entries.push(Prefetched {
+            key,
+            data,
+            loaded_at: now,
+        });
+    }
+
+    /// Removes and returns the data for `key`, if it was prefetched recently enough.
+    fn take(&mut self, key: &LoaderKey, now: f64) -> Option<D> {
+        self.expire(now);
+        let index = self.entries.iter().position(|entry| entry.key == *key)?;
+        Some(self.entries.remove(index).data)
+    }
+}
+
+// milliseconds since some fixed point in the past
+fn now() -> f64 {
+    #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+    {
+        js_sys::Date::now()
+    }
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    {
+        use std::{sync::OnceLock, time::Instant};
+
+        static START: OnceLock<Instant> = OnceLock::new();
+        START.get_or_init(Instant::now).elapsed().as_secs_f64() * 1000.0
+    }
+}
+
+/// Provided via context while a route is being prefetched, so that its loader starts loading
+/// the data for the next navigation instead of providing it to a view.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Prefetching;
+
+impl fmt::Debug for RouteLoader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RouteLoader").finish_non_exhaustive()
+    }
+}
+
+impl PartialEq for RouteLoader {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for RouteLoader {}
+
+// on the server, and while hydrating, routes are rendered synchronously, and the data is
+// streamed in or deserialized by the resource instead
+fn waits_for_data() -> bool {
+    Owner::current_shared_context()
+        .map(|sc| sc.is_browser() && !sc.during_hydration())
+        .unwrap_or(true)
+}
+
+/// The data loaded by a route's [`RouteLoader`], provided via context to its view.
+pub(crate) struct RouteData<T>(pub ArcResource<T>)
+where
+    T: Send + Sync + 'static;
+
+impl<T> Clone for RouteData<T>
+where
+    T: Send + Sync + 'static,
+{
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+/// A route's view along with its loader, which runs before the view is chosen.
+#[derive(Clone)]
+pub(crate) struct WithLoader<View> {
+    view: View,
+    loader: Option<RouteLoader>,
+    // shared between the clones made for the same match, so the loader only runs once
+    started: Arc<AtomicBool>,
+}
+
+impl<View> WithLoader<View> {
+    pub fn new(view: View, loader: Option<RouteLoader>) -> Self {
+        Self {
+            view,
+            loader,
+            started: Default::default(),
+        }
+    }
+
+    async fn load(&self) {
+        if let Some(loader) = &self.loader {
+            if !self.started.swap(true, Ordering::Relaxed) {
+                (loader.0)().await;
+            }
+        }
+    }
+}
+
+impl<View> ChooseView for WithLoader<View>
+where
+    View: ChooseView,
+{
+    async fn choose(self) -> AnyView {
+        self.load().await;
+        self.view.choose().await
+    }
+
+    async fn preload(&self) {
+        join(self.load(), self.view.preload()).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::future::join_all;
+    use reactive_graph::computed::ScopedFuture;
+    use std::{sync::atomic::AtomicUsize, time::Duration};
+    use tokio::{sync::Barrier, time::timeout};
+
+    fn init() -> Owner {
+        _ = Executor::init_tokio();
+        let owner = Owner::new();
+        owner.set();
+        owner
+    }
+
+    // a loader that returns the `id` param, counting how many times it has run
+    fn id_loader(runs: &Arc<AtomicUsize>) -> RouteLoader {
+        let runs = Arc::clone(runs);
+        RouteLoader::new(move |params: ParamsMap, _| {
+            runs.fetch_add(1, Ordering::SeqCst);
+            async move { params.get("id") }
+        })
+    }
+
+    // params are decoded by calling into JS, except when rendering on the server, so the tests
+    // of the prefetch cache only run with `ssr`
+    #[cfg(feature = "ssr")]
+    fn key(id: &str, query: &[(&'static str, &str)]) -> LoaderKey {
+        (
+            [("id", id)].into_iter().collect(),
+            query.iter().copied().collect(),
+        )
+    }
+
+    #[cfg(feature = "ssr")]
+    #[test]
+    fn prefetched_data_matches_params_and_query() {
+        let mut cache = PrefetchCache::new();
+        cache.insert(key("1", &[]), "user 1", 0.0);
+        cache.insert(key("1", &[("tab", "posts")]), "user 1 posts", 0.0);
+        assert_eq!(cache.take(&key("2", &[]), 0.0), None);
+        assert_eq!(cache.take(&key("1", &[("tab", "likes")]), 0.0), None);
+        assert_eq!(
+            cache.take(&key("1", &[("tab", "posts")]), 0.0),
+            Some("user 1 posts")
+        );
+        assert_eq!(cache.take(&key("1", &[]), 0.0), Some("user 1"));
+        // each prefetch is used by one navigation
+        assert_eq!(cache.take(&key("1", &[]), 0.0), None);
+    }
+
+    #[cfg(feature = "ssr")]
+    #[test]
+    fn drops_the_oldest_prefetch_when_full() {
+        let mut cache = PrefetchCache::new();
+        for id in 0..=MAX_PREFETCHED {
+            cache.insert(key(&id.to_string(), &[]), id, 0.0);
+        }
+        assert_eq!(cache.entries.len(), MAX_PREFETCHED);
+        assert_eq!(cache.take(&key("0", &[]), 0.0), None);
+        assert_eq!(cache.take(&key("1", &[]), 0.0), Some(1));
+        assert_eq!(
+            cache.take(&key(&MAX_PREFETCHED.to_string(), &[]), 0.0),
+            Some(MAX_PREFETCHED)
+        );
+    }
+
+    #[cfg(feature = "ssr")]
+    #[test]
+    fn prefetched_data_expires() {
+        let mut cache = PrefetchCache::new();
+        cache.insert(key("1", &[]), 1, 0.0);
+        cache.insert(key("2", &[]), 2, 10_000.0);
+        assert!(cache.contains(&key("1", &[]), PREFETCH_TTL_MS - 1.0));
+        assert_eq!(cache.take(&key("1", &[]), PREFETCH_TTL_MS), None);
+        assert_eq!(cache.take(&key("2", &[]), PREFETCH_TTL_MS), Some(2));
+    }
+
+    #[tokio::test]
+    async fn loads_once_per_match() {
+        let _owner = init();
+        let runs = Arc::new(AtomicUsize::new(0));
+        let loader = id_loader(&runs);
+
+        // the router preloads a match, then chooses its view from a clone
+        let view = WithLoader::new((), Some(loader.clone()));
+        view.preload().await;
+        view.clone().preload().await;
+        _ = view.clone().choose().await;
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+
+        // a new match of the same route loads again
+        WithLoader::new((), Some(loader)).preload().await;
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn nested_loaders_run_in_parallel() {
+        let _owner = init();
+        // each level only finishes once every level has started, so this can only complete
+        // if none of them waits for another
+        let levels = 3;
+        let barrier = Arc::new(Barrier::new(levels));
+        let views = (0..levels)
+            .map(|level| {
+                let barrier = Arc::clone(&barrier);
+                let loader = RouteLoader::new(move |_, _| {
+                    let barrier = Arc::clone(&barrier);
+                    async move {
+                        barrier.wait().await;
+                        level
+                    }
+                });
+                WithLoader::new((), Some(loader))
+            })
+            .collect::<Vec<_>>();
+
+        // as the nested router does, each level is preloaded in its own owner
+        let preloads = views.iter().map(|view| {
+            Owner::new().with(|| ScopedFuture::new(view.preload()))
+        });
+        timeout(Duration::from_secs(5), join_all(preloads))
+            .await
+            .expect("nested loaders waited for each other");
+    }
+
+    // params are decoded by calling into JS, except when rendering on the server
+    #[cfg(feature = "ssr")]
+    #[tokio::test]
+    async fn reloads_when_params_change() {
+        use crate::location::{LocationChange, LocationProvider, MemoryUrl};
+        use reactive_graph::traits::Set;
+
+        // sets the URL and adds it to the history, as the router does when navigating
+        fn navigate(memory: &MemoryUrl, path: &str) {
+            memory.as_url().set(MemoryUrl::parse(path).unwrap());
+            memory.complete_navigation(&LocationChange {
+                value: path.to_string(),
+                ..Default::default()
+            });
+        }
+
+        let _owner = init();
+        let memory = MemoryUrl::with_entries(["/users/1"], 0).unwrap();
+        let url = memory.as_url().clone();
+        // the params the router would match for `/users/:id`
+        let params = ArcMemo::new({
+            let url = url.clone();
+            move |_| {
+                let id = url.with(|url| {
+                    url.path().trim_start_matches("/users/").to_string()
+                });
+                [("id", id)].into_iter().collect::<ParamsMap>()
+            }
+        });
+        provide_context(params);
+        provide_context(url.clone());
+
+        let runs = Arc::new(AtomicUsize::new(0));
+        WithLoader::new((), Some(id_loader(&runs))).preload().await;
+        let data = use_context::<RouteData<Option<String>>>().unwrap().0;
+        assert_eq!(data.clone().await.as_deref(), Some("1"));
+
+        navigate(&memory, "/users/2");
+        Executor::tick().await;
+        assert_eq!(data.clone().await.as_deref(), Some("2"));
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+
+        // a change to the URL that leaves the params and query alone doesn't reload
+        navigate(&memory, "/users/2#bio");
+        Executor::tick().await;
+        assert_eq!(data.await.as_deref(), Some("2"));
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+    }
+}
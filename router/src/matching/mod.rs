@@ -1,9 +1,11 @@
 #![allow(missing_docs)]
 
 mod choose_view;
+pub(crate) mod loader;
 mod path_segment;
 pub(crate) mod resolve_path;
 pub use choose_view::*;
+pub use loader::RouteLoader;
 pub use path_segment::*;
 mod horizontal;
 mod nested;
@@ -2,7 +2,10 @@ use super::{
     MatchInterface, MatchNestedRoutes, PartialPathMatch, PathSegment,
     PossibleRouteMatch, RouteMatchId,
 };
-use crate::{ChooseView, GeneratedRouteData, MatchParams, Method, SsrMode};
+use crate::{
+    matching::loader::WithLoader, ChooseView, GeneratedRouteData, MatchParams,
+    Method, RouteLoader, SsrMode,
+};
 use core::{fmt, iter};
 use either_of::Either;
 use std::{
@@ -22,6 +25,7 @@ pub struct NestedRoute<Segments, Children, Data, View> {
     children: Option<Children>,
     data: Data,
     view: View,
+    loader: Option<RouteLoader>,
     methods: HashSet<Method>,
     ssr_mode: SsrMode,
 }
@@ -41,6 +45,7 @@ where
             children: self.children.clone(),
             data: self.data.clone(),
             view: self.view.clone(),
+            loader: self.loader.clone(),
             methods: self.methods.clone(),
             ssr_mode: self.ssr_mode.clone(),
         }
@@ -58,6 +63,7 @@ impl<Segments, View> NestedRoute<Segments, (), (), View> {
             children: None,
             data: (),
             view,
+            loader: None,
             methods: [Method::Get].into(),
             ssr_mode: Default::default(),
         }
@@ -74,6 +80,7 @@ impl<Segments, Data, View> NestedRoute<Segments, (), Data, View> {
             segments,
             data,
             view,
+            loader,
             ssr_mode,
             methods,
             ..
@@ -84,15 +91,26 @@ impl<Segments, Data, View> NestedRoute<Segments, (), Data, View> {
             children: Some(child),
             data,
             view,
+            loader,
             ssr_mode,
             methods,
         }
     }
+}
 
+impl<Segments, Children, Data, View>
+    NestedRoute<Segments, Children, Data, View>
+{
     pub fn ssr_mode(mut self, ssr_mode: SsrMode) -> Self {
         self.ssr_mode = ssr_mode;
         self
     }
+
+    /// Loads data for this route before it is displayed. See [`RouteLoader`].
+    pub fn loader(mut self, loader: Option<RouteLoader>) -> Self {
+        self.loader = loader;
+        self
+    }
 }
 
 #[derive(PartialEq, Eq)]
@@ -105,6 +123,7 @@ pub struct NestedMatch<Child, View> {
     /// The nested route.
     child: Option<Child>,
     view_fn: View,
+    loader: Option<RouteLoader>,
 }
 
 impl<Child, View> fmt::Debug for NestedMatch<Child, View>
@@ -143,7 +162,7 @@ where
     }
 
     fn into_view_and_child(self) -> (impl ChooseView, Option<Self::Child>) {
-        (self.view_fn, self.child)
+        (WithLoader::new(self.view_fn, self.loader), self.child)
     }
 }
 
@@ -199,6 +218,7 @@ where
                                     params,
                                     child: inner,
                                     view_fn: self.view.clone(),
+                                    loader: self.loader.clone(),
                                 },
                             )),
                             remaining,
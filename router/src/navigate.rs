@@ -1,4 +1,14 @@
-use crate::location::State;
+use crate::location::{LocationChange, State};
+use or_poisoned::OrPoisoned;
+use reactive_graph::graph::untrack;
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
 
 /// Options that can be used to configure a navigation. Used with [use_navigate](crate::hooks::use_navigate).
 #[derive(Clone, Debug)]
@@ -26,3 +36,128 @@ impl Default for NavigateOptions {
         }
     }
 }
+
+type BlockWhen = Arc<dyn Fn() -> bool + Send + Sync>;
+type ConfirmFn = Arc<
+    dyn Fn(LocationChange) -> Pin<Box<dyn Future<Output = bool>>> + Send + Sync,
+>;
+
+struct Blocker {
+    id: usize,
+    when: BlockWhen,
+    confirm: ConfirmFn,
+}
+
+/// The navigation blockers registered with a router by
+/// [`use_navigation_blocker`](crate::hooks::use_navigation_blocker).
+#[derive(Clone, Default)]
+pub(crate) struct NavigationBlockers(Arc<Mutex<Vec<Blocker>>>);
+
+impl NavigationBlockers {
+    pub fn add<Fut>(
+        &self,
+        when: impl Fn() -> bool + Send + Sync + 'static,
+        confirm: impl Fn(LocationChange) -> Fut + Send + Sync + 'static,
+    ) -> usize
+    where
+        Fut: Future<Output = bool> + 'static,
+    {
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        let confirm: ConfirmFn = Arc::new(move |change| {
+            Box::pin(confirm(change)) as Pin<Box<dyn Future<Output = bool>>>
+        });
+        self.0.lock().or_poisoned().push(Blocker {
+            id,
+            when: Arc::new(when),
+            confirm,
+        });
+        id
+    }
+
+    pub fn remove(&self, id: usize) {
+        self.0
+            .lock()
+            .or_poisoned()
+            .retain(|blocker| blocker.id != id);
+    }
+
+    /// Whether any blocker currently wants to block navigations.
+    pub fn is_blocking(&self) -> bool {
+        !self.active().is_empty()
+    }
+
+    /// Asks each blocker that is currently blocking whether the navigation may continue,
+    /// stopping at the first that refuses.
+    pub async fn allows(&self, change: &LocationChange) -> bool {
+        for confirm in self.active() {
+            if !confirm(change.clone()).await {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn active(&self) -> Vec<ConfirmFn> {
+        // the blockers are cloned out so that one can be added or removed while another is
+        // deciding
+        let blockers = self
+            .0
+            .lock()
+            .or_poisoned()
+            .iter()
+            .map(|blocker| (blocker.when.clone(), blocker.confirm.clone()))
+            .collect::<Vec<_>>();
+        blockers
+            .into_iter()
+            .filter(|(when, _)| untrack(|| when()))
+            .map(|(_, confirm)| confirm)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NavigationBlockers;
+    use crate::location::LocationChange;
+    use futures::executor::block_on;
+    use std::sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    };
+
+    #[test]
+    fn only_blocks_while_asked_to() {
+        let blockers = NavigationBlockers::default();
+        let dirty = Arc::new(AtomicBool::new(false));
+        blockers.add(
+            {
+                let dirty = Arc::clone(&dirty);
+                move || dirty.load(Ordering::Relaxed)
+            },
+            |_| async { false },
+        );
+        assert!(!blockers.is_blocking());
+        assert!(block_on(blockers.allows(&LocationChange::default())));
+
+        dirty.store(true, Ordering::Relaxed);
+        assert!(blockers.is_blocking());
+        assert!(!block_on(blockers.allows(&LocationChange::default())));
+    }
+
+    #[test]
+    fn every_blocker_must_allow() {
+        let blockers = NavigationBlockers::default();
+        blockers.add(|| true, |change| async move { change.value == "/a" });
+        let refuse = blockers.add(|| true, |_| async { false });
+        let change = |value: &str| LocationChange {
+            value: value.to_string(),
+            ..Default::default()
+        };
+        assert!(!block_on(blockers.allows(&change("/a"))));
+
+        blockers.remove(refuse);
+        assert!(block_on(blockers.allows(&change("/a"))));
+        assert!(!block_on(blockers.allows(&change("/b"))));
+    }
+}
@@ -1,7 +1,7 @@
 use crate::{
     hooks::Matched,
     location::{LocationProvider, Url},
-    matching::RouteDefs,
+    matching::{loader::Prefetching, RouteDefs},
     params::ParamsMap,
     view_transition::start_view_transition,
     ChooseView, MatchInterface, MatchNestedRoutes, MatchParams, PathSegment,
@@ -853,6 +853,46 @@ where
     }
 }
 
+/// Preloads the views of a matched route and its children ahead of a navigation to it, and
+/// starts their loaders, each in an owner with the same context it will have once it is
+/// displayed.
+pub(crate) fn prefetch_route<Match>(
+    route: Match,
+    url: &Url,
+    parent_params: ParamsMap,
+    parent_matched: String,
+    parent: &Owner,
+    preloads: &mut Vec<Pin<Box<dyn Future<Output = ()>>>>,
+) where
+    Match: MatchInterface + MatchParams,
+{
+    let owner = parent.child();
+    let params = parent_params
+        .into_iter()
+        .chain(route.to_params())
+        .collect::<ParamsMap>();
+    let matched = parent_matched + route.as_matched();
+    let (view, child) = route.into_view_and_child();
+
+    preloads.push(Box::pin(owner.with(|| {
+        provide_context(Prefetching);
+        provide_context(ArcMemo::new({
+            let params = params.clone();
+            move |_| params.clone()
+        }));
+        provide_context(ArcRwSignal::new(url.to_owned()));
+        provide_context(Matched(ArcMemo::new({
+            let matched = matched.clone();
+            move |_| matched.clone()
+        })));
+        ScopedFuture::new(async move { view.preload().await })
+    })));
+
+    if let Some(child) = child {
+        prefetch_route(child, url, params, matched, &owner, preloads);
+    }
+}
+
 impl<Fal> Mountable for NestedRouteViewState<Fal>
 where
     Fal: Render,
@@ -0,0 +1,540 @@
+//! Sending several server function calls in a single HTTP request.
+//!
+//! A page that loads data from several server functions at once makes one round trip per call,
+//! which adds up quickly on a high-latency connection. A server function that uses
+//! [`BatchClient`] as its client instead waits until the current task yields, and sends every
+//! call made in the meantime as one `POST` request to the batch endpoint next to the server
+//! function (see [`batch_path`]):
+//!
+//! ```rust,ignore
+//! #[server(client = BatchClient<BrowserClient>)]
+//! pub async fn load_user(id: u32) -> Result<User, ServerFnError> {
+//!     todo!()
+//! }
+//!
+//! #[server(client = BatchClient<BrowserClient>)]
+//! pub async fn load_notifications() -> Result<Vec<Notification>, ServerFnError> {
+//!     todo!()
+//! }
+//!
+//! // both calls are sent in the same request
+//! let user = Resource::new(|| (), |_| load_user(1));
+//! let notifications = Resource::new(|| (), |_| load_notifications());
+//! ```
+//!
+//! On the server, the batch endpoint (`handle_batch` in the `axum` and `actix` modules, which the
+//! Leptos integrations register alongside the server functions) runs each call through the
+//! usual server function handler, with the headers of the batch request, and responds with the
+//! status, headers and body of each call in order. Each caller then decodes its own response,
+//! so errors are returned to the call that caused them. A batch holds at most
+//! [`MAX_BATCH_SIZE`] calls; the client splits larger ones, and the server refuses them.
+//!
+//! Calls are buffered in full on both sides, so server functions that stream their input or
+//! output, or that use multipart forms, can't use [`BatchClient`]. Streaming and multipart
+//! requests fail before they are sent, and calls whose response is streamed fail with a
+//! `400 Bad Request`.
+
+use crate::{
+    client::Client,
+    error::{NoCustomError, ServerFnError},
+    redirect::REDIRECT_HEADER,
+    request::ClientReq,
+    response::ClientRes,
+};
+use bytes::Bytes;
+use dashmap::DashMap;
+use futures::{channel::oneshot, stream, Stream};
+use http::{Method, StatusCode};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::{
+    any::TypeId,
+    collections::BTreeSet,
+    future::Future,
+    marker::PhantomData,
+    mem,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// The most calls that are sent in, or accepted as, a single batch.
+pub const MAX_BATCH_SIZE: usize = 64;
+
+/// Returns the path at which the server handles batches that contain a call to the server
+/// function at `path`.
+///
+/// This is `_batch` in the same directory as the server function, so `/api/get_user` is batched
+/// to `/api/_batch`, and a server function with a custom prefix is batched under that prefix.
+pub fn batch_path(path: &str) -> String {
+    let path = path.split_once('?').map_or(path, |(path, _)| path);
+    let dir = path.rsplit_once('/').map_or("", |(dir, _)| dir);
+    format!("{dir}/_batch")
+}
+
+/// Returns the batch paths for all of the given server function paths, without duplicates.
+pub fn batch_paths<'a>(
+    paths: impl IntoIterator<Item = &'a str>,
+) -> BTreeSet<String> {
+    paths.into_iter().map(batch_path).collect()
+}
+
+/// A single server function call within a batch.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchedRequest {
+    /// The HTTP method of the call.
+    pub method: String,
+    /// The path of the server function, including any query string.
+    pub path: String,
+    /// The headers of the call, including its `Content-Type` and `Accept`.
+    pub headers: Vec<(String, String)>,
+    /// The body of the call.
+    #[serde(with = "base64_body")]
+    pub body: Bytes,
+}
+
+/// The response to a single server function call within a batch.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchedResponse {
+    /// The HTTP status code of the response.
+    pub status: u16,
+    /// The headers of the response.
+    pub headers: Vec<(String, String)>,
+    /// The body of the response.
+    #[serde(with = "base64_body")]
+    pub body: Bytes,
+}
+
+impl BatchedResponse {
+    /// A response that holds an error message with the given status.
+    pub fn error(status: StatusCode, message: impl ToString) -> Self {
+        Self {
+            status: status.as_u16(),
+            headers: Vec::new(),
+            body: Bytes::from(message.to_string()),
+        }
+    }
+}
+
+// bodies may be binary, so they are base64-encoded inside the JSON that carries the batch
+mod base64_body {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use bytes::Bytes;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        body: &Bytes,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&STANDARD.encode(body))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Bytes, D::Error> {
+        let body = String::deserialize(deserializer)?;
+        STANDARD
+            .decode(body)
+            .map(Bytes::from)
+            .map_err(D::Error::custom)
+    }
+}
+
+/// A server function call that is waiting to be sent as part of a batch by [`BatchClient`].
+#[derive(Debug, Clone)]
+pub struct BatchRequest(BatchedRequest);
+
+impl BatchRequest {
+    fn new(
+        method: Method,
+        path: String,
+        accepts: &str,
+        content_type: &str,
+        body: Bytes,
+    ) -> Self {
+        Self(BatchedRequest {
+            method: method.to_string(),
+            path,
+            headers: vec![
+                ("accept".to_string(), accepts.to_string()),
+                ("content-type".to_string(), content_type.to_string()),
+            ],
+            body,
+        })
+    }
+}
+
+fn cannot_batch<CustErr>(kind: &str) -> ServerFnError<CustErr> {
+    ServerFnError::Request(format!(
+        "{kind} requests cannot be sent with BatchClient"
+    ))
+}
+
+impl<CustErr> ClientReq<CustErr> for BatchRequest {
+    type FormData = ();
+
+    fn try_new_get(
+        path: &str,
+        accepts: &str,
+        content_type: &str,
+        query: &str,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        Ok(Self::new(
+            Method::GET,
+            format!("{path}?{query}"),
+            accepts,
+            content_type,
+            Bytes::new(),
+        ))
+    }
+
+    fn try_new_post(
+        path: &str,
+        accepts: &str,
+        content_type: &str,
+        body: String,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        Ok(Self::new(
+            Method::POST,
+            path.to_string(),
+            accepts,
+            content_type,
+            Bytes::from(body),
+        ))
+    }
+
+    fn try_new_post_bytes(
+        path: &str,
+        accepts: &str,
+        content_type: &str,
+        body: Bytes,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        Ok(Self::new(
+            Method::POST,
+            path.to_string(),
+            accepts,
+            content_type,
+            body,
+        ))
+    }
+
+    fn try_new_post_form_data(
+        _path: &str,
+        _accepts: &str,
+        _content_type: &str,
+        _body: Self::FormData,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        Err(cannot_batch("form data"))
+    }
+
+    fn try_new_multipart(
+        _path: &str,
+        _accepts: &str,
+        _body: Self::FormData,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        Err(cannot_batch("multipart"))
+    }
+
+    fn try_new_streaming(
+        _path: &str,
+        _accepts: &str,
+        _content_type: &str,
+        _body: impl Stream<Item = Result<Bytes, ServerFnError>> + Send + 'static,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        Err(cannot_batch("streaming"))
+    }
+
+    fn request_url(&self) -> String {
+        format!("{}{}", crate::client::get_server_url(), self.0.path)
+    }
+
+    fn insert_header(&mut self, name: &str, value: &str) {
+        let headers = &mut self.0.headers;
+        headers.retain(|(existing, _)| !existing.eq_ignore_ascii_case(name));
+        headers.push((name.to_ascii_lowercase(), value.to_string()));
+    }
+
+    fn try_clone(&self) -> Option<Self> {
+        Some(self.clone())
+    }
+}
+
+/// The response to a single call sent by [`BatchClient`].
+#[derive(Debug, Clone)]
+pub struct BatchResponse {
+    path: String,
+    res: BatchedResponse,
+}
+
+impl BatchResponse {
+    fn header_value(&self, name: &str) -> Option<String> {
+        self.res
+            .headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.clone())
+    }
+}
+
+impl<CustErr> ClientRes<CustErr> for BatchResponse {
+    async fn try_into_string(self) -> Result<String, ServerFnError<CustErr>> {
+        String::from_utf8(self.res.body.into())
+            .map_err(|e| ServerFnError::Deserialization(e.to_string()))
+    }
+
+    async fn try_into_bytes(self) -> Result<Bytes, ServerFnError<CustErr>> {
+        Ok(self.res.body)
+    }
+
+    fn try_into_stream(
+        self,
+    ) -> Result<
+        impl Stream<Item = Result<Bytes, ServerFnError>> + Send + Sync + 'static,
+        ServerFnError<CustErr>,
+    > {
+        Ok(stream::iter([Ok(self.res.body)]))
+    }
+
+    fn status(&self) -> u16 {
+        self.res.status
+    }
+
+    fn status_text(&self) -> String {
+        StatusCode::from_u16(self.res.status)
+            .ok()
+            .and_then(|status| status.canonical_reason())
+            .unwrap_or_default()
+            .to_string()
+    }
+
+    fn location(&self) -> String {
+        self.header_value("location")
+            .unwrap_or_else(|| self.path.clone())
+    }
+
+    fn has_redirect(&self) -> bool {
+        self.header_value(REDIRECT_HEADER).is_some()
+    }
+
+    fn header(&self, name: &str) -> Option<String> {
+        self.header_value(name)
+    }
+}
+
+/// A [`Client`] that gathers the calls made in the same tick into a single request to
+/// their [`batch_path`], which it sends with the client `C`.
+///
+/// Any middleware for `C` is applied to the batch request, rather than to each call.
+/// If the call that sends a batch is dropped before the response arrives, the other calls
+/// in the batch fail with [`ServerFnError::Request`].
+///
+/// See the [module documentation](crate::batch) for more details.
+pub struct BatchClient<C>(PhantomData<fn() -> C>);
+
+struct Queued {
+    req: BatchedRequest,
+    tx: oneshot::Sender<Result<BatchedResponse, ServerFnError>>,
+}
+
+// calls waiting to be sent, for each inner client and batch path
+static QUEUES: Lazy<DashMap<(TypeId, String), Vec<Queued>>> =
+    Lazy::new(DashMap::new);
+
+impl<C, CustErr> Client<CustErr> for BatchClient<C>
+where
+    C: Client<NoCustomError> + 'static,
+{
+    type Request = BatchRequest;
+    type Response = BatchResponse;
+
+    fn send(
+        req: Self::Request,
+    ) -> impl Future<Output = Result<Self::Response, ServerFnError<CustErr>>> + Send
+    {
+        let path = req.0.path.clone();
+        let key = (TypeId::of::<C>(), batch_path(&path));
+        let (tx, rx) = oneshot::channel();
+        QUEUES
+            .entry(key.clone())
+            .or_default()
+            .push(Queued { req: req.0, tx });
+
+        async move {
+            // give every other call made in this tick the chance to join the batch; the first
+            // call to resume then sends all of them
+            YieldNow(false).await;
+            let mut queued = QUEUES
+                .get_mut(&key)
+                .map(|mut queue| mem::take(&mut *queue))
+                .unwrap_or_default();
+            let mut batches = Vec::new();
+            while !queued.is_empty() {
+                let rest = queued.split_off(queued.len().min(MAX_BATCH_SIZE));
+                batches.push(send_batch::<C>(&key.1, queued));
+                queued = rest;
+            }
+            futures::future::join_all(batches).await;
+
+            let res = rx.await.map_err(|_| {
+                ServerFnError::Request(
+                    "the batch containing this call was cancelled".into(),
+                )
+            })?;
+            res.map(|res| BatchResponse { path, res }).map_err(|e| {
+                e.map_custom_err(|e| ServerFnError::ServerError(e.to_string()))
+            })
+        }
+    }
+}
+
+async fn send_batch<C>(batch_path: &str, queued: Vec<Queued>)
+where
+    C: Client<NoCustomError> + 'static,
+{
+    let (reqs, senders): (Vec<_>, Vec<_>) = queued
+        .into_iter()
+        .map(|queued| (queued.req, queued.tx))
+        .unzip();
+
+    let res = async {
+        let body = serde_json::to_vec(&reqs).map_err(|e| {
+            ServerFnError::<NoCustomError>::Serialization(e.to_string())
+        })?;
+        let req = C::Request::try_new_post_bytes(
+            batch_path,
+            "application/json",
+            "application/json",
+            Bytes::from(body),
+        )?;
+        let res = crate::client::send::<C, NoCustomError>(req).await?;
+        let status = res.status();
+        let body = res.try_into_bytes().await?;
+        if !(200..=299).contains(&status) {
+            return Err(ServerFnError::Response(format!(
+                "the batch request failed with status {status}: {}",
+                String::from_utf8_lossy(&body)
+            )));
+        }
+        let responses: Vec<BatchedResponse> = serde_json::from_slice(&body)
+            .map_err(|e| {
+                ServerFnError::<NoCustomError>::Deserialization(e.to_string())
+            })?;
+        if responses.len() != senders.len() {
+            return Err(ServerFnError::Deserialization(format!(
+                "expected {} responses in the batch, but received {}",
+                senders.len(),
+                responses.len()
+            )));
+        }
+        Ok(responses)
+    }
+    .await;
+
+    match res {
+        Ok(responses) => {
+            for (tx, res) in senders.into_iter().zip(responses) {
+                _ = tx.send(Ok(res));
+            }
+        }
+        Err(e) => {
+            for tx in senders {
+                _ = tx.send(Err(e.clone()));
+            }
+        }
+    }
+}
+
+/// Returns `Pending` once, so that other tasks get to run before this one resumes.
+struct YieldNow(bool);
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            Poll::Ready(())
+        } else {
+            self.0 = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+/// Runs each call in the body of a batch request with `dispatch`, and returns the body of the
+/// response, which holds their responses in the same order.
+///
+/// The calls are created before the returned future is polled, so that it doesn't hold
+/// `dispatch`, which would otherwise have to be `Sync` for the future to be `Send`.
+#[cfg(feature = "ssr")]
+pub(crate) fn run_batch<Fut>(
+    body: &[u8],
+    dispatch: impl Fn(BatchedRequest) -> Fut,
+) -> impl Future<Output = Result<String, ServerFnError>>
+where
+    Fut: Future<Output = BatchedResponse>,
+{
+    let calls = parse_batch(body)
+        .map(|reqs| reqs.into_iter().map(dispatch).collect::<Vec<_>>());
+    async move {
+        let responses = futures::future::join_all(calls?).await;
+        serde_json::to_string(&responses)
+            .map_err(|e| ServerFnError::Serialization(e.to_string()))
+    }
+}
+
+#[cfg(feature = "ssr")]
+fn parse_batch(body: &[u8]) -> Result<Vec<BatchedRequest>, ServerFnError> {
+    let reqs: Vec<BatchedRequest> = serde_json::from_slice(body)
+        .map_err(|e| ServerFnError::<NoCustomError>::Args(e.to_string()))?;
+    if reqs.len() > MAX_BATCH_SIZE {
+        return Err(ServerFnError::Args(format!(
+            "a batch can hold at most {MAX_BATCH_SIZE} calls, but this one \
+             holds {}",
+            reqs.len()
+        )));
+    }
+    Ok(reqs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn batch_path_follows_the_prefix() {
+        assert_eq!(batch_path("/api/get_user123"), "/api/_batch");
+        assert_eq!(batch_path("/custom/get_user?id=1"), "/custom/_batch");
+        assert_eq!(batch_path("/api/v2/get_user"), "/api/v2/_batch");
+        assert_eq!(batch_path("/get_user"), "/_batch");
+    }
+
+    #[test]
+    fn batch_paths_are_deduplicated() {
+        let paths = batch_paths(["/api/a", "/api/b", "/custom/c"]);
+        assert_eq!(
+            paths.into_iter().collect::<Vec<_>>(),
+            ["/api/_batch", "/custom/_batch"]
+        );
+    }
+
+    #[cfg(feature = "ssr")]
+    #[test]
+    fn refuses_batches_over_the_limit() {
+        let req = BatchedRequest {
+            method: "POST".into(),
+            path: "/api/a".into(),
+            headers: Vec::new(),
+            body: Bytes::new(),
+        };
+        let dispatch = |_| async { BatchedResponse::error(StatusCode::OK, "") };
+
+        let body =
+            serde_json::to_vec(&vec![req.clone(); MAX_BATCH_SIZE]).unwrap();
+        let res = futures::executor::block_on(run_batch(&body, dispatch));
+        assert!(res.is_ok());
+
+        let body = serde_json::to_vec(&vec![req; MAX_BATCH_SIZE + 1]).unwrap();
+        let res = futures::executor::block_on(run_batch(&body, dispatch));
+        assert!(matches!(res, Err(ServerFnError::Args(_))));
+    }
+}
@@ -0,0 +1,417 @@
+use crate::{error::ServerFnError, response::Res};
+use once_cell::sync::Lazy;
+use pin_project_lite::pin_project;
+use std::{
+    any::Any,
+    cell::Cell,
+    collections::HashMap,
+    future::Future,
+    pin::Pin,
+    sync::{Mutex, PoisonError},
+    task::{Context, Poll},
+    time::Duration,
+};
+
+/// Controls the `Cache-Control` and `ETag` headers sent with the response to a `GET`
+/// server function, and whether it answers a matching `If-None-Match` with `304 Not Modified`.
+///
+/// Only server functions with an input encoding that uses `GET` (like `GetUrl`) are cached.
+/// The policy can be set for a server function with `#[server(cache = ...)]`, and changed
+/// for a single call from inside its body with [`override_cache_policy`].
+///
+/// ```rust,ignore
+/// #[server(
+///     input = GetUrl,
+///     cache = CachePolicy::new()
+///         .with_max_age(Duration::from_secs(60))
+///         .with_etag()
+/// )]
+/// pub async fn list_posts() -> Result<Vec<Post>, ServerFnError> {
+///     todo!()
+/// }
+/// ```
+///
+/// ## Conditional Requests
+/// When the policy includes an `ETag`, the client remembers the last value it decoded from the
+/// server function's response, and sends its tag with the next call to the same URL. If the
+/// server responds with `304 Not Modified`, the remembered value is returned again instead of
+/// being downloaded. This requires the server function's return type to implement `Clone`. The
+/// client remembers the values of the 128 most recently used URLs.
+///
+/// Computing the tag reads the whole response body into memory, so it should not be combined
+/// with streaming output encodings.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CachePolicy {
+    max_age: Option<Duration>,
+    private: bool,
+    no_cache: bool,
+    no_store: bool,
+    etag: bool,
+}
+
+impl CachePolicy {
+    /// Creates a policy that sends no caching headers.
+    pub const fn new() -> Self {
+        Self {
+            max_age: None,
+            private: false,
+            no_cache: false,
+            no_store: false,
+            etag: false,
+        }
+    }
+
+    /// Allows the response to be reused for `max_age` without asking the server again.
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// Only allows the response to be cached by the browser, not by shared caches.
+    pub fn with_private(mut self) -> Self {
+        self.private = true;
+        self
+    }
+
+    /// Requires the response to be revalidated with the server before it is reused.
+    pub fn with_no_cache(mut self) -> Self {
+        self.no_cache = true;
+        self
+    }
+
+    /// Forbids the response from being stored by any cache.
+    pub fn with_no_store(mut self) -> Self {
+        self.no_store = true;
+        self
+    }
+
+    /// Tags the response with an `ETag` computed from its body, and answers requests that
+    /// already have the current version with `304 Not Modified`.
+    pub fn with_etag(mut self) -> Self {
+        self.etag = true;
+        self
+    }
+
+    /// How long the response may be reused, if set.
+    pub fn max_age(&self) -> Option<Duration> {
+        self.max_age
+    }
+
+    /// Whether the response is tagged with an `ETag`.
+    pub fn etag(&self) -> bool {
+        self.etag
+    }
+
+    /// The value of the `Cache-Control` header for this policy, if it sets one.
+    pub fn cache_control(&self) -> Option<String> {
+        let mut directives = Vec::new();
+        if self.no_store {
+            directives.push("no-store".to_string());
+        }
+        if self.no_cache {
+            directives.push("no-cache".to_string());
+        }
+        if self.private {
+            directives.push("private".to_string());
+        }
+        if let Some(max_age) = self.max_age {
+            directives.push(format!("max-age={}", max_age.as_secs()));
+        }
+        (!directives.is_empty()).then(|| directives.join(", "))
+    }
+}
+
+thread_local! {
+    static CURRENT_POLICY: Cell<Option<CachePolicy>> = const { Cell::new(None) };
+}
+
+/// Replaces the [`CachePolicy`] for the response to the server function call that is
+/// currently running.
+///
+/// This has no effect outside the body of a `GET` server function, or in a task that the
+/// body has spawned.
+pub fn override_cache_policy(policy: CachePolicy) {
+    CURRENT_POLICY.with(|current| {
+        if current.get().is_some() {
+            current.set(Some(policy));
+        }
+    });
+}
+
+pin_project! {
+    /// Runs the body of a server function, keeping track of any changes it makes to its
+    /// cache policy.
+    pub(crate) struct WithCachePolicy<F> {
+        #[pin]
+        inner: F,
+        policy: CachePolicy,
+    }
+}
+
+impl<F> WithCachePolicy<F> {
+    pub(crate) fn new(policy: CachePolicy, inner: F) -> Self {
+        Self { inner, policy }
+    }
+}
+
+impl<F> Future for WithCachePolicy<F>
+where
+    F: Future,
+{
+    type Output = (F::Output, CachePolicy);
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let prev =
+            CURRENT_POLICY.with(|current| current.replace(Some(*this.policy)));
+        let res = this.inner.poll(cx);
+        if let Some(policy) =
+            CURRENT_POLICY.with(|current| current.replace(prev))
+        {
+            *this.policy = policy;
+        }
+        res.map(|output| (output, *this.policy))
+    }
+}
+
+/// Adds the caching headers for the policy to a response, replacing it with
+/// `304 Not Modified` if the client already has the current version.
+pub(crate) async fn apply_cache_policy<CustErr, R>(
+    policy: CachePolicy,
+    if_none_match: Option<String>,
+    mut res: R,
+) -> Result<R, ServerFnError<CustErr>>
+where
+    R: Res<CustErr> + Send,
+{
+    if let Some(cache_control) = policy.cache_control() {
+        res.insert_header("cache-control", &cache_control);
+    }
+    if !policy.etag() {
+        return Ok(res);
+    }
+
+    let (mut res, body) = res.try_into_buffered().await?;
+    // responses that can't be buffered are sent without an `ETag`
+    let Some(body) = body else {
+        return Ok(res);
+    };
+    let etag =
+        format!("\"{:016x}\"", xxhash_rust::const_xxh64::xxh64(&body, 0));
+    res.insert_header("etag", &etag);
+    let matches = if_none_match.is_some_and(|tags| {
+        tags.split(',').map(str::trim).any(|tag| {
+            tag == "*" || tag.strip_prefix("W/").unwrap_or(tag) == etag
+        })
+    });
+    Ok(if matches {
+        res.into_not_modified()
+    } else {
+        res
+    })
+}
+
+/// The most cached values the client keeps at once. When another is stored, the one that
+/// was used least recently is forgotten.
+const MAX_CLIENT_CACHE_ENTRIES: usize = 128;
+
+/// The values most recently decoded from cached server function URLs on the client, with
+/// the tags of the responses they were decoded from.
+struct ClientCache {
+    entries: HashMap<String, CacheEntry>,
+    capacity: usize,
+    // incremented on each access, so entries can be ordered by when they were last used
+    clock: u64,
+}
+
+struct CacheEntry {
+    etag: String,
+    output: Box<dyn Any + Send>,
+    last_used: u64,
+}
+
+impl ClientCache {
+    fn new(capacity: usize) -> Self {
+        Self {
+            entries: HashMap::new(),
+            capacity,
+            clock: 0,
+        }
+    }
+
+    fn get(&mut self, url: &str) -> Option<&CacheEntry> {
+        self.clock += 1;
+        let entry = self.entries.get_mut(url)?;
+        entry.last_used = self.clock;
+        Some(entry)
+    }
+
+    fn insert(&mut self, url: &str, etag: &str, output: Box<dyn Any + Send>) {
+        self.clock += 1;
+        if !self.entries.contains_key(url)
+            && self.entries.len() >= self.capacity
+        {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(url, _)| url.clone());
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.entries.insert(
+            url.to_string(),
+            CacheEntry {
+                etag: etag.to_string(),
+                output,
+                last_used: self.clock,
+            },
+        );
+    }
+}
+
+static CLIENT_CACHE: Lazy<Mutex<ClientCache>> =
+    Lazy::new(|| Mutex::new(ClientCache::new(MAX_CLIENT_CACHE_ENTRIES)));
+
+/// Returns the tag of the value cached for the given URL, if any.
+pub(crate) fn cached_etag(url: &str) -> Option<String> {
+    CLIENT_CACHE
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get(url)
+        .map(|entry| entry.etag.clone())
+}
+
+/// Remembers the value decoded from the response at the given URL.
+#[doc(hidden)]
+pub fn store_output<T>(url: &str, etag: &str, output: &T)
+where
+    T: Clone + Send + 'static,
+{
+    CLIENT_CACHE
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .insert(url, etag, Box::new(output.clone()));
+}
+
+/// Returns the value last decoded from the response at the given URL, if any.
+#[doc(hidden)]
+pub fn load_output<T>(url: &str) -> Option<T>
+where
+    T: Clone + 'static,
+{
+    CLIENT_CACHE
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get(url)
+        .and_then(|entry| entry.output.downcast_ref::<T>())
+        .cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::NoCustomError;
+    use bytes::Bytes;
+    use futures::Stream;
+
+    #[test]
+    fn cache_control_lists_directives() {
+        assert_eq!(CachePolicy::new().cache_control(), None);
+        assert_eq!(
+            CachePolicy::new()
+                .with_private()
+                .with_max_age(Duration::from_secs(60))
+                .cache_control()
+                .as_deref(),
+            Some("private, max-age=60")
+        );
+    }
+
+    #[test]
+    fn client_cache_forgets_least_recently_used() {
+        let mut cache = ClientCache::new(2);
+        cache.insert("/a", "\"a\"", Box::new(1_u32));
+        cache.insert("/b", "\"b\"", Box::new(2_u32));
+        // using `/a` makes `/b` the least recently used
+        assert!(cache.get("/a").is_some());
+        cache.insert("/c", "\"c\"", Box::new(3_u32));
+
+        assert_eq!(cache.entries.len(), 2);
+        assert!(cache.get("/b").is_none());
+        assert_eq!(cache.get("/a").map(|e| e.etag.as_str()), Some("\"a\""));
+        assert_eq!(cache.get("/c").map(|e| e.etag.as_str()), Some("\"c\""));
+    }
+
+    #[test]
+    fn client_cache_replaces_entries_without_evicting() {
+        let mut cache = ClientCache::new(2);
+        cache.insert("/a", "\"1\"", Box::new(1_u32));
+        cache.insert("/b", "\"b\"", Box::new(2_u32));
+        cache.insert("/a", "\"2\"", Box::new(3_u32));
+
+        assert_eq!(cache.entries.len(), 2);
+        assert!(cache.get("/b").is_some());
+        let entry = cache.get("/a").unwrap();
+        assert_eq!(entry.etag, "\"2\"");
+        assert_eq!(entry.output.downcast_ref::<u32>(), Some(&3));
+    }
+
+    /// A response that only implements the required methods of [`Res`].
+    #[derive(Debug, PartialEq)]
+    struct Unbuffered;
+
+    impl Res<NoCustomError> for Unbuffered {
+        fn try_from_string(
+            _content_type: &str,
+            _data: String,
+        ) -> Result<Self, ServerFnError> {
+            Ok(Unbuffered)
+        }
+
+        fn try_from_bytes(
+            _content_type: &str,
+            _data: Bytes,
+        ) -> Result<Self, ServerFnError> {
+            Ok(Unbuffered)
+        }
+
+        fn try_from_stream(
+            _content_type: &str,
+            _data: impl Stream<Item = Result<Bytes, ServerFnError>> + Send + 'static,
+        ) -> Result<Self, ServerFnError> {
+            Ok(Unbuffered)
+        }
+
+        fn error_response(_path: &str, _err: &ServerFnError) -> Self {
+            Unbuffered
+        }
+
+        fn redirect(&mut self, _path: &str) {}
+    }
+
+    #[test]
+    fn responses_that_cannot_be_buffered_are_sent_as_they_are() {
+        let policy = CachePolicy::new().with_etag();
+        let res = futures::executor::block_on(apply_cache_policy(
+            policy,
+            Some("*".to_string()),
+            Unbuffered,
+        ));
+        assert_eq!(res, Ok(Unbuffered));
+    }
+
+    #[test]
+    fn stored_output_is_loaded_by_type() {
+        store_output("/cache-test?id=1", "\"x\"", &String::from("value"));
+
+        assert_eq!(cached_etag("/cache-test?id=1").as_deref(), Some("\"x\""));
+        assert_eq!(
+            load_output::<String>("/cache-test?id=1").as_deref(),
+            Some("value")
+        );
+        assert_eq!(load_output::<u32>("/cache-test?id=1"), None);
+        assert_eq!(load_output::<String>("/cache-test?id=2"), None);
+    }
+}
@@ -1,5 +1,24 @@
-use crate::{error::ServerFnError, request::ClientReq, response::ClientRes};
-use std::{future::Future, sync::OnceLock};
+#[cfg(feature = "websocket")]
+use crate::codec::ByteStream;
+use crate::{
+    error::ServerFnError,
+    middleware::{BoxedService, Layer, Service},
+    request::ClientReq,
+    response::ClientRes,
+};
+use futures::{
+    future::{ready, select, Abortable, Either},
+    FutureExt, TryFutureExt,
+};
+use std::{
+    any::Any,
+    fmt::Display,
+    future::Future,
+    marker::PhantomData,
+    pin::{pin, Pin},
+    sync::{Arc, Mutex, OnceLock, PoisonError},
+    time::Duration,
+};
 
 static ROOT_URL: OnceLock<&'static str> = OnceLock::new();
 
@@ -15,6 +34,99 @@ pub fn get_server_url() -> &'static str {
     ROOT_URL.get().copied().unwrap_or("")
 }
 
+static CALL_POLICY: OnceLock<CallPolicy> = OnceLock::new();
+
+/// Set the default [`CallPolicy`] for calls to server functions from the client.
+///
+/// Server functions that set their own policy with `#[server(policy = ...)]` ignore this.
+/// If this is not set, calls have no timeout and are never retried.
+pub fn set_call_policy(policy: CallPolicy) {
+    CALL_POLICY.set(policy).unwrap();
+}
+
+/// Returns the default [`CallPolicy`] for calls to server functions from the client.
+pub fn get_call_policy() -> CallPolicy {
+    CALL_POLICY.get().copied().unwrap_or_default()
+}
+
+/// Controls how long a call to a server function from the client may take, and whether
+/// it is retried if it fails.
+///
+/// Only server functions with an input encoding that uses `GET` (like `GetUrl`) are retried,
+/// as calling them more than once should have no side effects. A call is retried if it
+/// times out or could not reach the server, but not if the server returns an error.
+///
+/// ```rust,ignore
+/// #[server(
+///     input = GetUrl,
+///     policy = CallPolicy::new()
+///         .with_timeout(Duration::from_secs(5))
+///         .with_retries(3)
+/// )]
+/// pub async fn load_feed() -> Result<Vec<Post>, ServerFnError> {
+///     // ...
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CallPolicy {
+    timeout: Option<Duration>,
+    max_retries: u32,
+    backoff: Duration,
+}
+
+impl Default for CallPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CallPolicy {
+    /// Creates a policy with no timeout, which never retries.
+    pub const fn new() -> Self {
+        Self {
+            timeout: None,
+            max_retries: 0,
+            backoff: Duration::from_millis(100),
+        }
+    }
+
+    /// Fails each attempt with [`ServerFnError::Timeout`] if the server has not responded
+    /// within `timeout`.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Retries a failed call up to `max_retries` times.
+    pub fn with_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Sets the delay before the first retry, which is doubled for each further retry.
+    ///
+    /// This defaults to 100 milliseconds.
+    pub fn with_backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// The timeout for each attempt, if any.
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// The maximum number of times a failed call is retried.
+    pub fn max_retries(&self) -> u32 {
+        self.max_retries
+    }
+
+    /// The delay before the given retry, counting from `0`.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        self.backoff.saturating_mul(2u32.saturating_pow(retry))
+    }
+}
+
 /// A client defines a pair of request/response types and the logic to send
 /// and receive them.
 ///
@@ -31,19 +143,501 @@ pub trait Client<CustErr> {
     fn send(
         req: Self::Request,
     ) -> impl Future<Output = Result<Self::Response, ServerFnError<CustErr>>> + Send;
+
+    /// Opens a websocket connection to the server function at `path`.
+    ///
+    /// The messages in `outgoing` are sent to the server for as long as the connection
+    /// is open, and the returned stream yields the messages received from the server.
+    ///
+    /// By default, this returns an error, as not every client supports websockets.
+    #[cfg(feature = "websocket")]
+    fn open_websocket(
+        path: &str,
+        outgoing: ByteStream,
+    ) -> impl Future<Output = Result<ByteStream, ServerFnError<CustErr>>> + Send
+    {
+        _ = outgoing;
+        let msg = format!(
+            "Could not open a websocket connection to {path}: this client \
+             does not support websockets."
+        );
+        async move { Err(ServerFnError::Request(msg)) }
+    }
+
+    /// Middleware that should be applied to every request sent by this client.
+    ///
+    /// This runs inside any global middleware added with [`add_client_middleware`].
+    fn middlewares() -> Vec<ClientLayer<Self::Request, Self::Response>> {
+        Vec::new()
+    }
+
+    /// Returns a future that completes after the given duration, which is used for
+    /// timeouts and for the delay between retries.
+    ///
+    /// By default, this returns `None`, which means that calls sent with this client never
+    /// time out, and are retried without any delay.
+    fn sleep(duration: Duration) -> Option<impl Future<Output = ()> + Send> {
+        _ = duration;
+        None::<std::future::Ready<()>>
+    }
+}
+
+/// Client-side middleware, which wraps the sending of each request by a [`Client`].
+///
+/// A layer can modify the request before passing it on (for example, with
+/// [`ClientReq::insert_header`]), inspect or replace the response, or return an error
+/// without sending the request at all.
+///
+/// Errors pass through client middleware without any custom error type, as the same
+/// middleware is shared by every server function that uses the client.
+pub type ClientLayer<Req, Res> =
+    Arc<dyn Layer<Req, Result<Res, ServerFnError>>>;
+
+static MIDDLEWARE: Mutex<Vec<Box<dyn Any + Send + Sync>>> =
+    Mutex::new(Vec::new());
+
+/// Adds middleware that is applied to every request sent by any client with the given
+/// request and response types.
+///
+/// Each layer wraps the layers added before it, and all global middleware wraps the
+/// middleware set by [`Client::middlewares`].
+///
+/// ```rust,ignore
+/// // attaches the token to requests sent by `BrowserClient`
+/// add_client_middleware(Intercept::new(BearerToken));
+/// ```
+pub fn add_client_middleware<Req, Res>(
+    layer: impl Layer<Req, Result<Res, ServerFnError>>,
+) where
+    Req: 'static,
+    Res: 'static,
+{
+    let layer: ClientLayer<Req, Res> = Arc::new(layer);
+    MIDDLEWARE
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .push(Box::new(layer));
+}
+
+fn global_middleware<Req, Res>() -> Vec<ClientLayer<Req, Res>>
+where
+    Req: 'static,
+    Res: 'static,
+{
+    MIDDLEWARE
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .iter()
+        .filter_map(|layer| layer.downcast_ref::<ClientLayer<Req, Res>>())
+        .cloned()
+        .collect()
+}
+
+/// Sends the request with the client `C`, through its own middleware and any global
+/// middleware for its request and response types.
+pub(crate) async fn send<C, CustErr>(
+    req: C::Request,
+) -> Result<C::Response, ServerFnError<CustErr>>
+where
+    C: Client<CustErr> + 'static,
+    CustErr: Display + Send + 'static,
+{
+    let middleware = C::middlewares()
+        .into_iter()
+        .chain(global_middleware())
+        .collect::<Vec<_>>();
+    if middleware.is_empty() {
+        return C::send(req).await;
+    }
+
+    // middleware only sees a `ServerFnError` without the custom error type, so a custom
+    // error returned by the client is set aside, and handed back as it was if the middleware
+    // passes the error along unchanged
+    let custom_err = Arc::new(Mutex::new(None));
+    let mut service = BoxedService::new(SendRequest::<C, CustErr> {
+        custom_err: Arc::clone(&custom_err),
+        client: PhantomData,
+    });
+    for layer in middleware {
+        service = layer.layer(service);
+    }
+    service.0.run(req).await.map_err(|e| {
+        let custom = custom_err
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take();
+        match custom {
+            Some((erased, custom)) if erased == e => {
+                ServerFnError::WrappedServerError(custom)
+            }
+            _ => {
+                e.map_custom_err(|e| ServerFnError::ServerError(e.to_string()))
+            }
+        }
+    })
+}
+
+/// The innermost service in a client's middleware stack, which actually sends the request.
+#[allow(clippy::type_complexity)]
+struct SendRequest<C, CustErr> {
+    custom_err: Arc<Mutex<Option<(ServerFnError, CustErr)>>>,
+    client: PhantomData<fn() -> C>,
+}
+
+impl<C, CustErr> Service<C::Request, Result<C::Response, ServerFnError>>
+    for SendRequest<C, CustErr>
+where
+    C: Client<CustErr> + 'static,
+    CustErr: Display + Send + 'static,
+{
+    fn run(
+        &mut self,
+        req: C::Request,
+    ) -> Pin<Box<dyn Future<Output = Result<C::Response, ServerFnError>> + Send>>
+    {
+        let custom_err = Arc::clone(&self.custom_err);
+        Box::pin(C::send(req).map_err(move |e| match e {
+            ServerFnError::WrappedServerError(custom) => {
+                let erased = ServerFnError::ServerError(custom.to_string());
+                *custom_err.lock().unwrap_or_else(PoisonError::into_inner) =
+                    Some((erased.clone(), custom));
+                erased
+            }
+            e => {
+                e.map_custom_err(|e| ServerFnError::ServerError(e.to_string()))
+            }
+        }))
+    }
+}
+
+/// A simple form of client-side middleware, which is called before each request is sent
+/// and after each response is received.
+///
+/// Wrap an interceptor in [`Intercept`] to use it as a [`ClientLayer`].
+///
+/// ```rust,ignore
+/// #[derive(Default)]
+/// struct BearerToken;
+///
+/// impl Interceptor<BrowserRequest, BrowserResponse> for BearerToken {
+///     fn on_request(
+///         &self,
+///         req: &mut BrowserRequest,
+///     ) -> Result<(), ServerFnError> {
+///         let token = load_token()
+///             .ok_or_else(|| ServerFnError::Rejected("Not logged in.".into()))?;
+///         req.headers().set("Authorization", &format!("Bearer {token}"));
+///         Ok(())
+///     }
+/// }
+/// ```
+pub trait Interceptor<Req, Res>: Send + Sync + 'static {
+    /// Called before the request is sent. Returning an error cancels the request.
+    ///
+    /// The call is not retried: a [`ServerFnError::Request`] or [`ServerFnError::Timeout`]
+    /// returned here is reported as [`ServerFnError::Rejected`].
+    fn on_request(&self, req: &mut Req) -> Result<(), ServerFnError> {
+        _ = req;
+        Ok(())
+    }
+
+    /// Called once the response has been received. Returning an error discards the response.
+    fn on_response(&self, res: &Res) -> Result<(), ServerFnError> {
+        _ = res;
+        Ok(())
+    }
+}
+
+/// Adapts an [`Interceptor`] into a middleware [`Layer`] for a [`Client`].
+#[derive(Debug, Default)]
+pub struct Intercept<I>(Arc<I>);
+
+impl<I> Intercept<I> {
+    /// Wraps the interceptor.
+    pub fn new(interceptor: I) -> Self {
+        Self(Arc::new(interceptor))
+    }
+}
+
+impl<I, Req, Res> Layer<Req, Result<Res, ServerFnError>> for Intercept<I>
+where
+    I: Interceptor<Req, Res>,
+    Req: Send + 'static,
+    Res: Send + 'static,
+{
+    fn layer(
+        &self,
+        inner: BoxedService<Req, Result<Res, ServerFnError>>,
+    ) -> BoxedService<Req, Result<Res, ServerFnError>> {
+        BoxedService::new(InterceptService {
+            interceptor: Arc::clone(&self.0),
+            inner,
+        })
+    }
+}
+
+struct InterceptService<I, Req, Res> {
+    interceptor: Arc<I>,
+    inner: BoxedService<Req, Result<Res, ServerFnError>>,
+}
+
+impl<I, Req, Res> Service<Req, Result<Res, ServerFnError>>
+    for InterceptService<I, Req, Res>
+where
+    I: Interceptor<Req, Res>,
+    Req: Send + 'static,
+    Res: Send + 'static,
+{
+    fn run(
+        &mut self,
+        mut req: Req,
+    ) -> Pin<Box<dyn Future<Output = Result<Res, ServerFnError>> + Send>> {
+        if let Err(e) = self.interceptor.on_request(&mut req) {
+            let e = match e {
+                ServerFnError::Request(e) | ServerFnError::Timeout(e) => {
+                    ServerFnError::Rejected(e)
+                }
+                e => e,
+            };
+            return Box::pin(ready(Err(e)));
+        }
+        let interceptor = Arc::clone(&self.interceptor);
+        let res = self.inner.0.run(req);
+        Box::pin(async move {
+            let res = res.await?;
+            interceptor.on_response(&res)?;
+            Ok(res)
+        })
+    }
+}
+
+/// A [`Client`] that sends requests with the client `C`, wrapping any middleware that `C`
+/// already has in the layer `L`.
+///
+/// This can be used to add middleware to individual server functions:
+/// ```rust,ignore
+/// #[server(client = Layered<BrowserClient, Intercept<BearerToken>>)]
+/// pub async fn load_account() -> Result<Account, ServerFnError> {
+///     // ...
+/// }
+/// ```
+pub struct Layered<C, L>(PhantomData<fn() -> (C, L)>);
+
+impl<C, L, CustErr> Client<CustErr> for Layered<C, L>
+where
+    C: Client<CustErr>,
+    L: Layer<C::Request, Result<C::Response, ServerFnError>> + Default,
+{
+    type Request = C::Request;
+    type Response = C::Response;
+
+    fn send(
+        req: Self::Request,
+    ) -> impl Future<Output = Result<Self::Response, ServerFnError<CustErr>>> + Send
+    {
+        C::send(req)
+    }
+
+    #[cfg(feature = "websocket")]
+    fn open_websocket(
+        path: &str,
+        outgoing: ByteStream,
+    ) -> impl Future<Output = Result<ByteStream, ServerFnError<CustErr>>> + Send
+    {
+        C::open_websocket(path, outgoing)
+    }
+
+    fn middlewares() -> Vec<ClientLayer<Self::Request, Self::Response>> {
+        let mut middleware = C::middlewares();
+        middleware.push(Arc::new(L::default()));
+        middleware
+    }
+
+    fn sleep(duration: Duration) -> Option<impl Future<Output = ()> + Send> {
+        C::sleep(duration)
+    }
+}
+
+/// Makes each attempt to call a server function according to the policy, retrying idempotent
+/// calls that time out or cannot reach the server.
+pub(crate) async fn call_with_policy<C, CustErr, T, Fut>(
+    mut req: C::Request,
+    policy: CallPolicy,
+    idempotent: bool,
+    mut call: impl FnMut(C::Request) -> Fut,
+) -> Result<T, ServerFnError<CustErr>>
+where
+    C: Client<CustErr>,
+    Fut: Future<Output = Result<T, ServerFnError<CustErr>>>,
+{
+    let mut retry = 0;
+    loop {
+        let next = (idempotent && retry < policy.max_retries)
+            .then(|| req.try_clone())
+            .flatten();
+        let res = with_timeout::<C, _, _>(policy.timeout, call(req)).await;
+        match (res, next) {
+            (
+                Err(ServerFnError::Request(_) | ServerFnError::Timeout(_)),
+                Some(next),
+            ) => req = next,
+            (res, _) => return res,
+        }
+        if let Some(delay) = C::sleep(policy.backoff(retry)) {
+            delay.await;
+        }
+        retry += 1;
+    }
+}
+
+async fn with_timeout<C, CustErr, T>(
+    timeout: Option<Duration>,
+    call: impl Future<Output = Result<T, ServerFnError<CustErr>>>,
+) -> Result<T, ServerFnError<CustErr>>
+where
+    C: Client<CustErr>,
+{
+    let Some((timeout, timer)) =
+        timeout.and_then(|timeout| Some((timeout, C::sleep(timeout)?)))
+    else {
+        return call.await;
+    };
+    match select(pin!(call), pin!(timer)).await {
+        Either::Left((res, _)) => res,
+        Either::Right(_) => Err(ServerFnError::Timeout(format!(
+            "no response after {timeout:?}"
+        ))),
+    }
+}
+
+/// A handle that cancels a server function call made [`abortable`].
+#[derive(Debug, Clone)]
+pub struct AbortHandle(futures::future::AbortHandle);
+
+impl AbortHandle {
+    /// Cancels the call, which then resolves to [`ServerFnError::Cancelled`].
+    ///
+    /// If the request has already been sent, it is aborted: in the browser, this triggers its
+    /// `AbortController`, while other clients drop the request.
+    pub fn abort(&self) {
+        self.0.abort();
+    }
+
+    /// Whether [`abort`](AbortHandle::abort) has been called.
+    pub fn is_aborted(&self) -> bool {
+        self.0.is_aborted()
+    }
+}
+
+/// Wraps a call to a server function so that it can be cancelled with the returned
+/// [`AbortHandle`].
+///
+/// For example, a call that should not outlive a component can be aborted when the
+/// component is unmounted:
+/// ```rust,ignore
+/// let (call, handle) = abortable(load_feed());
+/// on_cleanup(move || handle.abort());
+/// spawn_local(async move {
+///     if let Ok(feed) = call.await {
+///         set_feed.set(feed);
+///     }
+/// });
+/// ```
+pub fn abortable<T, CustErr>(
+    call: impl Future<Output = Result<T, ServerFnError<CustErr>>>,
+) -> (
+    impl Future<Output = Result<T, ServerFnError<CustErr>>>,
+    AbortHandle,
+) {
+    let (handle, registration) = futures::future::AbortHandle::new_pair();
+    // once aborted, the inner future is dropped, which cancels any request in flight
+    let call = Abortable::new(call, registration).map(|res| {
+        res.unwrap_or_else(|_| {
+            Err(ServerFnError::Cancelled("the call was aborted".to_string()))
+        })
+    });
+    (call, AbortHandle(handle))
+}
+
+/// Returns the `ws://` or `wss://` URL at which the server function at `path` is served,
+/// given the `http://` or `https://` origin of the server.
+#[cfg(feature = "websocket")]
+#[allow(dead_code)] // only used by clients that support websockets
+fn websocket_url(origin: &str, path: &str) -> String {
+    let origin = origin
+        .strip_prefix("http")
+        .map(|rest| format!("ws{rest}"))
+        .unwrap_or_else(|| origin.to_string());
+    format!("{origin}{path}")
+}
+
+/// Forwards the `outgoing` messages to the socket's `sink` while the returned stream of
+/// `incoming` messages is being polled, so that the connection lives exactly as long as that
+/// stream.
+///
+/// A message that fails to encode is reported on the returned stream, and the rest are still
+/// sent. If the socket itself can no longer be written to, that error is reported and nothing
+/// more is sent.
+#[cfg(feature = "websocket")]
+#[allow(dead_code)] // only used by clients that support websockets
+fn pump_websocket<M, S>(
+    incoming: impl futures::Stream<Item = Result<bytes::Bytes, ServerFnError>>,
+    sink: S,
+    outgoing: ByteStream,
+    to_message: impl Fn(bytes::Bytes) -> M,
+) -> impl futures::Stream<Item = Result<bytes::Bytes, ServerFnError>>
+where
+    S: futures::Sink<M> + Unpin,
+    S::Error: Display,
+{
+    use futures::{stream, SinkExt, StreamExt};
+
+    let outgoing = Box::pin(outgoing.into_inner());
+    let errors = stream::unfold(
+        Some((outgoing, sink, to_message)),
+        |state| async move {
+            let (mut outgoing, mut sink, to_message) = state?;
+            loop {
+                match outgoing.next().await {
+                    Some(Ok(msg)) => {
+                        if let Err(e) = sink.send(to_message(msg)).await {
+                            let e = ServerFnError::Request(e.to_string());
+                            return Some((Err(e), None));
+                        }
+                    }
+                    Some(Err(e)) => {
+                        return Some((
+                            Err(e),
+                            Some((outgoing, sink, to_message)),
+                        ))
+                    }
+                    None => {
+                        _ = sink.close().await;
+                        return None;
+                    }
+                }
+            }
+        },
+    );
+    stream::select(incoming, errors)
 }
 
 #[cfg(feature = "browser")]
 /// Implements [`Client`] for a `fetch` request in the browser.
 pub mod browser {
     use super::Client;
+    #[cfg(feature = "websocket")]
+    use crate::codec::ByteStream;
     use crate::{
         error::ServerFnError,
         request::browser::{BrowserRequest, RequestInner},
         response::browser::BrowserResponse,
     };
+    use js_sys::{Function, Promise, Reflect};
     use send_wrapper::SendWrapper;
-    use std::future::Future;
+    use std::{future::Future, time::Duration};
+    use wasm_bindgen::{JsCast, JsValue};
+    use wasm_bindgen_futures::JsFuture;
 
     /// Implements [`Client`] for a `fetch` request in the browser.    
     pub struct BrowserClient;
@@ -61,7 +655,16 @@ pub mod browser {
                 let RequestInner {
                     request,
                     mut abort_ctrl,
+                    buffered_body,
                 } = req;
+                let request = match buffered_body {
+                    Some(body) => {
+                        body.attach_to(request).await.map_err(|e| {
+                            ServerFnError::Request(format!("{e:?}"))
+                        })?
+                    }
+                    None => request,
+                };
                 let res = request
                     .send()
                     .await
@@ -76,6 +679,77 @@ pub mod browser {
                 res
             })
         }
+
+        #[cfg(feature = "websocket")]
+        fn open_websocket(
+            path: &str,
+            outgoing: ByteStream,
+        ) -> impl Future<Output = Result<ByteStream, ServerFnError<CustErr>>> + Send
+        {
+            use super::{get_server_url, pump_websocket, websocket_url};
+            use bytes::Bytes;
+            use futures::{future::ready, StreamExt};
+            use gloo_net::websocket::{
+                futures::WebSocket, Message, WebSocketError,
+            };
+
+            let origin = match get_server_url() {
+                "" => web_sys::window()
+                    .and_then(|window| window.location().origin().ok())
+                    .unwrap_or_default(),
+                server_url => server_url.to_string(),
+            };
+            let url = websocket_url(&origin, path);
+            SendWrapper::new(async move {
+                let socket = WebSocket::open(&url)
+                    .map_err(|e| ServerFnError::Request(e.to_string()))?;
+                let (sink, socket) = socket.split();
+
+                let incoming = socket.filter_map(|msg| {
+                    ready(match msg {
+                        Ok(Message::Bytes(data)) => Some(Ok(Bytes::from(data))),
+                        Ok(Message::Text(data)) => Some(Ok(Bytes::from(data))),
+                        Err(WebSocketError::ConnectionClose(_)) => None,
+                        Err(e) => {
+                            Some(Err(ServerFnError::Request(e.to_string())))
+                        }
+                    })
+                });
+
+                Ok(ByteStream::new(SendWrapper::new(pump_websocket(
+                    incoming,
+                    sink,
+                    outgoing,
+                    |msg| Message::Bytes(msg.into()),
+                ))))
+            })
+        }
+
+        fn sleep(
+            duration: Duration,
+        ) -> Option<impl Future<Output = ()> + Send> {
+            let millis =
+                i32::try_from(duration.as_millis()).unwrap_or(i32::MAX);
+            // `setTimeout` is looked up on the global scope rather than the window, so that
+            // it also works in web workers
+            let set_timeout = Reflect::get(
+                &js_sys::global(),
+                &JsValue::from_str("setTimeout"),
+            )
+            .ok()?
+            .dyn_into::<Function>()
+            .ok()?;
+            let timer = Promise::new(&mut |resolve, _| {
+                _ = set_timeout.call2(
+                    &JsValue::UNDEFINED,
+                    &resolve,
+                    &JsValue::from(millis),
+                );
+            });
+            Some(SendWrapper::new(async move {
+                _ = JsFuture::from(timer).await;
+            }))
+        }
     }
 }
 
@@ -83,10 +757,12 @@ pub mod browser {
 /// Implements [`Client`] for a request made by [`reqwest`].
 pub mod reqwest {
     use super::Client;
+    #[cfg(feature = "reqwest-websocket")]
+    use crate::codec::ByteStream;
     use crate::{error::ServerFnError, request::reqwest::CLIENT};
     use futures::TryFutureExt;
     use reqwest::{Request, Response};
-    use std::future::Future;
+    use std::{future::Future, time::Duration};
 
     /// Implements [`Client`] for a request made by [`reqwest`].
     pub struct ReqwestClient;
@@ -103,5 +779,212 @@ pub mod reqwest {
                 .execute(req)
                 .map_err(|e| ServerFnError::Request(e.to_string()))
         }
+
+        #[cfg(feature = "reqwest-websocket")]
+        fn open_websocket(
+            path: &str,
+            outgoing: ByteStream,
+        ) -> impl Future<Output = Result<ByteStream, ServerFnError<CustErr>>> + Send
+        {
+            use super::{get_server_url, pump_websocket, websocket_url};
+            use bytes::Bytes;
+            use futures::{future::ready, StreamExt};
+            use tokio_tungstenite::tungstenite::Message;
+
+            let url = websocket_url(get_server_url(), path);
+            async move {
+                let (socket, _) =
+                    tokio_tungstenite::connect_async(url)
+                        .await
+                        .map_err(|e| ServerFnError::Request(e.to_string()))?;
+                let (sink, socket) = socket.split();
+
+                let incoming = socket.filter_map(|msg| {
+                    ready(match msg {
+                        Ok(Message::Binary(data)) => {
+                            Some(Ok(Bytes::from(data)))
+                        }
+                        Ok(Message::Text(data)) => Some(Ok(Bytes::from(data))),
+                        Ok(_) => None,
+                        Err(e) => {
+                            Some(Err(ServerFnError::Request(e.to_string())))
+                        }
+                    })
+                });
+
+                Ok(ByteStream::new(pump_websocket(
+                    incoming,
+                    sink,
+                    outgoing,
+                    |msg| Message::Binary(msg.into()),
+                )))
+            }
+        }
+
+        fn sleep(
+            duration: Duration,
+        ) -> Option<impl Future<Output = ()> + Send> {
+            Some(tokio::time::sleep(duration))
+        }
+    }
+}
+
+#[cfg(all(test, feature = "websocket"))]
+mod websocket_tests {
+    use super::*;
+    use bytes::Bytes;
+    use futures::{channel::mpsc, executor::block_on, stream, StreamExt};
+
+    fn outgoing(msgs: Vec<Result<&'static str, ServerFnError>>) -> ByteStream {
+        ByteStream::new(stream::iter(
+            msgs.into_iter().map(|msg| msg.map(Bytes::from)),
+        ))
+    }
+
+    #[test]
+    fn websocket_reports_outgoing_errors_and_keeps_sending() {
+        let (tx, rx) = mpsc::unbounded::<Bytes>();
+        let received = pump_websocket(
+            stream::empty(),
+            tx,
+            outgoing(vec![
+                Ok("first"),
+                Err(ServerFnError::Serialization("bad message".into())),
+                Ok("second"),
+            ]),
+            |msg| msg,
+        );
+        assert_eq!(
+            block_on(received.collect::<Vec<_>>()),
+            vec![Err(ServerFnError::Serialization("bad message".into()))]
+        );
+        assert_eq!(
+            block_on(rx.collect::<Vec<_>>()),
+            vec![Bytes::from("first"), Bytes::from("second")]
+        );
+    }
+
+    #[test]
+    fn websocket_reports_a_closed_socket_once() {
+        let (tx, rx) = mpsc::unbounded::<Bytes>();
+        drop(rx);
+        let received = pump_websocket(
+            stream::empty(),
+            tx,
+            outgoing(vec![Ok("first"), Ok("second")]),
+            |msg| msg,
+        );
+        let received = block_on(received.collect::<Vec<_>>());
+        assert_eq!(received.len(), 1);
+        assert!(matches!(received[0], Err(ServerFnError::Request(_))));
+    }
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod middleware_tests {
+    use super::*;
+    use crate::mock::{MockRequest, MockResponse};
+    use futures::executor::block_on;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum AccountError {
+        Locked,
+    }
+
+    impl Display for AccountError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{self:?}")
+        }
+    }
+
+    struct PassThrough;
+
+    impl Interceptor<MockRequest, MockResponse> for PassThrough {}
+
+    struct LockedClient;
+
+    impl Client<AccountError> for LockedClient {
+        type Request = MockRequest;
+        type Response = MockResponse;
+
+        async fn send(
+            _req: Self::Request,
+        ) -> Result<Self::Response, ServerFnError<AccountError>> {
+            Err(ServerFnError::WrappedServerError(AccountError::Locked))
+        }
+
+        fn middlewares() -> Vec<ClientLayer<Self::Request, Self::Response>> {
+            vec![Arc::new(Intercept::new(PassThrough))]
+        }
+    }
+
+    fn request() -> MockRequest {
+        <MockRequest as ClientReq<AccountError>>::try_new_get(
+            "/api/account",
+            "application/json",
+            "application/x-www-form-urlencoded",
+            "",
+        )
+        .unwrap()
+    }
+
+    static ATTEMPTS: AtomicUsize = AtomicUsize::new(0);
+
+    struct NotLoggedIn;
+
+    impl Interceptor<MockRequest, MockResponse> for NotLoggedIn {
+        fn on_request(
+            &self,
+            _req: &mut MockRequest,
+        ) -> Result<(), ServerFnError> {
+            ATTEMPTS.fetch_add(1, Ordering::SeqCst);
+            Err(ServerFnError::Request("Not logged in.".into()))
+        }
+    }
+
+    struct RejectingClient;
+
+    impl Client<AccountError> for RejectingClient {
+        type Request = MockRequest;
+        type Response = MockResponse;
+
+        async fn send(
+            _req: Self::Request,
+        ) -> Result<Self::Response, ServerFnError<AccountError>> {
+            unreachable!("the interceptor rejects every request")
+        }
+
+        fn middlewares() -> Vec<ClientLayer<Self::Request, Self::Response>> {
+            vec![Arc::new(Intercept::new(NotLoggedIn))]
+        }
+    }
+
+    #[test]
+    fn interceptor_rejections_are_not_retried() {
+        let policy = CallPolicy::new()
+            .with_retries(3)
+            .with_backoff(Duration::ZERO);
+        let res = block_on(call_with_policy::<RejectingClient, _, _, _>(
+            request(),
+            policy,
+            true,
+            send::<RejectingClient, AccountError>,
+        ));
+        assert_eq!(
+            res.unwrap_err(),
+            ServerFnError::Rejected("Not logged in.".into())
+        );
+        assert_eq!(ATTEMPTS.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn middleware_passes_custom_errors_through() {
+        let err = block_on(send::<LockedClient, AccountError>(request()))
+            .unwrap_err();
+        assert_eq!(
+            err,
+            ServerFnError::WrappedServerError(AccountError::Locked)
+        );
     }
 }
@@ -0,0 +1,202 @@
+use super::{Encoding, FromReq, IntoReq};
+use crate::{
+    error::ServerFnError,
+    request::{ClientReq, Req},
+};
+use bytes::Bytes;
+use http::Method;
+use url::form_urlencoded;
+
+/// Sends one chunk of a file as the body of a `POST` request, with its position in the file
+/// in the query string.
+///
+/// This is used for uploads that can be resumed after a dropped connection, and is usually
+/// driven by [`upload`](crate::upload::upload) rather than called directly.
+pub struct Chunked;
+
+impl Encoding for Chunked {
+    const CONTENT_TYPE: &'static str = "application/octet-stream";
+    const METHOD: Method = Method::POST;
+}
+
+/// A chunk of a file that is being uploaded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UploadChunk {
+    id: String,
+    name: String,
+    offset: u64,
+    total: u64,
+    data: Bytes,
+}
+
+impl UploadChunk {
+    /// Creates a chunk of the upload `id`, which starts `offset` bytes into a file of `total`
+    /// bytes.
+    pub fn new(
+        id: impl Into<String>,
+        name: impl Into<String>,
+        offset: u64,
+        total: u64,
+        data: Bytes,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+            offset,
+            total,
+            data,
+        }
+    }
+
+    /// The ID of the upload, which is the same for every chunk of a file.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// The name of the file being uploaded.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The position of this chunk in the file.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// The size of the whole file.
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    /// The contents of this chunk.
+    pub fn data(&self) -> &Bytes {
+        &self.data
+    }
+
+    /// Consumes the chunk, returning its contents.
+    pub fn into_data(self) -> Bytes {
+        self.data
+    }
+}
+
+impl<CustErr, T, Request> IntoReq<Chunked, Request, CustErr> for T
+where
+    Request: ClientReq<CustErr>,
+    T: Into<UploadChunk>,
+{
+    fn into_req(
+        self,
+        path: &str,
+        accepts: &str,
+    ) -> Result<Request, ServerFnError<CustErr>> {
+        let chunk = self.into();
+        let query = form_urlencoded::Serializer::new(String::new())
+            .append_pair("id", &chunk.id)
+            .append_pair("name", &chunk.name)
+            .append_pair("offset", &chunk.offset.to_string())
+            .append_pair("total", &chunk.total.to_string())
+            .finish();
+        Request::try_new_post_bytes(
+            &format!("{path}?{query}"),
+            accepts,
+            Chunked::CONTENT_TYPE,
+            chunk.data,
+        )
+    }
+}
+
+impl<CustErr, T, Request> FromReq<Chunked, Request, CustErr> for T
+where
+    Request: Req<CustErr> + Send + 'static,
+    T: From<UploadChunk>,
+{
+    async fn from_req(req: Request) -> Result<Self, ServerFnError<CustErr>> {
+        let (mut id, mut name, mut offset, mut total) =
+            (None, None, None, None);
+        for (key, value) in form_urlencoded::parse(
+            req.as_query().unwrap_or_default().as_bytes(),
+        ) {
+            match key.as_ref() {
+                "id" => id = Some(value.into_owned()),
+                "name" => name = Some(value.into_owned()),
+                "offset" => offset = value.parse::<u64>().ok(),
+                "total" => total = value.parse::<u64>().ok(),
+                _ => {}
+            }
+        }
+        let missing = |field: &str| -> ServerFnError<CustErr> {
+            ServerFnError::Args(format!("missing or invalid `{field}`"))
+        };
+        let id = id.ok_or_else(|| missing("id"))?;
+        let name = name.unwrap_or_default();
+        let offset = offset.ok_or_else(|| missing("offset"))?;
+        let total = total.ok_or_else(|| missing("total"))?;
+        let data = req.try_into_bytes().await?;
+        if offset.saturating_add(data.len() as u64) > total {
+            return Err(ServerFnError::Args(format!(
+                "the chunk at {offset} runs past the end of the file of {total} \
+                 bytes"
+            )));
+        }
+        Ok(UploadChunk::new(id, name, offset, total, data).into())
+    }
+}
+
+#[cfg(all(test, feature = "generic"))]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+    use http::Request;
+
+    fn from_req(
+        query: &str,
+        body: &'static [u8],
+    ) -> Result<UploadChunk, ServerFnError> {
+        let req = Request::post(format!("/upload?{query}"))
+            .body(Bytes::from_static(body))
+            .unwrap();
+        block_on(<UploadChunk as FromReq<Chunked, _, _>>::from_req(req))
+    }
+
+    #[test]
+    fn reads_the_position_from_the_query() {
+        let chunk =
+            from_req("id=abc&name=video.mp4&offset=4&total=10", b"data")
+                .unwrap();
+        assert_eq!(
+            chunk,
+            UploadChunk::new(
+                "abc",
+                "video.mp4",
+                4,
+                10,
+                Bytes::from_static(b"data")
+            )
+        );
+    }
+
+    #[test]
+    fn requires_the_id_offset_and_total() {
+        for query in [
+            "offset=0&total=4",
+            "id=abc&total=4",
+            "id=abc&offset=0",
+            "id=abc&offset=-1&total=4",
+            "id=abc&offset=0&total=many",
+        ] {
+            assert!(
+                matches!(from_req(query, b"data"), Err(ServerFnError::Args(_))),
+                "{query}"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_chunks_past_the_end_of_the_file() {
+        assert!(matches!(
+            from_req("id=abc&offset=8&total=10", b"data"),
+            Err(ServerFnError::Args(_))
+        ));
+        assert!(from_req("id=abc&offset=6&total=10", b"data").is_ok());
+    }
+}
@@ -156,9 +156,9 @@ where
             accepts,
             Streaming::CONTENT_TYPE,
             data.0.map(|chunk| {
-                serde_json::to_vec(&chunk)
+                Ok(serde_json::to_vec(&chunk)
                     .unwrap_or_else(|_| Vec::new())
-                    .into()
+                    .into())
             }),
         )
     }
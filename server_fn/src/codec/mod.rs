@@ -44,6 +44,11 @@ mod multipart;
 #[cfg(feature = "multipart")]
 pub use multipart::*;
 
+#[cfg(feature = "upload")]
+mod chunked;
+#[cfg(feature = "upload")]
+pub use chunked::*;
+
 #[cfg(feature = "msgpack")]
 mod msgpack;
 #[cfg(feature = "msgpack")]
@@ -54,11 +59,22 @@ mod postcard;
 #[cfg(feature = "postcard")]
 pub use postcard::*;
 
+#[cfg(feature = "protobuf")]
+mod protobuf;
+#[cfg(feature = "protobuf")]
+pub use protobuf::*;
+
+mod sse;
 mod stream;
+#[cfg(feature = "websocket")]
+mod websocket;
 use crate::error::ServerFnError;
 use futures::Future;
 use http::Method;
+pub use sse::*;
 pub use stream::*;
+#[cfg(feature = "websocket")]
+pub use websocket::*;
 
 /// Serializes a data type into an HTTP request, on the client.
 ///
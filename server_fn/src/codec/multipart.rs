@@ -1,12 +1,16 @@
 use super::{Encoding, FromReq};
 use crate::{
     error::ServerFnError,
-    request::{browser::BrowserFormData, ClientReq, Req},
+    request::{
+        browser::BrowserFormData, too_large, BodyLimits, ClientReq, Req,
+    },
     IntoReq,
 };
-use futures::StreamExt;
+use bytes::Bytes;
+use futures::{Stream, StreamExt};
 use http::Method;
-use multer::Multipart;
+use multer::{Constraints, Multipart, SizeLimit};
+use std::fmt;
 use web_sys::FormData;
 
 /// Encodes multipart form data.
@@ -86,11 +90,145 @@ where
             .to_content_type()
             .and_then(|ct| multer::parse_boundary(ct).ok())
             .expect("couldn't parse boundary");
-        let stream = req.try_into_stream()?;
-        let data = multer::Multipart::new(
-            stream.map(|data| data.map_err(|e| e.to_string())),
+        let limits = req.body_limits();
+        let stream = limit_multipart(req.try_into_stream()?, &boundary, limits);
+        let mut size_limit = SizeLimit::new();
+        if let Some(max) = limits.max_field_size() {
+            size_limit = size_limit.per_field(max as u64);
+        }
+        let data = multer::Multipart::with_constraints(
+            stream.map(|data| {
+                data.map_err(|e| match e {
+                    ServerFnError::PayloadTooLarge(msg) => {
+                        Box::new(TooLarge(msg)) as BoxError
+                    }
+                    e => e.to_string().into(),
+                })
+            }),
             boundary,
+            Constraints::new().size_limit(size_limit),
         );
         Ok(MultipartData::Server(data).into())
     }
 }
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+// A limit on the whole body, passed through `multer` so it can be told apart from other
+// errors when the server function reads the fields.
+#[derive(Debug)]
+struct TooLarge(String);
+
+impl fmt::Display for TooLarge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for TooLarge {}
+
+/// Converts a [`multer::Error`] caused by one of the [`BodyLimits`] into
+/// [`ServerFnError::PayloadTooLarge`], so that it is returned with a `413` status.
+pub(crate) fn payload_too_large<CustErr>(
+    err: &(dyn std::error::Error + 'static),
+) -> Option<ServerFnError<CustErr>> {
+    let msg = match err.downcast_ref::<multer::Error>()? {
+        multer::Error::FieldSizeExceeded {
+            limit,
+            field_name: Some(name),
+        } => format!(
+            "the field `{name}` is larger than the limit of {limit} bytes"
+        ),
+        multer::Error::FieldSizeExceeded { limit, .. } => {
+            format!("a field is larger than the limit of {limit} bytes")
+        }
+        multer::Error::StreamSizeExceeded { limit } => {
+            format!(
+                "the request body is larger than the limit of {limit} bytes"
+            )
+        }
+        multer::Error::StreamReadFailed(e) => {
+            e.downcast_ref::<TooLarge>()?.0.clone()
+        }
+        _ => return None,
+    };
+    Some(ServerFnError::PayloadTooLarge(msg))
+}
+
+// Enforces the whole-body and field count limits as the body streams in.
+//
+// Every field starts with the boundary delimiter, and the body is closed by one more, so
+// the number of fields can be counted without parsing them.
+fn limit_multipart(
+    stream: impl Stream<Item = Result<Bytes, ServerFnError>> + Send + 'static,
+    boundary: &str,
+    limits: BodyLimits,
+) -> impl Stream<Item = Result<Bytes, ServerFnError>> + Send + 'static {
+    let delimiter = format!("--{boundary}").into_bytes();
+    // the end of the previous chunk, in case a delimiter is split between chunks
+    let mut tail = Vec::new();
+    let mut delimiters = 0;
+    let mut size = 0;
+
+    stream.map(move |chunk| {
+        let chunk = chunk?;
+        size += chunk.len();
+        if let Some(max) = limits.max_multipart_size() {
+            if size > max {
+                return Err(too_large(max));
+            }
+        }
+        if let Some(max) = limits.max_fields() {
+            let mut window = std::mem::take(&mut tail);
+            window.extend_from_slice(&chunk);
+            delimiters += window
+                .windows(delimiter.len())
+                .filter(|w| *w == delimiter.as_slice())
+                .count();
+            let keep = window.len().saturating_sub(delimiter.len() - 1);
+            tail = window.split_off(keep);
+            if delimiters > max + 1 {
+                return Err(ServerFnError::PayloadTooLarge(format!(
+                    "the request body has more than the limit of {max} fields"
+                )));
+            }
+        }
+        Ok(chunk)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the conversion made by `?` in a server function that returns `ServerFnError`
+    fn convert(err: multer::Error) -> ServerFnError {
+        err.into()
+    }
+
+    #[test]
+    fn field_size_limit_is_payload_too_large() {
+        let err = multer::Error::FieldSizeExceeded {
+            limit: 10,
+            field_name: Some("file".into()),
+        };
+        assert!(matches!(convert(err), ServerFnError::PayloadTooLarge(_)));
+    }
+
+    #[test]
+    fn body_limit_is_payload_too_large() {
+        let err = multer::Error::StreamReadFailed(Box::new(TooLarge(
+            "too large".into(),
+        )));
+        assert_eq!(
+            convert(err),
+            ServerFnError::PayloadTooLarge("too large".into())
+        );
+    }
+
+    #[test]
+    fn other_errors_are_server_errors() {
+        let err = multer::Error::StreamReadFailed("connection reset".into());
+        assert!(matches!(convert(err), ServerFnError::ServerError(_)));
+    }
+}
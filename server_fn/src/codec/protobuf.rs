@@ -0,0 +1,299 @@
+use super::{Encoding, FromReq, FromRes, IntoReq, IntoRes};
+use crate::{
+    error::{NoCustomError, ServerFnError},
+    request::{ClientReq, Req},
+    response::{ClientRes, Res},
+};
+use bytes::{Buf, Bytes, BytesMut};
+use futures::{stream, Stream, StreamExt};
+use http::Method;
+use prost::Message;
+use std::pin::Pin;
+
+/// Pass arguments and receive responses as [Protobuf](https://protobuf.dev/) messages in a
+/// `POST` request, using [`prost`].
+///
+/// The argument and return types should implement [`prost::Message`], like the types
+/// generated by `prost-build` from a `.proto` file. This allows the same endpoint to be called
+/// from clients written in other languages.
+///
+/// A Protobuf request body is a single message, so a server function that uses this as its
+/// input encoding must take exactly one argument, whose type is the message.
+///
+/// ```rust,ignore
+/// #[server(input = Protobuf, output = Protobuf)]
+/// pub async fn say_hello(
+///     req: HelloRequest,
+/// ) -> Result<HelloReply, ServerFnError> {
+///     Ok(HelloReply {
+///         message: format!("Hello, {}!", req.name),
+///     })
+/// }
+/// ```
+pub struct Protobuf;
+
+impl Encoding for Protobuf {
+    const CONTENT_TYPE: &'static str = "application/x-protobuf";
+    const METHOD: Method = Method::POST;
+}
+
+/// The arguments of a server function that uses the [`Protobuf`] input encoding.
+///
+/// The `#[server]` macro implements this for the arguments of any server function that uses
+/// [`Protobuf`], converting them to and from the message type of its only argument.
+pub trait ProtobufArgs: Sized {
+    /// The message sent as the body of the request.
+    type Message: Message + Default;
+
+    /// Converts the arguments into a message.
+    fn into_message(self) -> Self::Message;
+
+    /// Converts a message back into the arguments.
+    fn from_message(message: Self::Message) -> Self;
+}
+
+impl<CustErr, T, Request> IntoReq<Protobuf, Request, CustErr> for T
+where
+    Request: ClientReq<CustErr>,
+    T: ProtobufArgs + Send,
+{
+    fn into_req(
+        self,
+        path: &str,
+        accepts: &str,
+    ) -> Result<Request, ServerFnError<CustErr>> {
+        let data = self.into_message().encode_to_vec();
+        Request::try_new_post_bytes(
+            path,
+            accepts,
+            Protobuf::CONTENT_TYPE,
+            Bytes::from(data),
+        )
+    }
+}
+
+impl<CustErr, T, Request> FromReq<Protobuf, Request, CustErr> for T
+where
+    Request: Req<CustErr> + Send + 'static,
+    T: ProtobufArgs,
+{
+    async fn from_req(req: Request) -> Result<Self, ServerFnError<CustErr>> {
+        let data = req.try_into_bytes().await?;
+        T::Message::decode(data)
+            .map(T::from_message)
+            .map_err(|e| ServerFnError::Args(e.to_string()))
+    }
+}
+
+impl<CustErr, T, Response> IntoRes<Protobuf, Response, CustErr> for T
+where
+    Response: Res<CustErr>,
+    T: Message + Send,
+{
+    async fn into_res(self) -> Result<Response, ServerFnError<CustErr>> {
+        let data = self.encode_to_vec();
+        Response::try_from_bytes(Protobuf::CONTENT_TYPE, Bytes::from(data))
+    }
+}
+
+impl<CustErr, T, Response> FromRes<Protobuf, Response, CustErr> for T
+where
+    Response: ClientRes<CustErr> + Send,
+    T: Message + Default + Send,
+{
+    async fn from_res(res: Response) -> Result<Self, ServerFnError<CustErr>> {
+        let data = res.try_into_bytes().await?;
+        T::decode(data)
+            .map_err(|e| ServerFnError::Deserialization(e.to_string()))
+    }
+}
+
+/// An encoding that represents a stream of Protobuf messages.
+///
+/// Each message is prefixed with its length as a varint, which is the same framing used by
+/// `writeDelimitedTo` in the Java library and `protodelim` in Go.
+///
+/// A server function that uses this as its output encoding should return [`ProtobufStream`].
+///
+/// ## Browser Support for Streaming Input
+///
+/// Browser fetch requests do not currently support full request duplexing, which
+/// means that that they do begin handling responses until the full request has been sent.
+/// This means that if you use a streaming input encoding, the input stream needs to
+/// end before the output will begin.
+///
+/// Streaming requests are only allowed over HTTP2 or HTTP3.
+pub struct StreamingProtobuf;
+
+impl Encoding for StreamingProtobuf {
+    const CONTENT_TYPE: &'static str = "application/x-protobuf-stream";
+    const METHOD: Method = Method::POST;
+}
+
+/// A stream of Protobuf messages.
+///
+/// A server function can return this type if its output encoding is [`StreamingProtobuf`].
+pub struct ProtobufStream<T, CustErr = NoCustomError>(
+    Pin<Box<dyn Stream<Item = Result<T, ServerFnError<CustErr>>> + Send>>,
+);
+
+impl<T, CustErr> std::fmt::Debug for ProtobufStream<T, CustErr> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("ProtobufStream").finish()
+    }
+}
+
+impl<T> ProtobufStream<T> {
+    /// Creates a new `ProtobufStream` from the given stream.
+    pub fn new(
+        value: impl Stream<Item = Result<T, ServerFnError>> + Send + 'static,
+    ) -> Self {
+        Self(Box::pin(value))
+    }
+}
+
+impl<T, CustErr> ProtobufStream<T, CustErr> {
+    /// Consumes the wrapper, returning a stream of messages.
+    pub fn into_inner(
+        self,
+    ) -> impl Stream<Item = Result<T, ServerFnError<CustErr>>> + Send {
+        self.0
+    }
+}
+
+impl<S, T: 'static, CustErr: 'static> From<S> for ProtobufStream<T, CustErr>
+where
+    S: Stream<Item = T> + Send + 'static,
+{
+    fn from(value: S) -> Self {
+        Self(Box::pin(value.map(Ok)))
+    }
+}
+
+impl<CustErr, S, T, Request> IntoReq<StreamingProtobuf, Request, CustErr> for S
+where
+    Request: ClientReq<CustErr>,
+    S: Stream<Item = T> + Send + 'static,
+    T: Message + 'static,
+{
+    fn into_req(
+        self,
+        path: &str,
+        accepts: &str,
+    ) -> Result<Request, ServerFnError<CustErr>> {
+        let data: ProtobufStream<T> = self.into();
+        Request::try_new_streaming(
+            path,
+            accepts,
+            StreamingProtobuf::CONTENT_TYPE,
+            data.0.map(|chunk| {
+                chunk.map(|message| {
+                    message.encode_length_delimited_to_vec().into()
+                })
+            }),
+        )
+    }
+}
+
+impl<CustErr, T, S, Request> FromReq<StreamingProtobuf, Request, CustErr> for S
+where
+    Request: Req<CustErr> + Send + 'static,
+    // The additional `Stream<Item = T>` bound is never used, but it is required to avoid an error where `T` is unconstrained
+    S: Stream<Item = T> + From<ProtobufStream<T>> + Send + 'static,
+    T: Message + Default + 'static,
+{
+    async fn from_req(req: Request) -> Result<Self, ServerFnError<CustErr>> {
+        let data = req.try_into_stream()?;
+        let s = ProtobufStream::new(decode_delimited(data));
+        Ok(s.into())
+    }
+}
+
+impl<CustErr, T, Response> IntoRes<StreamingProtobuf, Response, CustErr>
+    for ProtobufStream<T, CustErr>
+where
+    Response: Res<CustErr>,
+    CustErr: 'static,
+    T: Message + 'static,
+{
+    async fn into_res(self) -> Result<Response, ServerFnError<CustErr>> {
+        Response::try_from_stream(
+            StreamingProtobuf::CONTENT_TYPE,
+            self.into_inner().map(|message| {
+                Ok(Bytes::from(message?.encode_length_delimited_to_vec()))
+            }),
+        )
+    }
+}
+
+impl<CustErr, T, Response> FromRes<StreamingProtobuf, Response, CustErr>
+    for ProtobufStream<T>
+where
+    Response: ClientRes<CustErr> + Send,
+    T: Message + Default + 'static,
+{
+    async fn from_res(res: Response) -> Result<Self, ServerFnError<CustErr>> {
+        let stream = res.try_into_stream()?;
+        Ok(ProtobufStream::new(decode_delimited(stream)))
+    }
+}
+
+/// Splits a stream of bytes into the length-delimited messages it contains.
+///
+/// Chunk boundaries don't need to line up with messages: a chunk may hold several
+/// messages, or only part of one.
+fn decode_delimited<T>(
+    data: impl Stream<Item = Result<Bytes, ServerFnError>> + Send + 'static,
+) -> impl Stream<Item = Result<T, ServerFnError>> + Send
+where
+    T: Message + Default,
+{
+    stream::unfold(
+        (Box::pin(data), BytesMut::new(), false),
+        |(mut data, mut buf, done)| async move {
+            loop {
+                if done {
+                    return None;
+                }
+                match next_message(&mut buf) {
+                    Ok(Some(message)) => {
+                        let message = T::decode(message).map_err(|e| {
+                            ServerFnError::Deserialization(e.to_string())
+                        });
+                        return Some((message, (data, buf, false)));
+                    }
+                    Ok(None) => {}
+                    Err(e) => return Some((Err(e), (data, buf, true))),
+                }
+                match data.next().await {
+                    Some(Ok(chunk)) => buf.extend_from_slice(&chunk),
+                    Some(Err(e)) => return Some((Err(e), (data, buf, true))),
+                    None if buf.is_empty() => return None,
+                    None => {
+                        let e = ServerFnError::Deserialization(
+                            "stream ended partway through a message".into(),
+                        );
+                        return Some((Err(e), (data, buf, true)));
+                    }
+                }
+            }
+        },
+    )
+}
+
+/// Removes the next complete message from the buffer, or returns `None` if more data is
+/// needed to read it.
+fn next_message(buf: &mut BytesMut) -> Result<Option<Bytes>, ServerFnError> {
+    let len = match prost::decode_length_delimiter(&buf[..]) {
+        Ok(len) => len,
+        // a varint is at most 10 bytes long, so a shorter one may still be incomplete
+        Err(_) if buf.len() < 10 => return Ok(None),
+        Err(e) => return Err(ServerFnError::Deserialization(e.to_string())),
+    };
+    let header = prost::length_delimiter_len(len);
+    if buf.len() < header + len {
+        return Ok(None);
+    }
+    buf.advance(header);
+    Ok(Some(buf.split_to(len).freeze()))
+}
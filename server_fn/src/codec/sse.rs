@@ -0,0 +1,712 @@
+#[cfg(feature = "json")]
+use super::JsonStream;
+use super::{Encoding, FromRes, IntoRes, TextStream};
+use crate::{
+    error::{NoCustomError, ServerFnError, ServerFnErrorSerde},
+    response::{ClientRes, Res},
+};
+use bytes::Bytes;
+use futures::{
+    stream::{self, Fuse},
+    Future, Stream, StreamExt,
+};
+use http::Method;
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    collections::VecDeque,
+    fmt::{Debug, Display, Write},
+    pin::Pin,
+    str::FromStr,
+};
+
+/// The header in which a reconnecting `EventSource` sends the id of the last event it received.
+pub const LAST_EVENT_ID_HEADER: &str = "Last-Event-ID";
+
+// Errors in the output stream are sent as an event with this name, rather than ending the
+// response body, so that the client can deserialize them.
+const ERROR_EVENT: &str = "serverfnerror";
+
+/// An encoding that sends a stream of items as [server-sent events](https://html.spec.whatwg.org/multipage/server-sent-events.html).
+///
+/// A server function that uses this as its output encoding can return an [`SseStream`],
+/// a [`TextStream`], or (with the `json` feature) a [`JsonStream`]. Each item is sent as a
+/// single `text/event-stream` event. Items in a [`TextStream`] are sent as they are; all
+/// other items are encoded as JSON.
+///
+/// Unlike [`Streaming`](super::Streaming), the response is marked as uncacheable and
+/// unbuffered, so that proxies pass each event along as soon as it is sent.
+///
+/// ```rust,ignore
+/// #[server(output = Sse)]
+/// async fn notifications() -> Result<SseStream<Notification>, ServerFnError> {
+///     Ok(notification_stream().into())
+/// }
+/// ```
+///
+/// ## Resuming a Stream
+///
+/// Every event has an id. Events in a [`TextStream`] or [`JsonStream`] are numbered from
+/// `0`, while an [`SseStream`] can set its own ids with [`SseEvent::with_id`].
+///
+/// When a browser’s native `EventSource` reconnects, it sends the id of the last event it
+/// received in the [`Last-Event-ID`](LAST_EVENT_ID_HEADER) header, so a server function
+/// with a `GetUrl` input encoding can be consumed by an `EventSource` directly. The server
+/// function can read this header using its integration’s extractors, and pass it to
+/// [`SseStream::resume_after`] to skip the events the client has already seen.
+///
+/// The typed clients don't send the `Last-Event-ID` header. Instead, wrap the call in
+/// [`SseStream::reconnecting`], which calls the server function again when the connection
+/// drops, passing the id of the last event received as an argument.
+pub struct Sse;
+
+impl Encoding for Sse {
+    const CONTENT_TYPE: &'static str = "text/event-stream";
+    const METHOD: Method = Method::POST;
+}
+
+/// A single event in an [`SseStream`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SseEvent<T> {
+    /// The id of the event, which is sent back by a reconnecting client.
+    pub id: Option<String>,
+    /// The name of the event, if it is not a plain `message`.
+    pub event: Option<String>,
+    /// The data carried by the event.
+    pub data: T,
+}
+
+impl<T> SseEvent<T> {
+    /// Creates a new event with the given data, and no id or name.
+    pub fn new(data: T) -> Self {
+        Self {
+            id: None,
+            event: None,
+            data,
+        }
+    }
+
+    /// Sets the id of the event. This should not contain any line breaks.
+    pub fn with_id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Sets the name of the event. This should not contain any line breaks.
+    pub fn with_event(mut self, event: impl Into<String>) -> Self {
+        self.event = Some(event.into());
+        self
+    }
+}
+
+/// A stream of typed [server-sent events](Sse), each encoded as JSON.
+///
+/// A server function can return this type if its output encoding is [`Sse`].
+#[allow(clippy::type_complexity)]
+pub struct SseStream<T, CustErr = NoCustomError>(
+    Pin<
+        Box<
+            dyn Stream<Item = Result<SseEvent<T>, ServerFnError<CustErr>>>
+                + Send,
+        >,
+    >,
+);
+
+impl<T, CustErr> Debug for SseStream<T, CustErr> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("SseStream").finish()
+    }
+}
+
+impl<T> SseStream<T> {
+    /// Creates a new `SseStream` from the given stream of events.
+    pub fn new(
+        value: impl Stream<Item = Result<SseEvent<T>, ServerFnError>>
+            + Send
+            + 'static,
+    ) -> Self {
+        Self(Box::pin(value))
+    }
+}
+
+impl<T, CustErr> SseStream<T, CustErr> {
+    /// Consumes the wrapper, returning a stream of events.
+    pub fn into_inner(
+        self,
+    ) -> impl Stream<Item = Result<SseEvent<T>, ServerFnError<CustErr>>> + Send
+    {
+        self.0
+    }
+
+    /// Skips every event up to and including the one with the given id.
+    ///
+    /// This is used to resume a stream from the
+    /// [`Last-Event-ID`](LAST_EVENT_ID_HEADER) sent by a reconnecting client. If
+    /// `last_event_id` is `None`, the stream is returned unchanged. Errors are never skipped.
+    ///
+    /// Events are held back until the id is found. If the stream ends without it (because
+    /// the id has expired, or the stream doesn't produce the same events each time), or more
+    /// than [`MAX_HELD_BACK_EVENTS`] events go by without it, the held back events are sent
+    /// after all, so the client receives the whole stream rather than none of it.
+    pub fn resume_after(self, last_event_id: Option<&str>) -> Self
+    where
+        T: Send + 'static,
+        CustErr: Send + 'static,
+    {
+        let Some(last_event_id) = last_event_id.map(str::to_owned) else {
+            return self;
+        };
+        let state = Resume {
+            events: self.0.fuse(),
+            last_event_id: Some(last_event_id),
+            skipped: VecDeque::new(),
+        };
+        Self(Box::pin(stream::unfold(state, |mut state| async move {
+            loop {
+                // once the id has been found or given up on, nothing more is held back
+                if state.last_event_id.is_none() {
+                    if let Some(event) = state.skipped.pop_front() {
+                        return Some((Ok(event), state));
+                    }
+                }
+                match state.events.next().await {
+                    Some(Ok(event)) => match &state.last_event_id {
+                        Some(id) if event.id.as_ref() == Some(id) => {
+                            state.last_event_id = None;
+                            state.skipped.clear();
+                        }
+                        Some(_) => {
+                            state.skipped.push_back(event);
+                            if state.skipped.len() >= MAX_HELD_BACK_EVENTS {
+                                state.last_event_id = None;
+                            }
+                        }
+                        None => return Some((Ok(event), state)),
+                    },
+                    Some(Err(e)) => return Some((Err(e), state)),
+                    // the id was never found, so nothing is skipped
+                    None if !state.skipped.is_empty() => {
+                        state.last_event_id = None
+                    }
+                    None => return None,
+                }
+            }
+        })))
+    }
+}
+
+impl<T> SseStream<T> {
+    /// Creates a stream that calls the server function again if the connection drops,
+    /// picking up where the last connection left off.
+    ///
+    /// `connect` is called with the id of the last event received, if any, and should call the
+    /// server function with it as an argument, so that the server can pass it to
+    /// [`SseStream::resume_after`]. Transport errors (`ServerFnError::Request` and
+    /// `ServerFnError::Response`) cause a reconnection, up to `max_retries` times in a row;
+    /// any other error, or a failure to reconnect, is passed on to the caller.
+    ///
+    /// ```rust,ignore
+    /// #[server(output = Sse)]
+    /// async fn notifications(
+    ///     last_event_id: Option<String>,
+    /// ) -> Result<SseStream<Notification>, ServerFnError> {
+    ///     Ok(SseStream::from(notification_stream())
+    ///         .resume_after(last_event_id.as_deref()))
+    /// }
+    ///
+    /// let events = SseStream::reconnecting(3, notifications);
+    /// ```
+    pub fn reconnecting<F, Fut, E>(max_retries: usize, connect: F) -> Self
+    where
+        T: Send + 'static,
+        F: FnMut(Option<String>) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<SseStream<T>, ServerFnError<E>>>
+            + Send
+            + 'static,
+        E: Display,
+    {
+        let state = Reconnect {
+            connect,
+            events: None,
+            last_event_id: None,
+            retries_left: max_retries,
+        };
+        Self(Box::pin(stream::unfold(
+            Some(state),
+            move |state| async move {
+                let mut state = state?;
+                loop {
+                    let events = match &mut state.events {
+                        Some(events) => events,
+                        None => {
+                            match (state.connect)(state.last_event_id.clone())
+                                .await
+                            {
+                                Ok(events) => state.events.insert(events.0),
+                                Err(e) => {
+                                    let e = e.map_custom_err(|e| {
+                                        ServerFnError::ServerError(
+                                            e.to_string(),
+                                        )
+                                    });
+                                    return Some((Err(e), None));
+                                }
+                            }
+                        }
+                    };
+                    match events.next().await {
+                        Some(Ok(event)) => {
+                            if event.id.is_some() {
+                                state.last_event_id.clone_from(&event.id);
+                            }
+                            state.retries_left = max_retries;
+                            return Some((Ok(event), Some(state)));
+                        }
+                        Some(Err(
+                            ServerFnError::Request(_)
+                            | ServerFnError::Response(_),
+                        )) if state.retries_left > 0 => {
+                            state.retries_left -= 1;
+                            state.events = None;
+                        }
+                        Some(Err(e)) => return Some((Err(e), Some(state))),
+                        None => return None,
+                    }
+                }
+            },
+        )))
+    }
+}
+
+/// The most events [`SseStream::resume_after`] holds back while looking for the last event
+/// the client received.
+pub const MAX_HELD_BACK_EVENTS: usize = 1024;
+
+#[allow(clippy::type_complexity)]
+struct Resume<T, CustErr> {
+    events: Fuse<
+        Pin<
+            Box<
+                dyn Stream<Item = Result<SseEvent<T>, ServerFnError<CustErr>>>
+                    + Send,
+            >,
+        >,
+    >,
+    // `None` once the last event the client received has been found
+    last_event_id: Option<String>,
+    skipped: VecDeque<SseEvent<T>>,
+}
+
+#[allow(clippy::type_complexity)]
+struct Reconnect<T, F> {
+    connect: F,
+    events: Option<
+        Pin<Box<dyn Stream<Item = Result<SseEvent<T>, ServerFnError>> + Send>>,
+    >,
+    last_event_id: Option<String>,
+    retries_left: usize,
+}
+
+impl<S, T: 'static, CustErr: 'static> From<S> for SseStream<T, CustErr>
+where
+    S: Stream<Item = T> + Send + 'static,
+{
+    fn from(value: S) -> Self {
+        Self(Box::pin(numbered(value.map(Ok))))
+    }
+}
+
+fn numbered<T, E>(
+    value: impl Stream<Item = Result<T, E>> + Send + 'static,
+) -> impl Stream<Item = Result<SseEvent<T>, E>> + Send + 'static
+where
+    T: 'static,
+    E: 'static,
+{
+    value.enumerate().map(|(id, item)| {
+        item.map(|data| SseEvent::new(data).with_id(id.to_string()))
+    })
+}
+
+fn encode_frame(id: Option<&str>, event: Option<&str>, data: &str) -> Bytes {
+    let mut frame = String::new();
+    if let Some(id) = id {
+        _ = writeln!(frame, "id: {id}");
+    }
+    if let Some(event) = event {
+        _ = writeln!(frame, "event: {event}");
+    }
+    for line in data.split('\n') {
+        _ = writeln!(frame, "data: {line}");
+    }
+    frame.push('\n');
+    Bytes::from(frame)
+}
+
+fn encode_events<T, CustErr>(
+    events: impl Stream<Item = Result<SseEvent<T>, ServerFnError<CustErr>>>
+        + Send
+        + 'static,
+    encode_data: fn(T) -> Result<String, ServerFnError<CustErr>>,
+) -> impl Stream<Item = Result<Bytes, ServerFnError<CustErr>>> + Send + 'static
+where
+    T: 'static,
+    CustErr: FromStr + Display + 'static,
+{
+    events.map(move |event| {
+        let frame = event.and_then(|event| {
+            let data = encode_data(event.data)?;
+            Ok(encode_frame(
+                event.id.as_deref(),
+                event.event.as_deref(),
+                &data,
+            ))
+        });
+        Ok(frame.unwrap_or_else(|e| {
+            let err = e.ser().unwrap_or_else(|_| e.to_string());
+            encode_frame(None, Some(ERROR_EVENT), &err)
+        }))
+    })
+}
+
+fn encode_json<T, CustErr>(data: T) -> Result<String, ServerFnError<CustErr>>
+where
+    T: Serialize,
+{
+    serde_json::to_string(&data)
+        .map_err(|e| ServerFnError::Serialization(e.to_string()))
+}
+
+fn event_stream_response<Response, CustErr>(
+    data: impl Stream<Item = Result<Bytes, ServerFnError<CustErr>>> + Send + 'static,
+) -> Result<Response, ServerFnError<CustErr>>
+where
+    Response: Res<CustErr>,
+{
+    let mut res = Response::try_from_stream(Sse::CONTENT_TYPE, data)?;
+    res.insert_header("cache-control", "no-cache");
+    // nginx buffers proxied responses unless told otherwise
+    res.insert_header("x-accel-buffering", "no");
+    Ok(res)
+}
+
+impl<CustErr, T, Response> IntoRes<Sse, Response, CustErr>
+    for SseStream<T, CustErr>
+where
+    Response: Res<CustErr>,
+    CustErr: FromStr + Display + 'static,
+    T: Serialize + 'static,
+{
+    async fn into_res(self) -> Result<Response, ServerFnError<CustErr>> {
+        event_stream_response(encode_events(self.0, encode_json))
+    }
+}
+
+#[cfg(feature = "json")]
+impl<CustErr, T, Response> IntoRes<Sse, Response, CustErr>
+    for JsonStream<T, CustErr>
+where
+    Response: Res<CustErr>,
+    CustErr: FromStr + Display + 'static,
+    T: Serialize + 'static,
+{
+    async fn into_res(self) -> Result<Response, ServerFnError<CustErr>> {
+        event_stream_response(encode_events(
+            numbered(self.into_inner()),
+            encode_json,
+        ))
+    }
+}
+
+impl<CustErr, Response> IntoRes<Sse, Response, CustErr> for TextStream<CustErr>
+where
+    Response: Res<CustErr>,
+    CustErr: FromStr + Display + 'static,
+{
+    async fn into_res(self) -> Result<Response, ServerFnError<CustErr>> {
+        event_stream_response(encode_events(numbered(self.into_inner()), Ok))
+    }
+}
+
+/// A single event as parsed from the response body, before its data is decoded.
+struct Frame {
+    id: Option<String>,
+    event: Option<String>,
+    data: String,
+}
+
+fn parse_frame(raw: &str) -> Option<Frame> {
+    let mut id = None;
+    let mut event = None;
+    let mut data: Option<String> = None;
+    for line in raw.lines() {
+        let (field, value) = line.split_once(':').unwrap_or((line, ""));
+        let value = value.strip_prefix(' ').unwrap_or(value);
+        match field {
+            "id" => id = Some(value.to_owned()),
+            "event" => event = Some(value.to_owned()),
+            "data" => match &mut data {
+                Some(data) => {
+                    data.push('\n');
+                    data.push_str(value);
+                }
+                None => data = Some(value.to_owned()),
+            },
+            // comments, `retry`, and unknown fields
+            _ => {}
+        }
+    }
+    // events without any data are not dispatched
+    data.map(|data| Frame { id, event, data })
+}
+
+fn decode_frames(
+    body: impl Stream<Item = Result<Bytes, ServerFnError>> + Send + 'static,
+) -> impl Stream<Item = Result<Frame, ServerFnError>> + Send + 'static {
+    let mut buf = Vec::new();
+    body.flat_map(move |chunk| {
+        let frames = match chunk {
+            Ok(chunk) => {
+                buf.extend(chunk.iter().filter(|byte| **byte != b'\r'));
+                let mut frames = Vec::new();
+                while let Some(end) =
+                    buf.windows(2).position(|window| window == b"\n\n")
+                {
+                    let raw = buf.drain(..end + 2).collect::<Vec<_>>();
+                    match String::from_utf8(raw) {
+                        Ok(raw) => frames.extend(parse_frame(&raw).map(Ok)),
+                        Err(e) => frames.push(Err(
+                            ServerFnError::Deserialization(e.to_string()),
+                        )),
+                    }
+                }
+                frames
+            }
+            Err(e) => vec![Err(e)],
+        };
+        stream::iter(frames)
+    })
+}
+
+fn decode_events<T>(
+    body: impl Stream<Item = Result<Bytes, ServerFnError>> + Send + 'static,
+    decode_data: fn(String) -> Result<T, ServerFnError>,
+) -> impl Stream<Item = Result<SseEvent<T>, ServerFnError>> + Send + 'static
+where
+    T: 'static,
+{
+    decode_frames(body).map(move |frame| {
+        let frame = frame?;
+        if frame.event.as_deref() == Some(ERROR_EVENT) {
+            return Err(ServerFnError::de(&frame.data));
+        }
+        Ok(SseEvent {
+            id: frame.id,
+            event: frame.event,
+            data: decode_data(frame.data)?,
+        })
+    })
+}
+
+fn decode_json<T>(data: String) -> Result<T, ServerFnError>
+where
+    T: DeserializeOwned,
+{
+    serde_json::from_str(&data)
+        .map_err(|e| ServerFnError::Deserialization(e.to_string()))
+}
+
+impl<CustErr, T, Response> FromRes<Sse, Response, CustErr> for SseStream<T>
+where
+    Response: ClientRes<CustErr> + Send,
+    T: DeserializeOwned + 'static,
+{
+    async fn from_res(res: Response) -> Result<Self, ServerFnError<CustErr>> {
+        let body = res.try_into_stream()?;
+        Ok(SseStream::new(decode_events(body, decode_json)))
+    }
+}
+
+#[cfg(feature = "json")]
+impl<CustErr, T, Response> FromRes<Sse, Response, CustErr> for JsonStream<T>
+where
+    Response: ClientRes<CustErr> + Send,
+    T: DeserializeOwned + 'static,
+{
+    async fn from_res(res: Response) -> Result<Self, ServerFnError<CustErr>> {
+        let body = res.try_into_stream()?;
+        Ok(JsonStream::new(
+            decode_events(body, decode_json)
+                .map(|event| event.map(|event| event.data)),
+        ))
+    }
+}
+
+impl<CustErr, Response> FromRes<Sse, Response, CustErr> for TextStream
+where
+    Response: ClientRes<CustErr> + Send,
+{
+    async fn from_res(res: Response) -> Result<Self, ServerFnError<CustErr>> {
+        let body = res.try_into_stream()?;
+        Ok(TextStream::new(
+            decode_events(body, Ok).map(|event| event.map(|event| event.data)),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        decode_frames, encode_frame, parse_frame, SseEvent, SseStream,
+        MAX_HELD_BACK_EVENTS,
+    };
+    use crate::error::ServerFnError;
+    use bytes::Bytes;
+    use futures::{executor::block_on, stream, StreamExt};
+    use std::sync::{Arc, Mutex};
+
+    fn decode(chunks: &[&str]) -> Vec<(Option<String>, String)> {
+        let body = stream::iter(
+            chunks
+                .iter()
+                .map(|chunk| Ok(Bytes::copy_from_slice(chunk.as_bytes())))
+                .collect::<Vec<_>>(),
+        );
+        block_on(decode_frames(body).collect::<Vec<_>>())
+            .into_iter()
+            .map(|frame| {
+                let frame = frame.unwrap();
+                (frame.id, frame.data)
+            })
+            .collect()
+    }
+
+    fn ids(events: Vec<Result<SseEvent<u32>, ServerFnError>>) -> Vec<u32> {
+        events
+            .into_iter()
+            .map(|event| event.unwrap().data)
+            .collect()
+    }
+
+    fn numbered(n: u32) -> SseStream<u32> {
+        SseStream::from(stream::iter(0..n))
+    }
+
+    #[test]
+    fn encodes_each_line_of_data() {
+        let frame = encode_frame(Some("3"), Some("update"), "a\nb");
+        assert_eq!(frame, "id: 3\nevent: update\ndata: a\ndata: b\n\n");
+        assert_eq!(encode_frame(None, None, ""), "data: \n\n");
+    }
+
+    #[test]
+    fn parses_an_encoded_frame() {
+        let encoded = encode_frame(Some("7"), Some("update"), "a\nb");
+        let frame = parse_frame(std::str::from_utf8(&encoded).unwrap())
+            .expect("frame has data");
+        assert_eq!(frame.id.as_deref(), Some("7"));
+        assert_eq!(frame.event.as_deref(), Some("update"));
+        assert_eq!(frame.data, "a\nb");
+    }
+
+    #[test]
+    fn ignores_comments_and_frames_without_data() {
+        assert!(parse_frame(": keep-alive\n").is_none());
+        assert!(parse_frame("id: 1\nretry: 100\n").is_none());
+        let frame = parse_frame(": hi\ndata:no space\nunknown: x\n").unwrap();
+        assert_eq!(frame.data, "no space");
+    }
+
+    #[test]
+    fn decodes_frames_split_across_chunks() {
+        let frames =
+            decode(&["id: 0\nda", "ta: one\n", "\nid: 1\ndata: two\n\nda"]);
+        assert_eq!(
+            frames,
+            vec![
+                (Some("0".into()), "one".into()),
+                (Some("1".into()), "two".into())
+            ]
+        );
+    }
+
+    #[test]
+    fn decodes_crlf_line_endings() {
+        let frames = decode(&["data: a\r\ndata: b\r\n\r\n"]);
+        assert_eq!(frames, vec![(None, "a\nb".into())]);
+    }
+
+    #[test]
+    fn resumes_after_the_last_event_id() {
+        let events = numbered(5).resume_after(Some("1")).into_inner();
+        assert_eq!(ids(block_on(events.collect())), vec![2, 3, 4]);
+    }
+
+    #[test]
+    fn resumes_from_the_start_if_the_id_is_missing() {
+        let events = numbered(3).resume_after(Some("expired")).into_inner();
+        assert_eq!(ids(block_on(events.collect())), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn stops_holding_back_events_after_the_limit() {
+        let n = MAX_HELD_BACK_EVENTS as u32 + 5;
+        let events = numbered(n).resume_after(Some("expired")).into_inner();
+        assert_eq!(ids(block_on(events.collect())), (0..n).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn reconnects_from_the_last_event_id() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let events = SseStream::reconnecting(1, {
+            let calls = Arc::clone(&calls);
+            move |last_event_id: Option<String>| {
+                calls.lock().unwrap().push(last_event_id.clone());
+                let start = last_event_id
+                    .map(|id| id.parse::<u32>().unwrap() + 1)
+                    .unwrap_or(0);
+                // the first connection drops after two events
+                let dropped = (start == 0).then(|| {
+                    Err(ServerFnError::Request("connection reset".into()))
+                });
+                let events = (start..start + 2)
+                    .map(|n| Ok(SseEvent::new(n).with_id(n.to_string())))
+                    .chain(dropped);
+                async move {
+                    Ok::<_, ServerFnError>(SseStream::new(stream::iter(
+                        events.collect::<Vec<_>>(),
+                    )))
+                }
+            }
+        })
+        .into_inner();
+        assert_eq!(ids(block_on(events.collect())), vec![0, 1, 2, 3]);
+        assert_eq!(*calls.lock().unwrap(), vec![None, Some("1".to_string())]);
+    }
+
+    #[test]
+    fn stops_reconnecting_after_the_retries_run_out() {
+        let events = SseStream::<u32>::reconnecting(2, |_| async {
+            Ok::<_, ServerFnError>(SseStream::new(stream::iter(vec![Err(
+                ServerFnError::Request("connection reset".into()),
+            )])))
+        })
+        .into_inner();
+        let events = block_on(events.collect::<Vec<_>>());
+        assert_eq!(events.len(), 1);
+        assert!(matches!(events[0], Err(ServerFnError::Request(_))));
+    }
+
+    #[test]
+    fn resuming_never_skips_errors() {
+        let events = SseStream::new(stream::iter(vec![
+            Ok(SseEvent::new(0).with_id("0")),
+            Err(ServerFnError::Response("oops".into())),
+            Ok(SseEvent::new(1).with_id("1")),
+        ]))
+        .resume_after(Some("0"))
+        .into_inner();
+        let events = block_on(events.collect::<Vec<_>>());
+        assert!(events[0].is_err());
+        assert_eq!(events[1].as_ref().unwrap().data, 1);
+    }
+}
@@ -6,13 +6,30 @@ use crate::{
     IntoRes,
 };
 use bytes::Bytes;
-use futures::{Stream, StreamExt};
+use futures::{future::ready, stream, Stream, StreamExt};
 use http::Method;
 use std::{fmt::Debug, pin::Pin};
 
 /// An encoding that represents a stream of bytes.
 ///
 /// A server function that uses this as its output encoding should return [`ByteStream`].
+/// A server function that uses it as its input encoding should take a single [`ByteStream`]
+/// argument, which it can read as the request body arrives, without buffering it:
+///
+/// ```rust,ignore
+/// #[server(input = Streaming)]
+/// pub async fn import(data: ByteStream) -> Result<usize, ServerFnError> {
+///     let mut data = data.into_inner();
+///     let mut len = 0;
+///     while let Some(chunk) = data.next().await {
+///         len += chunk?.len();
+///     }
+///     Ok(len)
+/// }
+/// ```
+///
+/// The body is only read as fast as the server function consumes it, so a slow consumer
+/// slows down the client rather than filling up memory on the server.
 ///
 /// ## Browser Support for Streaming Input
 ///
@@ -21,7 +38,9 @@ use std::{fmt::Debug, pin::Pin};
 /// This means that if you use a streaming input encoding, the input stream needs to
 /// end before the output will begin.
 ///
-/// Streaming requests are only allowed over HTTP2 or HTTP3.
+/// Streaming requests are only allowed over HTTP2 or HTTP3. Browsers that cannot send a
+/// `ReadableStream` as a request body at all fall back to reading the whole stream into
+/// memory and sending it in one piece.
 pub struct Streaming;
 
 impl Encoding for Streaming {
@@ -32,14 +51,20 @@ impl Encoding for Streaming {
 impl<CustErr, T, Request> IntoReq<Streaming, Request, CustErr> for T
 where
     Request: ClientReq<CustErr>,
-    T: Stream<Item = Bytes> + Send + Sync + 'static,
+    T: Into<ByteStream>,
 {
     fn into_req(
         self,
         path: &str,
         accepts: &str,
     ) -> Result<Request, ServerFnError<CustErr>> {
-        Request::try_new_streaming(path, accepts, Streaming::CONTENT_TYPE, self)
+        let data = self.into();
+        Request::try_new_streaming(
+            path,
+            accepts,
+            Streaming::CONTENT_TYPE,
+            data.0,
+        )
     }
 }
 
@@ -66,7 +91,9 @@ where
 /// This means that if you use a streaming input encoding, the input stream needs to
 /// end before the output will begin.
 ///
-/// Streaming requests are only allowed over HTTP2 or HTTP3.
+/// Streaming requests are only allowed over HTTP2 or HTTP3. Browsers that cannot send a
+/// `ReadableStream` as a request body at all fall back to reading the whole stream into
+/// memory and sending it in one piece.
 pub struct ByteStream<CustErr = NoCustomError>(
     Pin<Box<dyn Stream<Item = Result<Bytes, ServerFnError<CustErr>>> + Send>>,
 );
@@ -140,7 +167,9 @@ where
 /// This means that if you use a streaming input encoding, the input stream needs to
 /// end before the output will begin.
 ///
-/// Streaming requests are only allowed over HTTP2 or HTTP3.
+/// Streaming requests are only allowed over HTTP2 or HTTP3. Browsers that cannot send a
+/// `ReadableStream` as a request body at all fall back to reading the whole stream into
+/// memory and sending it in one piece.
 pub struct StreamingText;
 
 impl Encoding for StreamingText {
@@ -159,7 +188,9 @@ impl Encoding for StreamingText {
 /// This means that if you use a streaming input encoding, the input stream needs to
 /// end before the output will begin.
 ///
-/// Streaming requests are only allowed over HTTP2 or HTTP3.
+/// Streaming requests are only allowed over HTTP2 or HTTP3. Browsers that cannot send a
+/// `ReadableStream` as a request body at all fall back to reading the whole stream into
+/// memory and sending it in one piece.
 pub struct TextStream<CustErr = NoCustomError>(
     Pin<Box<dyn Stream<Item = Result<String, ServerFnError<CustErr>>> + Send>>,
 );
@@ -213,7 +244,7 @@ where
             path,
             accepts,
             Streaming::CONTENT_TYPE,
-            data.0.map(|chunk| chunk.unwrap_or_default().into()),
+            data.0.map(|chunk| chunk.map(Bytes::from)),
         )
     }
 }
@@ -225,12 +256,7 @@ where
 {
     async fn from_req(req: Request) -> Result<Self, ServerFnError<CustErr>> {
         let data = req.try_into_stream()?;
-        let s = TextStream::new(data.map(|chunk| {
-            chunk.and_then(|bytes| {
-                String::from_utf8(bytes.to_vec())
-                    .map_err(|e| ServerFnError::Deserialization(e.to_string()))
-            })
-        }));
+        let s = TextStream::new(decode_utf8(data));
         Ok(s.into())
     }
 }
@@ -255,11 +281,126 @@ where
 {
     async fn from_res(res: Response) -> Result<Self, ServerFnError<CustErr>> {
         let stream = res.try_into_stream()?;
-        Ok(TextStream(Box::pin(stream.map(|chunk| {
-            chunk.and_then(|bytes| {
-                String::from_utf8(bytes.into())
-                    .map_err(|e| ServerFnError::Deserialization(e.to_string()))
-            })
-        }))))
+        Ok(TextStream(Box::pin(decode_utf8(stream))))
+    }
+}
+
+/// Decodes a stream of bytes as UTF-8, holding back any character that is split between two
+/// chunks until the rest of it arrives.
+fn decode_utf8(
+    stream: impl Stream<Item = Result<Bytes, ServerFnError>> + Send + 'static,
+) -> impl Stream<Item = Result<String, ServerFnError>> + Send + 'static {
+    stream
+        .map(Some)
+        .chain(stream::once(ready(None)))
+        .scan(Vec::new(), |partial: &mut Vec<u8>, chunk| {
+            let text = match chunk {
+                Some(Ok(bytes)) => {
+                    partial.extend_from_slice(&bytes);
+                    let valid = match std::str::from_utf8(partial) {
+                        Ok(_) => partial.len(),
+                        // the last character is incomplete
+                        Err(e) if e.error_len().is_none() => e.valid_up_to(),
+                        Err(e) => {
+                            partial.clear();
+                            return ready(Some(Some(Err(
+                                ServerFnError::Deserialization(e.to_string()),
+                            ))));
+                        }
+                    };
+                    let rest = partial.split_off(valid);
+                    let text = std::mem::replace(partial, rest);
+                    Some(String::from_utf8(text).map_err(|e| {
+                        ServerFnError::Deserialization(e.to_string())
+                    }))
+                }
+                Some(Err(e)) => Some(Err(e)),
+                None if partial.is_empty() => None,
+                None => Some(Err(ServerFnError::Deserialization(
+                    "the stream ended in the middle of a character".to_string(),
+                ))),
+            };
+            ready(Some(text))
+        })
+        .filter_map(|text| {
+            ready(
+                text.filter(
+                    |text| !matches!(text, Ok(text) if text.is_empty()),
+                ),
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+
+    fn ok(bytes: &'static [u8]) -> Result<Bytes, ServerFnError> {
+        Ok(Bytes::from_static(bytes))
+    }
+
+    fn decode(
+        chunks: Vec<Result<Bytes, ServerFnError>>,
+    ) -> Vec<Result<String, ServerFnError>> {
+        block_on(decode_utf8(stream::iter(chunks)).collect())
+    }
+
+    #[test]
+    fn passes_whole_characters_through() {
+        assert_eq!(
+            decode(vec![ok(b"hello "), ok(b"world")]),
+            vec![Ok("hello ".to_string()), Ok("world".to_string())]
+        );
+    }
+
+    #[test]
+    fn joins_characters_split_between_chunks() {
+        // "é" is 0xC3 0xA9, and "🦀" is 0xF0 0x9F 0xA6 0x80
+        assert_eq!(
+            decode(vec![
+                ok(b"caf\xC3"),
+                ok(b"\xA9 \xF0\x9F"),
+                ok(b"\xA6"),
+                ok(b"\x80!")
+            ]),
+            vec![
+                Ok("caf".to_string()),
+                Ok("é ".to_string()),
+                Ok("🦀!".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn fails_on_invalid_bytes_and_recovers() {
+        let decoded = decode(vec![ok(b"ok"), ok(b"\xFF\xFE"), ok(b"again")]);
+        assert_eq!(decoded.len(), 3);
+        assert_eq!(decoded[0], Ok("ok".to_string()));
+        assert!(matches!(decoded[1], Err(ServerFnError::Deserialization(_))));
+        assert_eq!(decoded[2], Ok("again".to_string()));
+    }
+
+    #[test]
+    fn fails_if_the_stream_ends_inside_a_character() {
+        let decoded = decode(vec![ok(b"ab\xE2\x82")]);
+        assert_eq!(decoded.len(), 2);
+        assert_eq!(decoded[0], Ok("ab".to_string()));
+        assert!(matches!(decoded[1], Err(ServerFnError::Deserialization(_))));
+    }
+
+    #[test]
+    fn passes_errors_through() {
+        let decoded = decode(vec![
+            ok(b"a"),
+            Err(ServerFnError::Request("connection reset".into())),
+        ]);
+        assert_eq!(
+            decoded,
+            vec![
+                Ok("a".to_string()),
+                Err(ServerFnError::Request("connection reset".into()))
+            ]
+        );
     }
 }
@@ -143,3 +143,12 @@ where
     }
 }
  */
+
+/// Encodes server function arguments as the URL-encoded form a `<form>` would submit.
+#[doc(hidden)]
+pub fn form_values<T>(args: &T) -> Option<String>
+where
+    T: Serialize,
+{
+    serde_qs::to_string(args).ok()
+}
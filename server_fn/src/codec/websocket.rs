@@ -0,0 +1,211 @@
+use super::{ByteStream, Encoding, FromReq, FromRes, IntoReq, IntoRes};
+use crate::{
+    client::Client,
+    error::{ServerFnError, ServerFnErrorSerde},
+    request::WebsocketReq,
+    response::Res,
+    ServerFn,
+};
+use bytes::Bytes;
+use futures::{Future, Stream, StreamExt};
+use http::Method;
+use serde::{de::DeserializeOwned, Serialize};
+use std::{fmt::Debug, marker::PhantomData, pin::Pin};
+
+/// A bidirectional protocol that opens a websocket connection to the server function,
+/// rather than making a single request and receiving a single response.
+///
+/// A server function that uses this protocol takes a single [`BoxedStream`] of `In` messages
+/// as its argument, and returns a [`BoxedStream`] of `Out` messages. Each message is encoded
+/// as JSON.
+///
+/// This requires the `websocket` feature. On Actix, enable `actix-websocket` instead, and on
+/// the native client, `reqwest-websocket`.
+///
+/// ```rust,ignore
+/// #[server(protocol = Websocket<String, String>)]
+/// async fn shout(
+///     input: BoxedStream<String>,
+/// ) -> Result<BoxedStream<String>, ServerFnError> {
+///     Ok(input
+///         .into_inner()
+///         .map(|msg| msg.map(|msg| msg.to_uppercase()))
+///         .into())
+/// }
+/// ```
+///
+/// ## Establishing the Connection
+///
+/// The upgrade response is only sent once the server function has returned its output
+/// stream, so the function body should return that stream without waiting for any input.
+pub struct Websocket<In, Out>(PhantomData<fn() -> (In, Out)>);
+
+impl<In, Out> Encoding for Websocket<In, Out> {
+    const CONTENT_TYPE: &'static str = "application/json";
+    const METHOD: Method = Method::GET;
+}
+
+/// A stream of typed messages sent over a [`Websocket`].
+pub struct BoxedStream<T>(
+    Pin<Box<dyn Stream<Item = Result<T, ServerFnError>> + Send>>,
+);
+
+impl<T> Debug for BoxedStream<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("BoxedStream").finish()
+    }
+}
+
+impl<T> BoxedStream<T> {
+    /// Creates a new `BoxedStream` from the given stream.
+    pub fn new(
+        value: impl Stream<Item = Result<T, ServerFnError>> + Send + 'static,
+    ) -> Self {
+        Self(Box::pin(value))
+    }
+
+    /// Consumes the wrapper, returning a stream of messages.
+    pub fn into_inner(
+        self,
+    ) -> impl Stream<Item = Result<T, ServerFnError>> + Send {
+        self.0
+    }
+}
+
+impl<S, T> From<S> for BoxedStream<T>
+where
+    S: Stream<Item = T> + Send + 'static,
+    T: 'static,
+{
+    fn from(value: S) -> Self {
+        Self(Box::pin(value.map(Ok)))
+    }
+}
+
+// Messages are framed as a serialized `Result`, so that errors in either stream reach the
+// other side of the connection rather than silently closing it.
+fn encode_message<T>(
+    msg: Result<T, ServerFnError>,
+) -> Result<Bytes, ServerFnError>
+where
+    T: Serialize,
+{
+    let msg = msg.map_err(|e| e.ser().unwrap_or_else(|_| e.to_string()));
+    serde_json::to_vec(&msg)
+        .map(Bytes::from)
+        .map_err(|e| ServerFnError::Serialization(e.to_string()))
+}
+
+fn decode_message<T>(
+    msg: Result<Bytes, ServerFnError>,
+) -> Result<T, ServerFnError>
+where
+    T: DeserializeOwned,
+{
+    serde_json::from_slice::<Result<T, String>>(&msg?)
+        .map_err(|e| ServerFnError::Deserialization(e.to_string()))
+        .and_then(|msg| msg.map_err(|e| ServerFnError::de(&e)))
+}
+
+impl<In, Out> Websocket<In, Out>
+where
+    In: Serialize + DeserializeOwned + Send + 'static,
+    Out: Serialize + DeserializeOwned + Send + 'static,
+{
+    /// Upgrades the request to a websocket connection, runs the server function on the
+    /// stream of incoming messages, and forwards its output stream to the client.
+    #[doc(hidden)]
+    pub async fn run_server<F>(req: F::ServerRequest) -> F::ServerResponse
+    where
+        F: ServerFn<Output = BoxedStream<Out>> + From<BoxedStream<In>>,
+        F::ServerRequest:
+            WebsocketReq<F::Error, WebsocketResponse = F::ServerResponse>,
+    {
+        let (incoming, outgoing, res) = match req.try_into_websocket().await {
+            Ok(parts) => parts,
+            Err(e) => return F::ServerResponse::error_response(F::PATH, &e),
+        };
+        let input =
+            BoxedStream::<In>::new(incoming.into_inner().map(decode_message));
+        match F::from(input).run_body().await {
+            Ok(output) => {
+                // if this fails, the connection has already been closed
+                _ = outgoing
+                    .send(ByteStream::new(output.0.map(encode_message::<Out>)));
+                res
+            }
+            Err(e) => F::ServerResponse::error_response(F::PATH, &e),
+        }
+    }
+
+    /// Opens a websocket connection to the server function, sending the messages in its
+    /// input stream and returning the stream of messages it sends back.
+    #[doc(hidden)]
+    pub async fn run_client<F>(
+        args: F,
+    ) -> Result<BoxedStream<Out>, ServerFnError<F::Error>>
+    where
+        F: ServerFn<Output = BoxedStream<Out>> + Into<BoxedStream<In>>,
+    {
+        let input: BoxedStream<In> = args.into();
+        let outgoing = ByteStream::new(input.0.map(encode_message::<In>));
+        let incoming =
+            <F::Client as Client<F::Error>>::open_websocket(F::PATH, outgoing)
+                .await?;
+        Ok(BoxedStream::new(incoming.into_inner().map(decode_message)))
+    }
+}
+
+// Server functions that use the websocket protocol replace the request/response steps
+// entirely, by calling `Websocket::run_client` and `Websocket::run_server` directly. These
+// impls only exist to satisfy the bounds on `ServerFn`, and are never called. They return
+// async blocks rather than using `async fn`, which would capture arguments that aren't `Send`.
+
+const NOT_HTTP: &str =
+    "Websocket server functions cannot be called with a plain HTTP request.";
+
+impl<In, Out, CustErr, T, Request> IntoReq<Websocket<In, Out>, Request, CustErr>
+    for T
+{
+    fn into_req(
+        self,
+        _path: &str,
+        _accepts: &str,
+    ) -> Result<Request, ServerFnError<CustErr>> {
+        Err(ServerFnError::Request(NOT_HTTP.into()))
+    }
+}
+
+impl<In, Out, CustErr, T, Request> FromReq<Websocket<In, Out>, Request, CustErr>
+    for T
+{
+    #[allow(clippy::manual_async_fn)]
+    fn from_req(
+        _req: Request,
+    ) -> impl Future<Output = Result<Self, ServerFnError<CustErr>>> + Send {
+        async { Err(ServerFnError::Args(NOT_HTTP.into())) }
+    }
+}
+
+impl<In, Out, CustErr, T, Response>
+    IntoRes<Websocket<In, Out>, Response, CustErr> for T
+{
+    #[allow(clippy::manual_async_fn)]
+    fn into_res(
+        self,
+    ) -> impl Future<Output = Result<Response, ServerFnError<CustErr>>> + Send
+    {
+        async { Err(ServerFnError::Response(NOT_HTTP.into())) }
+    }
+}
+
+impl<In, Out, CustErr, T, Response>
+    FromRes<Websocket<In, Out>, Response, CustErr> for T
+{
+    #[allow(clippy::manual_async_fn)]
+    fn from_res(
+        _res: Response,
+    ) -> impl Future<Output = Result<Self, ServerFnError<CustErr>>> + Send {
+        async { Err(ServerFnError::Deserialization(NOT_HTTP.into())) }
+    }
+}
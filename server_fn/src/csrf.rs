@@ -0,0 +1,437 @@
+//! Protection against [cross-site request forgery](https://owasp.org/www-community/attacks/csrf)
+//! (CSRF) for server functions.
+//!
+//! Server functions that accept `POST` requests can be called by a `<form>` on any other
+//! site, and the browser will send the user's cookies along with the request. This module
+//! guards against that with the "double-submit cookie" pattern:
+//!
+//! 1. While rendering a page, the server integration gives the browser a random token in the
+//!    [`CSRF_COOKIE`] cookie, which is only readable by pages on the same site.
+//! 2. Every request to a server function sends the token back, either in the [`CSRF_HEADER`]
+//!    header (added by the server function client), or in the [`CSRF_FIELD`] field of a
+//!    URL-encoded form (added by `<ActionForm/>`, `<MultiActionForm/>` and the router's
+//!    `<Form/>`, so it works before the WASM has loaded).
+//! 3. The [`Csrf`] middleware rejects any request whose token doesn't match its cookie with
+//!    [`ServerFnError::InvalidCsrfToken`] and a `403 Forbidden` status.
+//!
+//! Protection is opt-in. Enable the `csrf` feature on `leptos` and your server integration,
+//! then either call [`enable_csrf_protection`] once at startup to check every server function,
+//! or add `#[middleware(Csrf)]` to individual server functions.
+//!
+//! Requests that use safe methods (`GET`, `HEAD` and `OPTIONS`) are not checked, so server
+//! functions with side effects should not use a `GET` input encoding.
+
+#[cfg(feature = "ssr")]
+pub use server::*;
+
+/// The name of the cookie that holds the CSRF token.
+pub const CSRF_COOKIE: &str = "leptos_csrf";
+
+/// The name of the header in which the server function client sends the CSRF token.
+pub const CSRF_HEADER: &str = "x-csrf-token";
+
+/// The name of the form field in which forms send the CSRF token.
+pub const CSRF_FIELD: &str = "__csrf";
+
+/// Finds the CSRF token in the value of a `Cookie` header.
+pub fn token_from_cookies(cookies: &str) -> Option<&str> {
+    cookies.split(';').find_map(|cookie| {
+        let (name, value) = cookie.trim().split_once('=')?;
+        (name == CSRF_COOKIE && !value.is_empty()).then_some(value)
+    })
+}
+
+/// The `Set-Cookie` header value that gives the browser a new CSRF token.
+///
+/// The cookie can't be `HttpOnly`, because the client needs to read it to send the token back.
+pub fn set_cookie_header(token: &str) -> String {
+    format!("{CSRF_COOKIE}={token}; Path=/; SameSite=Lax")
+}
+
+/// Returns the CSRF token in the browser's cookies, if there is one.
+///
+/// This always returns `None` outside the browser.
+pub fn client_token() -> Option<String> {
+    #[cfg(all(feature = "browser", target_arch = "wasm32"))]
+    {
+        use wasm_bindgen::JsCast;
+
+        let cookies = web_sys::window()?
+            .document()?
+            .dyn_into::<web_sys::HtmlDocument>()
+            .ok()?
+            .cookie()
+            .ok()?;
+        token_from_cookies(&cookies).map(str::to_string)
+    }
+    #[cfg(not(all(feature = "browser", target_arch = "wasm32")))]
+    {
+        None
+    }
+}
+
+#[cfg(feature = "ssr")]
+mod server {
+    use super::{token_from_cookies, CSRF_FIELD, CSRF_HEADER};
+    use crate::{
+        error::{NoCustomError, ServerFnError},
+        middleware::{BoxedService, Layer, RequestInfo, Service},
+        request::get_body_limits,
+        response::Res,
+    };
+    use http::Method;
+    use std::{
+        borrow::Cow,
+        future::Future,
+        pin::Pin,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc, Mutex,
+        },
+    };
+
+    static CSRF_PROTECTION: AtomicBool = AtomicBool::new(false);
+
+    /// Checks the CSRF token of every request to a server function from now on.
+    ///
+    /// This is applied by `handle_server_fn` in the `axum` and `actix` integrations, in front
+    /// of any middleware added to the server function itself.
+    pub fn enable_csrf_protection() {
+        CSRF_PROTECTION.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether [`enable_csrf_protection`] has been called.
+    pub fn csrf_protection_enabled() -> bool {
+        CSRF_PROTECTION.load(Ordering::Relaxed)
+    }
+
+    /// A server request whose body can be searched for the CSRF token of a form submission.
+    pub trait CsrfRequest: RequestInfo + Sized + Send + 'static {
+        /// Reads the value of the given field from a URL-encoded form body, returning the
+        /// request with its body intact so it can still be decoded by the server function.
+        fn take_form_field(
+            self,
+            name: &'static str,
+        ) -> impl Future<Output = (Self, Option<String>)> + Send;
+    }
+
+    fn form_field(body: &[u8], name: &str) -> Option<String> {
+        url::form_urlencoded::parse(body)
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.into_owned())
+    }
+
+    #[cfg(feature = "axum-no-default")]
+    impl CsrfRequest for http::Request<axum::body::Body> {
+        async fn take_form_field(
+            self,
+            name: &'static str,
+        ) -> (Self, Option<String>) {
+            use axum::body::Body;
+
+            let (parts, body) = self.into_parts();
+            let max = get_body_limits().max_body_size().unwrap_or(usize::MAX);
+            match axum::body::to_bytes(body, max).await {
+                Ok(bytes) => {
+                    let value = form_field(&bytes, name);
+                    (http::Request::from_parts(parts, Body::from(bytes)), value)
+                }
+                Err(_) => {
+                    (http::Request::from_parts(parts, Body::empty()), None)
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "generic")]
+    impl CsrfRequest for http::Request<bytes::Bytes> {
+        async fn take_form_field(
+            self,
+            name: &'static str,
+        ) -> (Self, Option<String>) {
+            let value = form_field(self.body(), name);
+            (self, value)
+        }
+    }
+
+    #[cfg(feature = "actix")]
+    impl CsrfRequest for crate::request::actix::ActixRequest {
+        fn take_form_field(
+            self,
+            name: &'static str,
+        ) -> impl Future<Output = (Self, Option<String>)> + Send {
+            use actix_web::{dev, web::Payload, FromRequest};
+
+            // Actix keeps the request on a single thread, like the other uses of `SendWrapper`
+            send_wrapper::SendWrapper::new(async move {
+                let (req, payload) = self.take();
+                let bytes = match get_body_limits().max_body_size() {
+                    None => payload.to_bytes().await.ok(),
+                    Some(max) => payload
+                        .to_bytes_limited(max)
+                        .await
+                        .ok()
+                        .and_then(Result::ok),
+                };
+                let value = bytes.as_deref().and_then(|b| form_field(b, name));
+                let mut body = dev::Payload::from(bytes.unwrap_or_default());
+                // extracting the payload never fails
+                let payload = Payload::from_request(&req, &mut body)
+                    .await
+                    .expect("could not extract payload");
+                (Self::from((req, payload)), value)
+            })
+        }
+    }
+
+    /// A middleware that rejects requests whose CSRF token doesn't match the one in their
+    /// cookies.
+    ///
+    /// See the [module documentation](crate::csrf) for how the token is issued.
+    #[derive(Debug, Clone, Copy, Default)]
+    pub struct Csrf;
+
+    impl<Req, Resp> Layer<Req, Resp> for Csrf
+    where
+        Req: CsrfRequest,
+        Resp: Res<NoCustomError> + Send + 'static,
+    {
+        fn layer(
+            &self,
+            inner: BoxedService<Req, Resp>,
+        ) -> BoxedService<Req, Resp> {
+            BoxedService::new(CsrfService {
+                inner: Arc::new(Mutex::new(inner)),
+            })
+        }
+
+        fn rejections(&self) -> Vec<ServerFnError> {
+            vec![ServerFnError::InvalidCsrfToken(String::new())]
+        }
+    }
+
+    struct CsrfService<Req, Resp> {
+        // the inner service can only be called once the token has been read from the body,
+        // which happens in the returned future
+        inner: Arc<Mutex<BoxedService<Req, Resp>>>,
+    }
+
+    impl<Req, Resp> Service<Req, Resp> for CsrfService<Req, Resp>
+    where
+        Req: CsrfRequest,
+        Resp: Res<NoCustomError> + Send + 'static,
+    {
+        fn run(
+            &mut self,
+            req: Req,
+        ) -> Pin<Box<dyn Future<Output = Resp> + Send>> {
+            if matches!(
+                req.method(),
+                Method::GET | Method::HEAD | Method::OPTIONS
+            ) {
+                return self.inner.lock().unwrap().0.run(req);
+            }
+
+            let path = req.path().to_string();
+            let expected = req.header("cookie").and_then(|cookies| {
+                token_from_cookies(&cookies).map(str::to_string)
+            });
+            let header = req.header(CSRF_HEADER).map(Cow::into_owned);
+            let is_form = req.header("content-type").is_some_and(|ty| {
+                ty.starts_with("application/x-www-form-urlencoded")
+            });
+
+            let inner = self.inner.clone();
+            Box::pin(async move {
+                let (req, submitted) = match header {
+                    Some(token) => (req, Some(token)),
+                    None if is_form => req.take_form_field(CSRF_FIELD).await,
+                    None => (req, None),
+                };
+                let valid = matches!(
+                    (&expected, &submitted),
+                    (Some(expected), Some(submitted))
+                        if constant_time_eq(expected.as_bytes(), submitted.as_bytes())
+                );
+                if valid {
+                    let res = inner.lock().unwrap().0.run(req);
+                    res.await
+                } else {
+                    let err = ServerFnError::InvalidCsrfToken(
+                        if expected.is_none() {
+                            "the request has no CSRF cookie"
+                        } else {
+                            "the request's CSRF token is missing or does not \
+                             match its cookie"
+                        }
+                        .to_string(),
+                    );
+                    Resp::error_response(&path, &err)
+                }
+            })
+        }
+    }
+
+    // compares tokens without leaking how much of them matched through timing
+    fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+        a.len() == b.len()
+            && a.iter().zip(b).fold(0, |acc, (a, b)| acc | (a ^ b)) == 0
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn finds_form_fields() {
+            let body = b"title=Hello%20world&__csrf=abc-123&done=on";
+            assert_eq!(
+                form_field(body, CSRF_FIELD).as_deref(),
+                Some("abc-123")
+            );
+            assert_eq!(form_field(body, "missing"), None);
+            assert_eq!(form_field(b"", CSRF_FIELD), None);
+        }
+
+        #[test]
+        fn compares_tokens() {
+            assert!(constant_time_eq(b"abc", b"abc"));
+            assert!(!constant_time_eq(b"abc", b"abd"));
+            assert!(!constant_time_eq(b"abc", b"abcd"));
+            assert!(!constant_time_eq(b"abc", b""));
+        }
+
+        #[cfg(feature = "generic")]
+        mod service {
+            use super::*;
+            use crate::response::generic::Body;
+            use bytes::Bytes;
+            use http::{Request, Response, StatusCode};
+
+            struct Called;
+
+            impl Service<Request<Bytes>, Response<Body>> for Called {
+                fn run(
+                    &mut self,
+                    _req: Request<Bytes>,
+                ) -> Pin<Box<dyn Future<Output = Response<Body>> + Send>>
+                {
+                    Box::pin(async {
+                        Response::new(Body::Sync(Bytes::from_static(b"called")))
+                    })
+                }
+            }
+
+            fn status(req: Request<Bytes>) -> StatusCode {
+                let mut service =
+                    Layer::<Request<Bytes>, Response<Body>>::layer(
+                        &Csrf,
+                        BoxedService::new(Called),
+                    );
+                futures::executor::block_on(service.0.run(req)).status()
+            }
+
+            fn post() -> http::request::Builder {
+                Request::builder().method(Method::POST).uri("/api/add_todo")
+            }
+
+            #[test]
+            fn accepts_a_matching_header() {
+                let req = post()
+                    .header("cookie", "theme=dark; leptos_csrf=token")
+                    .header(CSRF_HEADER, "token")
+                    .body(Bytes::new())
+                    .unwrap();
+                assert_eq!(status(req), StatusCode::OK);
+            }
+
+            #[test]
+            fn rejects_a_header_that_does_not_match() {
+                let req = post()
+                    .header("cookie", "leptos_csrf=token")
+                    .header(CSRF_HEADER, "other")
+                    .body(Bytes::new())
+                    .unwrap();
+                assert_eq!(status(req), StatusCode::FORBIDDEN);
+            }
+
+            #[test]
+            fn accepts_a_matching_form_field() {
+                let req = post()
+                    .header("cookie", "leptos_csrf=token")
+                    .header("content-type", "application/x-www-form-urlencoded")
+                    .body(Bytes::from_static(b"title=Hi&__csrf=token"))
+                    .unwrap();
+                assert_eq!(status(req), StatusCode::OK);
+            }
+
+            #[test]
+            fn ignores_form_fields_in_other_encodings() {
+                let req = post()
+                    .header("cookie", "leptos_csrf=token")
+                    .header("content-type", "application/json")
+                    .body(Bytes::from_static(b"__csrf=token"))
+                    .unwrap();
+                assert_eq!(status(req), StatusCode::FORBIDDEN);
+            }
+
+            #[test]
+            fn rejects_missing_or_malformed_tokens() {
+                // no cookie
+                let req = post()
+                    .header(CSRF_HEADER, "token")
+                    .body(Bytes::new())
+                    .unwrap();
+                assert_eq!(status(req), StatusCode::FORBIDDEN);
+                // no token
+                let req = post()
+                    .header("cookie", "leptos_csrf=token")
+                    .body(Bytes::new())
+                    .unwrap();
+                assert_eq!(status(req), StatusCode::FORBIDDEN);
+                // an empty cookie
+                let req = post()
+                    .header("cookie", "leptos_csrf=")
+                    .header(CSRF_HEADER, "")
+                    .body(Bytes::new())
+                    .unwrap();
+                assert_eq!(status(req), StatusCode::FORBIDDEN);
+            }
+
+            #[test]
+            fn does_not_check_safe_methods() {
+                let req = Request::builder()
+                    .method(Method::GET)
+                    .uri("/api/list_todos")
+                    .body(Bytes::new())
+                    .unwrap();
+                assert_eq!(status(req), StatusCode::OK);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_the_token_among_other_cookies() {
+        assert_eq!(token_from_cookies("leptos_csrf=abc"), Some("abc"));
+        assert_eq!(
+            token_from_cookies("theme=dark; leptos_csrf=abc; lang=en"),
+            Some("abc")
+        );
+        assert_eq!(token_from_cookies("theme=dark"), None);
+        assert_eq!(token_from_cookies("leptos_csrf="), None);
+        assert_eq!(token_from_cookies("not_leptos_csrf=abc"), None);
+        assert_eq!(token_from_cookies(""), None);
+    }
+
+    #[test]
+    fn sets_a_cookie_readable_by_the_client() {
+        let header = set_cookie_header("abc");
+        assert!(header.starts_with("leptos_csrf=abc;"));
+        assert!(!header.contains("HttpOnly"));
+    }
+}
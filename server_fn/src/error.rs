@@ -1,8 +1,14 @@
+use http::{HeaderMap, StatusCode};
+use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
     fmt,
     fmt::{Display, Write},
+    marker::PhantomData,
     str::FromStr,
+    sync::{PoisonError, RwLock},
 };
 use thiserror::Error;
 use throw_error::Error;
@@ -160,6 +166,25 @@ pub enum ServerFnError<E = NoCustomError> {
     Args(String),
     /// Occurs on the server if there's a missing argument.
     MissingArg(String),
+    /// Occurs on the client if the server does not respond within the call’s timeout.
+    Timeout(String),
+    /// Occurs on the client if the call is aborted before it completes.
+    Cancelled(String),
+    /// Occurs on the client if middleware rejects the request before it is sent. The call is
+    /// never retried.
+    Rejected(String),
+    /// Occurs on the server if the request body is larger than the server function accepts.
+    PayloadTooLarge(String),
+    /// Occurs on the server if the caller has made too many requests to the server function.
+    TooManyRequests(String),
+    /// Occurs on the server if the request's CSRF token is missing or invalid.
+    InvalidCsrfToken(String),
+    /// Occurs on the server if a client running an older build of the app calls a server
+    /// function that no longer exists.
+    ClientOutdated(String),
+    /// Returned by the server function if some of its arguments are invalid, with a message
+    /// for each field that failed validation.
+    Validation(ValidationErrors),
 }
 
 impl ServerFnError<NoCustomError> {
@@ -169,14 +194,240 @@ impl ServerFnError<NoCustomError> {
     }
 }
 
+impl<CustErr> ServerFnError<CustErr> {
+    /// Converts this into an error with a different custom error type, using `f` to convert
+    /// the custom error, if there is one. Every other variant is left unchanged.
+    pub fn map_custom_err<T>(
+        self,
+        f: impl FnOnce(CustErr) -> ServerFnError<T>,
+    ) -> ServerFnError<T> {
+        match self {
+            ServerFnError::WrappedServerError(e) => f(e),
+            ServerFnError::Registration(s) => ServerFnError::Registration(s),
+            ServerFnError::Request(s) => ServerFnError::Request(s),
+            ServerFnError::Response(s) => ServerFnError::Response(s),
+            ServerFnError::ServerError(s) => ServerFnError::ServerError(s),
+            ServerFnError::Deserialization(s) => {
+                ServerFnError::Deserialization(s)
+            }
+            ServerFnError::Serialization(s) => ServerFnError::Serialization(s),
+            ServerFnError::Args(s) => ServerFnError::Args(s),
+            ServerFnError::MissingArg(s) => ServerFnError::MissingArg(s),
+            ServerFnError::Timeout(s) => ServerFnError::Timeout(s),
+            ServerFnError::Cancelled(s) => ServerFnError::Cancelled(s),
+            ServerFnError::Rejected(s) => ServerFnError::Rejected(s),
+            ServerFnError::PayloadTooLarge(s) => {
+                ServerFnError::PayloadTooLarge(s)
+            }
+            ServerFnError::TooManyRequests(s) => {
+                ServerFnError::TooManyRequests(s)
+            }
+            ServerFnError::InvalidCsrfToken(s) => {
+                ServerFnError::InvalidCsrfToken(s)
+            }
+            ServerFnError::ClientOutdated(s) => {
+                ServerFnError::ClientOutdated(s)
+            }
+            ServerFnError::Validation(e) => ServerFnError::Validation(e),
+        }
+    }
+}
+
+/// Chooses the HTTP response sent when a server function fails with an error.
+///
+/// Implementing this for a custom error type used as `ServerFnError<E>` is optional. Errors
+/// whose type does not implement it are sent with `500 Internal Server Error` and no
+/// additional headers, as are errors that don't override the default methods:
+///
+/// ```rust,ignore
+/// impl ServerFnErrorStatus for InvalidArgument {
+///     fn status_code(&self) -> StatusCode {
+///         StatusCode::BAD_REQUEST
+///     }
+/// }
+/// ```
+///
+/// The implementation is picked up by every server function that returns
+/// `ServerFnError<InvalidArgument>` and is defined with the `#[server]` macro. Server
+/// functions that implement [`ServerFn`](crate::ServerFn) by hand can opt in with
+/// [`register_error_status`].
+///
+/// The error itself is still sent in the body of the response, so the client decodes it back
+/// into the same typed error whatever the status is.
+pub trait ServerFnErrorStatus {
+    /// The status code of the response.
+    ///
+    /// Only client and server error codes (`4xx` and `5xx`) are used; any other status is
+    /// replaced with `500 Internal Server Error`, because the client would not recognize the
+    /// response as an error.
+    fn status_code(&self) -> StatusCode {
+        StatusCode::INTERNAL_SERVER_ERROR
+    }
+
+    /// Additional headers to send with the response, like `WWW-Authenticate` or `Retry-After`.
+    fn headers(&self) -> HeaderMap {
+        HeaderMap::new()
+    }
+}
+
+impl ServerFnErrorStatus for NoCustomError {}
+
+type StatusFn = fn(&dyn Any) -> (StatusCode, HeaderMap);
+
+// the `ServerFnErrorStatus` implementations of custom error types, which are looked up at
+// runtime so that error types which don't have one can still be used
+static ERROR_STATUSES: Lazy<RwLock<HashMap<TypeId, StatusFn>>> =
+    Lazy::new(|| {
+        #[allow(unused_mut)]
+        let mut statuses = HashMap::new();
+        #[cfg(feature = "ssr")]
+        for registration in inventory::iter::<ErrorStatusRegistration> {
+            if let Some((type_id, status)) = (registration.0)() {
+                statuses.insert(type_id, status);
+            }
+        }
+        RwLock::new(statuses)
+    });
+
+/// Uses the [`ServerFnErrorStatus`] implementation of `E` for the responses of server
+/// functions that fail with `ServerFnError<E>`.
+///
+/// This is done automatically for server functions defined with the `#[server]` macro.
+pub fn register_error_status<E>()
+where
+    E: ServerFnErrorStatus + 'static,
+{
+    let (type_id, status) = status_fn::<E>();
+    ERROR_STATUSES
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .insert(type_id, status);
+}
+
+fn status_fn<E>() -> (TypeId, StatusFn)
+where
+    E: ServerFnErrorStatus + 'static,
+{
+    (TypeId::of::<E>(), |err| match err.downcast_ref::<E>() {
+        Some(err) => (err.status_code(), err.headers()),
+        None => (StatusCode::INTERNAL_SERVER_ERROR, HeaderMap::new()),
+    })
+}
+
+// the status and headers of a custom error, if its type has a `ServerFnErrorStatus`
+// implementation that has been registered
+fn custom_status<CustErr: 'static>(err: &CustErr) -> (StatusCode, HeaderMap) {
+    let status = ERROR_STATUSES
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get(&TypeId::of::<CustErr>())
+        .copied();
+    match status {
+        Some(status) => {
+            let (code, headers) = status(err);
+            (error_status(code), headers)
+        }
+        None => (StatusCode::INTERNAL_SERVER_ERROR, HeaderMap::new()),
+    }
+}
+
+/// Registers the [`ServerFnErrorStatus`] implementation of a custom error type, if it has one.
+///
+/// This is submitted by the `#[server]` macro, and should not need to be used directly.
+#[cfg(feature = "ssr")]
+#[doc(hidden)]
+pub struct ErrorStatusRegistration(pub fn() -> Option<(TypeId, StatusFn)>);
+
+#[cfg(feature = "ssr")]
+inventory::collect!(ErrorStatusRegistration);
+
+/// A wrapper used to look up the [`ServerFnErrorStatus`] implementation of a type, without
+/// requiring that it has one.
+///
+/// This is used by the `#[server]` macro, and should not need to be used directly.
+#[doc(hidden)]
+pub struct WrapErrorStatus<E>(PhantomData<fn() -> E>);
+
+impl<E> WrapErrorStatus<E> {
+    #[doc(hidden)]
+    pub const fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<E> Default for WrapErrorStatus<E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Looks up the [`ServerFnErrorStatus`] implementation of the wrapped type, if it has one.
+///
+/// Call this as `(&&WrapErrorStatus::<E>::new()).error_status()`, so that the implementation
+/// is preferred when it exists.
+#[doc(hidden)]
+pub trait ViaErrorStatus {
+    /// Returns the status function of the wrapped type, if it has one.
+    fn error_status(&self) -> Option<(TypeId, StatusFn)>;
+}
+
+impl<E: ServerFnErrorStatus + 'static> ViaErrorStatus for &WrapErrorStatus<E> {
+    fn error_status(&self) -> Option<(TypeId, StatusFn)> {
+        Some(status_fn::<E>())
+    }
+}
+
+impl<E> ViaErrorStatus for WrapErrorStatus<E> {
+    fn error_status(&self) -> Option<(TypeId, StatusFn)> {
+        None
+    }
+}
+
+impl<CustErr> ServerFnErrorStatus for ServerFnError<CustErr>
+where
+    CustErr: 'static,
+{
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ServerFnError::WrappedServerError(e) => custom_status(e).0,
+            ServerFnError::PayloadTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            ServerFnError::TooManyRequests(_) => StatusCode::TOO_MANY_REQUESTS,
+            ServerFnError::InvalidCsrfToken(_) => StatusCode::FORBIDDEN,
+            ServerFnError::ClientOutdated(_) => StatusCode::GONE,
+            ServerFnError::Validation(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn headers(&self) -> HeaderMap {
+        match self {
+            ServerFnError::WrappedServerError(e) => custom_status(e).1,
+            _ => HeaderMap::new(),
+        }
+    }
+}
+
+// the client only decodes an error from responses with an error status
+fn error_status(status: StatusCode) -> StatusCode {
+    if status.is_client_error() || status.is_server_error() {
+        status
+    } else {
+        StatusCode::INTERNAL_SERVER_ERROR
+    }
+}
+
 impl<CustErr> From<CustErr> for ServerFnError<CustErr> {
     fn from(value: CustErr) -> Self {
         ServerFnError::WrappedServerError(value)
     }
 }
 
-impl<E: std::error::Error> From<E> for ServerFnError {
+impl<E: std::error::Error + 'static> From<E> for ServerFnError {
     fn from(value: E) -> Self {
+        #[cfg(feature = "multipart")]
+        if let Some(err) = crate::codec::payload_too_large(&value) {
+            return err;
+        }
         ServerFnError::ServerError(value.to_string())
     }
 }
@@ -208,6 +459,21 @@ where
                 ServerFnError::MissingArg(s) => format!("missing argument {s}"),
                 ServerFnError::Response(s) =>
                     format!("error generating HTTP response: {s}"),
+                ServerFnError::Timeout(s) =>
+                    format!("server function call timed out: {s}"),
+                ServerFnError::Cancelled(s) =>
+                    format!("server function call was cancelled: {s}"),
+                ServerFnError::Rejected(s) =>
+                    format!("server function call was rejected: {s}"),
+                ServerFnError::PayloadTooLarge(s) =>
+                    format!("request body too large: {s}"),
+                ServerFnError::TooManyRequests(s) =>
+                    format!("too many requests: {s}"),
+                ServerFnError::InvalidCsrfToken(s) =>
+                    format!("invalid CSRF token: {s}"),
+                ServerFnError::ClientOutdated(s) =>
+                    format!("client outdated: {s}"),
+                ServerFnError::Validation(e) => format!("invalid input: {e}"),
                 ServerFnError::WrappedServerError(e) => format!("{e}"),
             }
         )
@@ -259,6 +525,24 @@ where
             ServerFnError::MissingArg(e) => {
                 write!(&mut buf, "MissingArg|{e}")
             }
+            ServerFnError::Timeout(e) => write!(&mut buf, "Timeout|{e}"),
+            ServerFnError::Cancelled(e) => write!(&mut buf, "Cancelled|{e}"),
+            ServerFnError::Rejected(e) => write!(&mut buf, "Rejected|{e}"),
+            ServerFnError::PayloadTooLarge(e) => {
+                write!(&mut buf, "PayloadTooLarge|{e}")
+            }
+            ServerFnError::TooManyRequests(e) => {
+                write!(&mut buf, "TooManyRequests|{e}")
+            }
+            ServerFnError::InvalidCsrfToken(e) => {
+                write!(&mut buf, "InvalidCsrfToken|{e}")
+            }
+            ServerFnError::ClientOutdated(e) => {
+                write!(&mut buf, "ClientOutdated|{e}")
+            }
+            ServerFnError::Validation(e) => {
+                write!(&mut buf, "Validation|{}", e.to_query())
+            }
         }?;
         Ok(buf)
     }
@@ -288,6 +572,24 @@ where
                 "MissingArg" => {
                     Some(ServerFnError::MissingArg(data.to_string()))
                 }
+                "Timeout" => Some(ServerFnError::Timeout(data.to_string())),
+                "Cancelled" => Some(ServerFnError::Cancelled(data.to_string())),
+                "Rejected" => Some(ServerFnError::Rejected(data.to_string())),
+                "PayloadTooLarge" => {
+                    Some(ServerFnError::PayloadTooLarge(data.to_string()))
+                }
+                "TooManyRequests" => {
+                    Some(ServerFnError::TooManyRequests(data.to_string()))
+                }
+                "InvalidCsrfToken" => {
+                    Some(ServerFnError::InvalidCsrfToken(data.to_string()))
+                }
+                "ClientOutdated" => {
+                    Some(ServerFnError::ClientOutdated(data.to_string()))
+                }
+                "Validation" => Some(ServerFnError::Validation(
+                    ValidationErrors::from_query(data),
+                )),
                 _ => None,
             })
             .unwrap_or_else(|| {
@@ -349,6 +651,33 @@ pub enum ServerFnErrorErr<E = NoCustomError> {
     /// Occurs on the server if there is an error creating an HTTP response.
     #[error("error creating response {0}")]
     Response(String),
+    /// Occurs on the client if the server does not respond within the call’s timeout.
+    #[error("server function call timed out: {0}")]
+    Timeout(String),
+    /// Occurs on the client if the call is aborted before it completes.
+    #[error("server function call was cancelled: {0}")]
+    Cancelled(String),
+    /// Occurs on the client if middleware rejects the request before it is sent. The call is
+    /// never retried.
+    #[error("server function call was rejected: {0}")]
+    Rejected(String),
+    /// Occurs on the server if the request body is larger than the server function accepts.
+    #[error("request body too large: {0}")]
+    PayloadTooLarge(String),
+    /// Occurs on the server if the caller has made too many requests to the server function.
+    #[error("too many requests: {0}")]
+    TooManyRequests(String),
+    /// Occurs on the server if the request's CSRF token is missing or invalid.
+    #[error("invalid CSRF token: {0}")]
+    InvalidCsrfToken(String),
+    /// Occurs on the server if a client running an older build of the app calls a server
+    /// function that no longer exists.
+    #[error("client outdated: {0}")]
+    ClientOutdated(String),
+    /// Returned by the server function if some of its arguments are invalid, with a message
+    /// for each field that failed validation.
+    #[error("invalid input: {0}")]
+    Validation(ValidationErrors),
 }
 
 impl<CustErr> From<ServerFnError<CustErr>> for ServerFnErrorErr<CustErr> {
@@ -375,6 +704,54 @@ impl<CustErr> From<ServerFnError<CustErr>> for ServerFnErrorErr<CustErr> {
                 ServerFnErrorErr::WrappedServerError(value)
             }
             ServerFnError::Response(value) => ServerFnErrorErr::Response(value),
+            ServerFnError::Timeout(value) => ServerFnErrorErr::Timeout(value),
+            ServerFnError::Cancelled(value) => {
+                ServerFnErrorErr::Cancelled(value)
+            }
+            ServerFnError::Rejected(value) => ServerFnErrorErr::Rejected(value),
+            ServerFnError::PayloadTooLarge(value) => {
+                ServerFnErrorErr::PayloadTooLarge(value)
+            }
+            ServerFnError::TooManyRequests(value) => {
+                ServerFnErrorErr::TooManyRequests(value)
+            }
+            ServerFnError::InvalidCsrfToken(value) => {
+                ServerFnErrorErr::InvalidCsrfToken(value)
+            }
+            ServerFnError::ClientOutdated(value) => {
+                ServerFnErrorErr::ClientOutdated(value)
+            }
+            ServerFnError::Validation(value) => {
+                ServerFnErrorErr::Validation(value)
+            }
+        }
+    }
+}
+
+impl<CustErr> ServerFnErrorStatus for ServerFnErrorErr<CustErr>
+where
+    CustErr: 'static,
+{
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ServerFnErrorErr::WrappedServerError(e) => custom_status(e).0,
+            ServerFnErrorErr::PayloadTooLarge(_) => {
+                StatusCode::PAYLOAD_TOO_LARGE
+            }
+            ServerFnErrorErr::TooManyRequests(_) => {
+                StatusCode::TOO_MANY_REQUESTS
+            }
+            ServerFnErrorErr::InvalidCsrfToken(_) => StatusCode::FORBIDDEN,
+            ServerFnErrorErr::ClientOutdated(_) => StatusCode::GONE,
+            ServerFnErrorErr::Validation(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn headers(&self) -> HeaderMap {
+        match self {
+            ServerFnErrorErr::WrappedServerError(e) => custom_status(e).1,
+            _ => HeaderMap::new(),
         }
     }
 }
@@ -461,3 +838,313 @@ impl<CustErr> From<ServerFnUrlError<CustErr>> for ServerFnErrorErr<CustErr> {
         error.error.into()
     }
 }
+
+/// The fields of a server function’s arguments that failed validation, with a message for
+/// each, and optionally the values that were submitted.
+///
+/// Return these as [`ServerFnError::Validation`] to report problems with the input that the
+/// user can fix, rather than a single error message:
+///
+/// ```rust,ignore
+/// #[server]
+/// pub async fn sign_up(
+///     email: String,
+///     password: String,
+/// ) -> Result<(), ServerFnError> {
+///     let mut errors = ValidationErrors::new();
+///     if !email.contains('@') {
+///         errors.add("email", "Enter a valid email address.");
+///     }
+///     if password.len() < 12 {
+///         errors.add("password", "Use at least 12 characters.");
+///     }
+///     errors.echo("email");
+///     errors.check()?;
+///     // ...
+/// }
+/// ```
+///
+/// When the server function is called by a `<form>` without JS/WASM, the submitted values of
+/// the fields passed to [`echo`](Self::echo) are sent back with the errors in the URL of the
+/// page the form is on, so that those inputs can be filled in again. No value is sent back
+/// unless its field has been listed, so that passwords and other secrets don't end up in a
+/// URL, where they could be logged or kept in the browser's history.
+#[derive(
+    Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize,
+)]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
+pub struct ValidationErrors {
+    errors: Vec<(String, String)>,
+    values: Vec<(String, String)>,
+    echoed: Vec<String>,
+}
+
+impl ValidationErrors {
+    /// Creates an empty set of errors.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an error for the field with the given name.
+    ///
+    /// The name should match the `name` of the form input it refers to.
+    pub fn add(
+        &mut self,
+        field: impl Into<String>,
+        message: impl Into<String>,
+    ) {
+        self.errors.push((field.into(), message.into()));
+    }
+
+    /// Adds an error for the field with the given name, returning `self`.
+    pub fn with_error(
+        mut self,
+        field: impl Into<String>,
+        message: impl Into<String>,
+    ) -> Self {
+        self.add(field, message);
+        self
+    }
+
+    /// Sends the submitted value of the field with the given name back with the errors, when
+    /// the server function was called by a `<form>` without JS/WASM.
+    ///
+    /// Only list fields whose values are safe to put in a URL.
+    pub fn echo(&mut self, field: impl Into<String>) {
+        self.echoed.push(field.into());
+    }
+
+    /// Returns `Err(ServerFnError::Validation(_))` if any errors have been added.
+    pub fn check<CustErr>(self) -> Result<(), ServerFnError<CustErr>> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(ServerFnError::Validation(self))
+        }
+    }
+
+    /// Whether no errors have been added.
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Every field name and error message, in the order they were added.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.errors
+            .iter()
+            .map(|(field, message)| (field.as_str(), message.as_str()))
+    }
+
+    /// The error messages for the field with the given name.
+    pub fn field<'a>(
+        &'a self,
+        field: &'a str,
+    ) -> impl Iterator<Item = &'a str> + 'a {
+        self.iter()
+            .filter(move |(name, _)| *name == field)
+            .map(|(_, message)| message)
+    }
+
+    /// The first error message for the field with the given name, if any.
+    pub fn first(&self, field: &str) -> Option<&str> {
+        self.iter()
+            .find(|(name, _)| *name == field)
+            .map(|(_, message)| message)
+    }
+
+    /// Remembers the values that were submitted for the fields passed to
+    /// [`echo`](Self::echo), given as a URL-encoded form body.
+    ///
+    /// This is done automatically for server functions called by a `<form>` without
+    /// JS/WASM.
+    pub fn with_values(mut self, form: &str) -> Self {
+        self.values = url::form_urlencoded::parse(form.as_bytes())
+            .into_owned()
+            .filter(|(name, _)| self.echoed.contains(name))
+            .collect();
+        self
+    }
+
+    /// Whether the submitted values have been remembered.
+    pub fn has_values(&self) -> bool {
+        !self.values.is_empty()
+    }
+
+    /// The value submitted for the field with the given name, if it was remembered.
+    pub fn value(&self, field: &str) -> Option<&str> {
+        self.values
+            .iter()
+            .find(|(name, _)| name == field)
+            .map(|(_, value)| value.as_str())
+    }
+
+    // errors and values share one query string, told apart by the prefix of their keys
+    fn to_query(&self) -> String {
+        let mut query = url::form_urlencoded::Serializer::new(String::new());
+        for (field, message) in &self.errors {
+            query.append_pair(&format!("e:{field}"), message);
+        }
+        for (field, value) in &self.values {
+            query.append_pair(&format!("v:{field}"), value);
+        }
+        query.finish()
+    }
+
+    fn from_query(query: &str) -> Self {
+        let mut this = Self::new();
+        for (key, value) in url::form_urlencoded::parse(query.as_bytes()) {
+            if let Some(field) = key.strip_prefix("e:") {
+                this.errors.push((field.to_string(), value.into_owned()));
+            } else if let Some(field) = key.strip_prefix("v:") {
+                this.values.push((field.to_string(), value.into_owned()));
+            }
+        }
+        this
+    }
+}
+
+impl Display for ValidationErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (field, message)) in self.iter().enumerate() {
+            if i > 0 {
+                f.write_str("; ")?;
+            }
+            write!(f, "{field}: {message}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Unregistered;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Unauthorized;
+
+    impl ServerFnErrorStatus for Unauthorized {
+        fn status_code(&self) -> StatusCode {
+            StatusCode::UNAUTHORIZED
+        }
+
+        fn headers(&self) -> HeaderMap {
+            let mut headers = HeaderMap::new();
+            headers.insert("www-authenticate", "Bearer".parse().unwrap());
+            headers
+        }
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct NotAnError;
+
+    impl ServerFnErrorStatus for NotAnError {
+        fn status_code(&self) -> StatusCode {
+            StatusCode::OK
+        }
+    }
+
+    #[test]
+    fn custom_errors_without_a_status_are_internal_errors() {
+        let err = ServerFnError::WrappedServerError(Unregistered);
+        assert_eq!(err.status_code(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(err.headers().is_empty());
+    }
+
+    #[test]
+    // borrows twice, the same as the `#[server]` macro, so that autoref picks the impl
+    #[allow(clippy::needless_borrow)]
+    fn registered_custom_errors_choose_their_status() {
+        register_error_status::<Unauthorized>();
+        let err = ServerFnError::WrappedServerError(Unauthorized);
+        assert_eq!(err.status_code(), StatusCode::UNAUTHORIZED);
+        assert_eq!(err.headers()["www-authenticate"], "Bearer");
+        // detected by the `#[server]` macro without registering anything
+        assert!((&&WrapErrorStatus::<Unauthorized>::new())
+            .error_status()
+            .is_some());
+        assert!((&&WrapErrorStatus::<Unregistered>::new())
+            .error_status()
+            .is_none());
+    }
+
+    #[test]
+    fn non_error_statuses_are_replaced() {
+        register_error_status::<NotAnError>();
+        let err = ServerFnError::WrappedServerError(NotAnError);
+        assert_eq!(err.status_code(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    fn sign_up_errors() -> ValidationErrors {
+        let mut errors = ValidationErrors::new()
+            .with_error("email", "Enter a valid email address.")
+            .with_error("password", "Use at least 12 characters.")
+            .with_error("password", "Don't reuse your email.");
+        errors.echo("email");
+        errors
+    }
+
+    #[test]
+    fn only_echoes_listed_fields() {
+        let errors = sign_up_errors()
+            .with_values("email=me%40example.com&password=hunter2&plan=free");
+        assert!(errors.has_values());
+        assert_eq!(errors.value("email"), Some("me@example.com"));
+        assert_eq!(errors.value("password"), None);
+        assert_eq!(errors.value("plan"), None);
+
+        // nothing is echoed by default
+        let errors = ValidationErrors::new()
+            .with_error("password", "Too short.")
+            .with_values("password=hunter2");
+        assert!(!errors.has_values());
+    }
+
+    #[test]
+    fn validation_errors_round_trip_through_query() {
+        let errors =
+            sign_up_errors().with_values("email=a%26b%3Dc%40d&password=x");
+        let decoded = ValidationErrors::from_query(&errors.to_query());
+        assert_eq!(
+            decoded.iter().collect::<Vec<_>>(),
+            errors.iter().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            decoded.field("password").collect::<Vec<_>>(),
+            ["Use at least 12 characters.", "Don't reuse your email."]
+        );
+        assert_eq!(decoded.value("email"), Some("a&b=c@d"));
+        assert_eq!(decoded.value("password"), None);
+    }
+
+    #[test]
+    fn validation_errors_round_trip_through_server_fn_error() {
+        let errors = sign_up_errors().with_values("email=me%40example.com");
+        let err = ServerFnError::<NoCustomError>::Validation(errors);
+        let decoded = ServerFnError::<NoCustomError>::de(&err.ser().unwrap());
+        let ServerFnError::Validation(decoded) = decoded else {
+            panic!("expected validation errors, got {decoded:?}");
+        };
+        assert_eq!(
+            decoded.first("email"),
+            Some("Enter a valid email address.")
+        );
+        assert_eq!(decoded.field("password").count(), 2);
+        assert_eq!(decoded.value("email"), Some("me@example.com"));
+    }
+
+    #[test]
+    fn built_in_errors_have_fixed_statuses() {
+        let err =
+            ServerFnError::<NoCustomError>::TooManyRequests(String::new());
+        assert_eq!(err.status_code(), StatusCode::TOO_MANY_REQUESTS);
+        let err =
+            ServerFnError::<NoCustomError>::Validation(ValidationErrors::new());
+        assert_eq!(err.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+}
@@ -96,23 +96,39 @@
 //! [`serde_qs`]: <https://docs.rs/serde_qs/latest/serde_qs/>
 //! [`cbor`]: <https://docs.rs/cbor/latest/cbor/>
 
+#[cfg(feature = "batch")]
+pub mod batch;
+/// Caching headers and conditional requests for `GET` server functions.
+pub mod cache;
 /// Implementations of the client side of the server function call.
 pub mod client;
 
 /// Encodings for arguments and results.
 pub mod codec;
 
+#[cfg(feature = "csrf")]
+pub mod csrf;
+
 #[macro_use]
 /// Error types and utilities.
 pub mod error;
 /// Types to add server middleware to a server function.
 pub mod middleware;
+#[cfg(feature = "mock")]
+pub mod mock;
 /// Utilities to allow client-side redirects.
 pub mod redirect;
 /// Types and traits for  for HTTP requests.
 pub mod request;
 /// Types and traits for HTTP responses.
 pub mod response;
+#[cfg(feature = "upload")]
+pub mod upload;
+/// Build versions, which let the server tell clients running an older build to reload.
+pub mod version;
+
+#[cfg(feature = "openapi")]
+pub mod openapi;
 
 #[cfg(feature = "actix")]
 #[doc(hidden)]
@@ -126,7 +142,8 @@ pub use ::bytes as bytes_export;
 #[cfg(feature = "generic")]
 #[doc(hidden)]
 pub use ::http as http_export;
-use client::Client;
+use cache::CachePolicy;
+use client::{CallPolicy, Client};
 use codec::{Encoding, FromReq, FromRes, IntoReq, IntoRes};
 #[doc(hidden)]
 pub use const_format;
@@ -139,7 +156,7 @@ use http::Method;
 use middleware::{Layer, Service};
 use once_cell::sync::Lazy;
 use redirect::RedirectHook;
-use request::Req;
+use request::{BodyLimits, ClientReq, Req};
 use response::{ClientRes, Res};
 #[cfg(feature = "rkyv")]
 pub use rkyv;
@@ -148,7 +165,10 @@ pub use serde;
 #[doc(hidden)]
 #[cfg(feature = "serde-lite")]
 pub use serde_lite;
-use std::{fmt::Display, future::Future, pin::Pin, str::FromStr, sync::Arc};
+use std::{
+    borrow::Cow, fmt::Display, future::Future, pin::Pin, str::FromStr,
+    sync::Arc,
+};
 #[doc(hidden)]
 pub use xxhash_rust;
 
@@ -223,7 +243,9 @@ where
     type OutputEncoding: Encoding;
 
     /// The type of the custom error on [`ServerFnError`], if any. (If there is no
-    /// custom error type, this can be `NoCustomError` by default.)
+    /// custom error type, this can be `NoCustomError` by default.) If it implements
+    /// [`ServerFnErrorStatus`](error::ServerFnErrorStatus), that chooses the status code and
+    /// headers of the response when the server function fails with it.
     type Error: FromStr + Display;
 
     /// Returns [`Self::PATH`].
@@ -231,6 +253,47 @@ where
         Self::PATH
     }
 
+    /// The timeout and retry policy for calls to this server function from the client.
+    ///
+    /// By default, this is the global policy set with [`set_call_policy`](client::set_call_policy).
+    fn call_policy() -> CallPolicy {
+        client::get_call_policy()
+    }
+
+    /// The limits on the size of the request body this server function accepts.
+    ///
+    /// By default, these are the global limits set with
+    /// [`set_body_limits`](request::set_body_limits).
+    fn body_limits() -> BodyLimits {
+        request::get_body_limits()
+    }
+
+    /// The caching headers sent with responses to this server function, if it uses `GET`.
+    ///
+    /// By default, no caching headers are sent.
+    fn cache_policy() -> CachePolicy {
+        CachePolicy::new()
+    }
+
+    /// Remembers the value decoded from a tagged response on the client, so that it can be
+    /// reused if the server later responds with `304 Not Modified`.
+    #[doc(hidden)]
+    fn cache_output(_url: &str, _etag: &str, _output: &Self::Output) {}
+
+    /// Returns the value remembered for the given URL by [`cache_output`](Self::cache_output).
+    #[doc(hidden)]
+    fn cached_output(_url: &str) -> Option<Self::Output> {
+        None
+    }
+
+    /// Encodes the arguments the way a `<form>` would submit them, so that they can be sent
+    /// back to a form submitted without JS/WASM along with any
+    /// [validation errors](ServerFnError::Validation).
+    #[doc(hidden)]
+    fn form_values(&self) -> Option<String> {
+        None
+    }
+
     /// Middleware that should be applied to this server function.
     fn middlewares(
     ) -> Vec<Arc<dyn Layer<Self::ServerRequest, Self::ServerResponse>>> {
@@ -299,13 +362,29 @@ where
     fn run_on_client(
         self,
     ) -> impl Future<Output = Result<Self::Output, ServerFnError<Self::Error>>> + Send
+    where
+        // client middleware is looked up by type
+        Self::Client: 'static,
+        Self::Error: Send + 'static,
     {
         async move {
             // create and send request on client
             let req =
                 self.into_req(Self::PATH, Self::OutputEncoding::CONTENT_TYPE)?;
-            Self::run_on_client_with_req(req, redirect::REDIRECT_HOOK.get())
-                .await
+            // only calls that use GET can safely be sent more than once
+            let idempotent = Self::InputEncoding::METHOD == Method::GET;
+            client::call_with_policy::<Self::Client, _, _, _>(
+                req,
+                Self::call_policy(),
+                idempotent,
+                |req| {
+                    Self::run_on_client_with_req(
+                        req,
+                        redirect::REDIRECT_HOOK.get(),
+                    )
+                },
+            )
+            .await
         }
     }
 
@@ -314,9 +393,25 @@ where
         req: <Self::Client as Client<Self::Error>>::Request,
         redirect_hook: Option<&RedirectHook>,
     ) -> impl Future<Output = Result<Self::Output, ServerFnError<Self::Error>>> + Send
+    where
+        Self::Client: 'static,
+        Self::Error: Send + 'static,
     {
         async move {
-            let res = Self::Client::send(req).await?;
+            let mut req = req;
+            let url = req.request_url();
+            if let Some(etag) = cache::cached_etag(&url) {
+                req.insert_header("if-none-match", &etag);
+            }
+            #[cfg(feature = "csrf")]
+            if let Some(token) = csrf::client_token() {
+                req.insert_header(csrf::CSRF_HEADER, &token);
+            }
+            if let Some(build) = version::get_build_version() {
+                req.insert_header(version::BUILD_VERSION_HEADER, build);
+            }
+
+            let res = client::send::<Self::Client, Self::Error>(req).await?;
 
             let status = res.status();
             let location = res.location();
@@ -325,17 +420,34 @@ where
             // if it returns an error status, deserialize the error using FromStr
             let res = if (400..=599).contains(&status) {
                 let text = res.try_into_string().await?;
-                Err(ServerFnError::<Self::Error>::de(&text))
+                let err = ServerFnError::<Self::Error>::de(&text);
+                if matches!(err, ServerFnError::ClientOutdated(_)) {
+                    version::call_client_outdated_hook();
+                }
+                Err(err)
+            } else if status == 304 {
+                // the value we decoded last time is still current
+                Ok(Self::cached_output(&url).ok_or_else(|| {
+                    ServerFnError::Deserialization(
+                        "the server responded 304 Not Modified, but there is \
+                         no cached value to reuse"
+                            .into(),
+                    )
+                }))
             } else {
                 // otherwise, deserialize the body as is
-                Ok(Self::Output::from_res(res).await)
+                let etag = res.header("etag");
+                let output = Self::Output::from_res(res).await;
+                if let (Some(etag), Ok(output)) = (etag, &output) {
+                    Self::cache_output(&url, &etag, output);
+                }
+                Ok(output)
             }?;
 
-            // if redirected, call the redirect hook (if that's been set)
-            if let Some(redirect_hook) = redirect_hook {
-                if (300..=399).contains(&status) || has_redirect_header {
-                    redirect_hook(&location);
-                }
+            // if redirected, handle it according to the redirect policy of this call, which
+            // calls the redirect hook (if that's been set) by default
+            if redirect::is_redirect(status, has_redirect_header) {
+                redirect::handle_redirect(&location, redirect_hook);
             }
             res
         }
@@ -349,10 +461,42 @@ where
         Output = Result<Self::ServerResponse, ServerFnError<Self::Error>>,
     > + Send {
         async {
+            let mut req = req;
+            req.set_body_limits(Self::body_limits());
+            let if_none_match = req.if_none_match().map(Cow::into_owned);
+            #[cfg(feature = "form-redirects")]
+            let accepts_html = req
+                .accepts()
+                .map(|n| n.contains("text/html"))
+                .unwrap_or(false);
             let this = Self::from_req(req).await?;
-            let output = this.run_body().await?;
+            // a form submitted without JS/WASM is redirected back to its page, which can only
+            // be filled in again if the submitted values are sent along with the errors
+            #[cfg(feature = "form-redirects")]
+            let submitted = accepts_html.then(|| this.form_values()).flatten();
+            let (output, cache_policy) = cache::WithCachePolicy::new(
+                Self::cache_policy(),
+                this.run_body(),
+            )
+            .await;
+            #[cfg(feature = "form-redirects")]
+            let output = output.map_err(|e| match (e, submitted) {
+                (ServerFnError::Validation(errors), Some(values))
+                    if !errors.has_values() =>
+                {
+                    ServerFnError::Validation(errors.with_values(&values))
+                }
+                (e, _) => e,
+            });
+            let output = output?;
             let res = output.into_res().await?;
-            Ok(res)
+            // only GET requests can be cached
+            if Self::InputEncoding::METHOD == Method::GET {
+                cache::apply_cache_policy(cache_policy, if_none_match, res)
+                    .await
+            } else {
+                Ok(res)
+            }
         }
     }
 }
@@ -387,6 +531,7 @@ pub struct ServerFnTraitObj<Req, Res> {
     method: Method,
     handler: fn(Req) -> Pin<Box<dyn Future<Output = Res> + Send>>,
     middleware: fn() -> MiddlewareSet<Req, Res>,
+    body_limits: fn() -> BodyLimits,
 }
 
 impl<Req, Res> ServerFnTraitObj<Req, Res> {
@@ -402,9 +547,20 @@ impl<Req, Res> ServerFnTraitObj<Req, Res> {
             method,
             handler,
             middleware,
+            body_limits: request::get_body_limits,
         }
     }
 
+    /// Sets the limits on the size of the request body the server function accepts, if it
+    /// doesn't use the global limits.
+    pub const fn with_body_limits(
+        mut self,
+        body_limits: fn() -> BodyLimits,
+    ) -> Self {
+        self.body_limits = body_limits;
+        self
+    }
+
     /// The path of the server function.
     pub fn path(&self) -> &'static str {
         self.path
@@ -424,6 +580,11 @@ impl<Req, Res> ServerFnTraitObj<Req, Res> {
     pub fn middleware(&self) -> MiddlewareSet<Req, Res> {
         (self.middleware)()
     }
+
+    /// The limits on the size of the request body this function accepts.
+    pub fn body_limits(&self) -> BodyLimits {
+        (self.body_limits)()
+    }
 }
 
 impl<Req, Res> Service<Req, Res> for ServerFnTraitObj<Req, Res>
@@ -444,6 +605,7 @@ impl<Req, Res> Clone for ServerFnTraitObj<Req, Res> {
             method: self.method.clone(),
             handler: self.handler,
             middleware: self.middleware,
+            body_limits: self.body_limits,
         }
     }
 }
@@ -495,7 +657,8 @@ pub mod axum {
                 T::InputEncoding::METHOD,
                 |req| Box::pin(T::run_on_server(req)),
                 T::middlewares,
-            ),
+            )
+            .with_body_limits(T::body_limits),
         );
     }
 
@@ -514,6 +677,20 @@ pub mod axum {
             get_server_fn_service(path, req.method().clone())
         {
             service.run(req).await
+        } else if let Some(err) = crate::version::unserved_version(
+            path,
+            server_fn_paths().map(|(path, _)| path),
+        )
+        .or_else(|| {
+            crate::version::client_outdated(
+                req.headers()
+                    .get(crate::version::BUILD_VERSION_HEADER)
+                    .and_then(|h| h.to_str().ok()),
+            )
+        }) {
+            crate::response::Res::<crate::error::NoCustomError>::error_response(
+                path, &err,
+            )
         } else {
             Response::builder()
                 .status(StatusCode::BAD_REQUEST)
@@ -543,9 +720,151 @@ pub mod axum {
             for middleware in middleware {
                 service = middleware.layer(service);
             }
+            #[cfg(feature = "csrf")]
+            if crate::csrf::csrf_protection_enabled() {
+                service = crate::middleware::Layer::layer(
+                    &crate::csrf::Csrf,
+                    service,
+                );
+            }
             service
         })
     }
+
+    /// An Axum handler that responds to a batch of server function calls sent by
+    /// [`BatchClient`](crate::batch::BatchClient), running each with [`handle_server_fn`].
+    #[cfg(feature = "batch")]
+    pub async fn handle_batch(req: Request<Body>) -> Response<Body> {
+        handle_batch_with(req, handle_server_fn).await
+    }
+
+    /// Responds to a batch of server function calls, running each with `handler`.
+    ///
+    /// Each call is given the headers and extensions of the batch request, overridden by the
+    /// headers of the call itself.
+    #[cfg(feature = "batch")]
+    pub async fn handle_batch_with<F, Fut>(
+        req: Request<Body>,
+        handler: F,
+    ) -> Response<Body>
+    where
+        F: Fn(Request<Body>) -> Fut,
+        Fut: std::future::Future<Output = Response<Body>>,
+    {
+        use crate::{
+            batch::{run_batch, BatchedResponse},
+            error::{NoCustomError, ServerFnError},
+            request::get_body_limits,
+            response::Res,
+        };
+        use axum::body::HttpBody;
+        use http::{HeaderName, HeaderValue, Uri};
+
+        let path = req.uri().path().to_string();
+        let error_response = |e: ServerFnError| {
+            <Response<Body> as Res<NoCustomError>>::error_response(&path, &e)
+        };
+
+        let (parts, body) = req.into_parts();
+        let max = get_body_limits().max_body_size().unwrap_or(usize::MAX);
+        let body = match axum::body::to_bytes(body, max).await {
+            Ok(body) => body,
+            Err(e) => {
+                return error_response(ServerFnError::PayloadTooLarge(
+                    e.to_string(),
+                ))
+            }
+        };
+
+        let res = run_batch(&body, |item| {
+            let mut parts = parts.clone();
+            let item_req = item
+                .method
+                .parse::<Method>()
+                .ok()
+                .zip(item.path.parse::<Uri>().ok());
+            let handled = item_req.map(|(method, uri)| {
+                parts.method = method;
+                parts.uri = uri;
+                for (name, value) in &item.headers {
+                    if let (Ok(name), Ok(value)) = (
+                        HeaderName::from_bytes(name.as_bytes()),
+                        HeaderValue::from_str(value),
+                    ) {
+                        parts.headers.insert(name, value);
+                    }
+                }
+                handler(Request::from_parts(parts, Body::from(item.body)))
+            });
+            async move {
+                let Some(res) = handled else {
+                    return BatchedResponse::error(
+                        StatusCode::BAD_REQUEST,
+                        "invalid method or path in batched call",
+                    );
+                };
+                let (parts, body) = res.await.into_parts();
+                // a streaming body may never end, so it can't be sent back with the others
+                if body.size_hint().exact().is_none() {
+                    return BatchedResponse::error(
+                        StatusCode::BAD_REQUEST,
+                        "server functions with a streaming output cannot be \
+                         batched",
+                    );
+                }
+                match axum::body::to_bytes(body, usize::MAX).await {
+                    Ok(body) => BatchedResponse {
+                        status: parts.status.as_u16(),
+                        headers: parts
+                            .headers
+                            .iter()
+                            .filter_map(|(name, value)| {
+                                Some((
+                                    name.to_string(),
+                                    value.to_str().ok()?.to_string(),
+                                ))
+                            })
+                            .collect(),
+                        body,
+                    },
+                    Err(e) => BatchedResponse::error(
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        e,
+                    ),
+                }
+            }
+        })
+        .await;
+
+        res.and_then(|json| Res::try_from_string("application/json", json))
+            .unwrap_or_else(error_response)
+    }
+
+    /// An Axum route that serves the OpenAPI document describing every
+    /// registered server function, as JSON.
+    ///
+    /// ```rust,ignore
+    /// let app = Router::new().route(
+    ///     "/api/openapi.json",
+    ///     server_fn::axum::openapi_route("My API", "1.0.0"),
+    /// );
+    /// ```
+    #[cfg(feature = "openapi")]
+    pub fn openapi_route<S>(
+        title: &str,
+        version: &str,
+    ) -> axum::routing::MethodRouter<S>
+    where
+        S: Clone + Send + Sync + 'static,
+    {
+        let doc = crate::openapi::openapi_document(title, version).to_string();
+        axum::routing::get(move || async move {
+            Response::builder()
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .body(Body::from(doc))
+                .unwrap()
+        })
+    }
 }
 
 /// Actix integration.
@@ -583,7 +902,8 @@ pub mod actix {
                 T::InputEncoding::METHOD,
                 |req| Box::pin(T::run_on_server(req)),
                 T::middlewares,
-            ),
+            )
+            .with_body_limits(T::body_limits),
         );
     }
 
@@ -608,6 +928,21 @@ pub mod actix {
                 .await
                 .0
                 .take()
+        } else if let Some(err) = crate::version::unserved_version(
+            path,
+            server_fn_paths().map(|(path, _)| path),
+        )
+        .or_else(|| {
+            crate::version::client_outdated(
+                req.headers()
+                    .get(crate::version::BUILD_VERSION_HEADER)
+                    .and_then(|h| h.to_str().ok()),
+            )
+        }) {
+            <ActixResponse as crate::response::Res<
+                crate::error::NoCustomError,
+            >>::error_response(path, &err)
+            .take()
         } else {
             HttpResponse::BadRequest().body(format!(
                 "Could not find a server function at the route {path}. \
@@ -648,8 +983,308 @@ pub mod actix {
                 for middleware in middleware {
                     service = middleware.layer(service);
                 }
+                #[cfg(feature = "csrf")]
+                if crate::csrf::csrf_protection_enabled() {
+                    service = crate::middleware::Layer::layer(
+                        &crate::csrf::Csrf,
+                        service,
+                    );
+                }
                 service
             },
         )
     }
+
+    /// An Actix handler that responds to a batch of server function calls sent by
+    /// [`BatchClient`](crate::batch::BatchClient), running each with [`handle_server_fn`].
+    #[cfg(feature = "batch")]
+    pub async fn handle_batch(
+        req: HttpRequest,
+        payload: Payload,
+    ) -> HttpResponse {
+        handle_batch_with(req, payload, handle_server_fn).await
+    }
+
+    /// Responds to a batch of server function calls, running each with `handler`.
+    ///
+    /// Each call is given the headers and peer address of the batch request, overridden by the
+    /// headers of the call itself. It is built as a new request, so it does not have access to
+    /// the app data of the batch request.
+    #[cfg(feature = "batch")]
+    pub async fn handle_batch_with<F, Fut>(
+        req: HttpRequest,
+        payload: Payload,
+        handler: F,
+    ) -> HttpResponse
+    where
+        F: Fn(HttpRequest, Payload) -> Fut,
+        Fut: std::future::Future<Output = HttpResponse>,
+    {
+        use crate::{
+            batch::{run_batch, BatchedRequest, BatchedResponse},
+            error::{NoCustomError, ServerFnError},
+            request::get_body_limits,
+            response::Res,
+        };
+        use actix_web::{
+            body::{to_bytes, BodySize, MessageBody},
+            http::{
+                header::{HeaderName, HeaderValue},
+                Method as ActixMethod, Uri,
+            },
+            test::TestRequest,
+            FromRequest,
+        };
+        use std::str::FromStr;
+
+        let path = req.path().to_string();
+        let error_response = |e: ServerFnError| {
+            <ActixResponse as Res<NoCustomError>>::error_response(&path, &e)
+                .take()
+        };
+
+        let body = match get_body_limits().max_body_size() {
+            None => payload
+                .to_bytes()
+                .await
+                .map_err(|e| ServerFnError::Args(e.to_string())),
+            Some(max) => match payload.to_bytes_limited(max).await {
+                Ok(body) => {
+                    body.map_err(|e| ServerFnError::Args(e.to_string()))
+                }
+                Err(e) => Err(ServerFnError::PayloadTooLarge(e.to_string())),
+            },
+        };
+        let body = match body {
+            Ok(body) => body,
+            Err(e) => return error_response(e),
+        };
+
+        // builds a request for the call, checking everything that `TestRequest` would panic on
+        let build = |item: BatchedRequest| {
+            let method =
+                ActixMethod::from_bytes(item.method.as_bytes()).ok()?;
+            Uri::from_str(&item.path).ok()?;
+            let mut item_req =
+                TestRequest::default().method(method).uri(&item.path);
+            for (name, value) in req.headers() {
+                item_req =
+                    item_req.insert_header((name.clone(), value.clone()));
+            }
+            for (name, value) in &item.headers {
+                if let (Ok(name), Ok(value)) = (
+                    HeaderName::from_bytes(name.as_bytes()),
+                    HeaderValue::from_str(value),
+                ) {
+                    item_req = item_req.insert_header((name, value));
+                }
+            }
+            if let Some(addr) = req.peer_addr() {
+                item_req = item_req.peer_addr(addr);
+            }
+            Some(
+                item_req
+                    .set_payload(item.body)
+                    .to_srv_request()
+                    .into_parts(),
+            )
+        };
+        let handler = &handler;
+
+        let res = run_batch(&body, |item| {
+            let item_req = build(item);
+            async move {
+                let Some((item_req, mut payload)) = item_req else {
+                    return BatchedResponse::error(
+                        http::StatusCode::BAD_REQUEST,
+                        "invalid method or path in batched call",
+                    );
+                };
+                // extracting the payload never fails
+                let payload = Payload::from_request(&item_req, &mut payload)
+                    .await
+                    .expect("could not extract payload");
+                let res = handler(item_req, payload).await;
+                // a streaming body may never end, so it can't be sent back with the others
+                if let BodySize::Stream = res.body().size() {
+                    return BatchedResponse::error(
+                        http::StatusCode::BAD_REQUEST,
+                        "server functions with a streaming output cannot be \
+                         batched",
+                    );
+                }
+                let status = res.status().as_u16();
+                let headers = res
+                    .headers()
+                    .iter()
+                    .filter_map(|(name, value)| {
+                        Some((
+                            name.to_string(),
+                            value.to_str().ok()?.to_string(),
+                        ))
+                    })
+                    .collect();
+                match to_bytes(res.into_body()).await {
+                    Ok(body) => BatchedResponse {
+                        status,
+                        headers,
+                        body,
+                    },
+                    Err(e) => BatchedResponse::error(
+                        http::StatusCode::INTERNAL_SERVER_ERROR,
+                        e,
+                    ),
+                }
+            }
+        })
+        .await;
+
+        match res
+            .and_then(|json| Res::try_from_string("application/json", json))
+        {
+            Ok(res) => ActixResponse::take(res),
+            Err(e) => error_response(e),
+        }
+    }
+
+    /// An Actix route that serves the OpenAPI document describing every
+    /// registered server function, as JSON.
+    ///
+    /// ```rust,ignore
+    /// App::new().route(
+    ///     "/api/openapi.json",
+    ///     server_fn::actix::openapi_route("My API", "1.0.0"),
+    /// )
+    /// ```
+    #[cfg(feature = "openapi")]
+    pub fn openapi_route(title: &str, version: &str) -> actix_web::Route {
+        let doc = crate::openapi::openapi_document(title, version).to_string();
+        actix_web::web::get().to(move || {
+            let doc = doc.clone();
+            async move {
+                HttpResponse::Ok()
+                    .content_type("application/json")
+                    .body(doc)
+            }
+        })
+    }
+}
+
+/// Integration with any server framework that can convert its requests and responses to and
+/// from [`http::Request<Bytes>`] and [`http::Response<Body>`](response::generic::Body).
+#[cfg(all(feature = "generic", feature = "ssr"))]
+pub mod generic {
+    use crate::{
+        middleware::BoxedService, response::generic::Body, Encoding,
+        LazyServerFnMap, ServerFn, ServerFnTraitObj,
+    };
+    use bytes::Bytes;
+    use http::{Method, Request, Response, StatusCode};
+
+    static REGISTERED_SERVER_FUNCTIONS: LazyServerFnMap<
+        Request<Bytes>,
+        Response<Body>,
+    > = initialize_server_fn_map!(Request<Bytes>, Response<Body>);
+
+    /// Explicitly register a server function. This is only necessary if you are
+    /// running the server in a WASM environment (or a rare environment that the
+    /// `inventory` crate won't work in.).
+    pub fn register_explicit<T>()
+    where
+        T: ServerFn<
+                ServerRequest = Request<Bytes>,
+                ServerResponse = Response<Body>,
+            > + 'static,
+    {
+        REGISTERED_SERVER_FUNCTIONS.insert(
+            (T::PATH.into(), T::InputEncoding::METHOD),
+            ServerFnTraitObj::new(
+                T::PATH,
+                T::InputEncoding::METHOD,
+                |req| Box::pin(T::run_on_server(req)),
+                T::middlewares,
+            )
+            .with_body_limits(T::body_limits),
+        );
+    }
+
+    /// The set of all registered server function paths.
+    pub fn server_fn_paths() -> impl Iterator<Item = (&'static str, Method)> {
+        REGISTERED_SERVER_FUNCTIONS
+            .iter()
+            .map(|item| (item.path(), item.method()))
+    }
+
+    /// Responds to a server function request.
+    pub async fn handle_server_fn(req: Request<Bytes>) -> Response<Body> {
+        let path = req.uri().path();
+
+        if let Some(mut service) =
+            get_server_fn_service(path, req.method().clone())
+        {
+            service.0.run(req).await
+        } else if let Some(err) = crate::version::unserved_version(
+            path,
+            server_fn_paths().map(|(path, _)| path),
+        )
+        .or_else(|| {
+            crate::version::client_outdated(
+                req.headers()
+                    .get(crate::version::BUILD_VERSION_HEADER)
+                    .and_then(|h| h.to_str().ok()),
+            )
+        }) {
+            crate::response::Res::<crate::error::NoCustomError>::error_response(
+                path, &err,
+            )
+        } else {
+            Response::builder()
+                .status(StatusCode::BAD_REQUEST)
+                .body(Body::from(format!(
+                    "Could not find a server function at the route {path}. \
+                     \n\nIt's likely that either\n 1. The API prefix you \
+                     specify in the `#[server]` macro doesn't match the \
+                     prefix at which your server function handler is mounted, \
+                     or \n2. You are on a platform that doesn't support \
+                     automatic server function registration and you need to \
+                     call ServerFn::register_explicit() on the server \
+                     function type, somewhere in your `main` function.",
+                )))
+                .unwrap()
+        }
+    }
+
+    /// Returns the limits on the size of the request body that the server function at the given
+    /// path accepts, so that an oversized body can be refused before it is read into memory.
+    pub fn get_server_fn_body_limits(
+        path: &str,
+        method: Method,
+    ) -> Option<crate::request::BodyLimits> {
+        REGISTERED_SERVER_FUNCTIONS
+            .get(&(path.into(), method))
+            .map(|server_fn| server_fn.body_limits())
+    }
+
+    /// Returns the server function at the given path as a service that can be modified.
+    pub fn get_server_fn_service(
+        path: &str,
+        method: Method,
+    ) -> Option<BoxedService<Request<Bytes>, Response<Body>>> {
+        let key = (path.into(), method);
+        REGISTERED_SERVER_FUNCTIONS.get(&key).map(|server_fn| {
+            let middleware = (server_fn.middleware)();
+            let mut service = BoxedService::new(server_fn.clone());
+            for middleware in middleware {
+                service = middleware.layer(service);
+            }
+            #[cfg(feature = "csrf")]
+            if crate::csrf::csrf_protection_enabled() {
+                service = crate::middleware::Layer::layer(
+                    &crate::csrf::Csrf,
+                    service,
+                );
+            }
+            service
+        })
+    }
 }
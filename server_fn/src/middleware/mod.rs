@@ -1,3 +1,7 @@
+#[cfg(feature = "ssr")]
+mod rate_limit;
+#[cfg(feature = "ssr")]
+pub use rate_limit::*;
 use std::{future::Future, pin::Pin};
 
 /// An abstraction over a middleware layer, which can be used to add additional
@@ -5,6 +9,13 @@ use std::{future::Future, pin::Pin};
 pub trait Layer<Req, Res>: Send + Sync + 'static {
     /// Adds this layer to the inner service.
     fn layer(&self, inner: BoxedService<Req, Res>) -> BoxedService<Req, Res>;
+
+    /// The errors this layer may respond with instead of calling the inner service.
+    ///
+    /// These are used to document the responses of the server functions it is applied to.
+    fn rejections(&self) -> Vec<crate::ServerFnError> {
+        Vec::new()
+    }
 }
 
 /// A type-erased service, which takes an HTTP request and returns a response.
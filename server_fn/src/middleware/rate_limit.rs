@@ -0,0 +1,418 @@
+use super::{BoxedService, Layer, Service};
+use crate::{
+    error::{NoCustomError, ServerFnError},
+    response::Res,
+};
+use dashmap::{mapref::entry::Entry, DashMap};
+use http::Method;
+use once_cell::sync::Lazy;
+use std::{
+    borrow::Cow,
+    future::Future,
+    net::{IpAddr, SocketAddr},
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+/// The parts of a server request that can be used to tell callers apart.
+///
+/// This is implemented for the request types of every server integration, so that the same
+/// [`RateLimit`] works with each of them.
+pub trait RequestInfo {
+    /// The method of the request.
+    fn method(&self) -> Method;
+
+    /// The path of the request.
+    fn path(&self) -> &str;
+
+    /// Returns the header with the given name, if any.
+    fn header(&self, name: &str) -> Option<Cow<'_, str>>;
+
+    /// The IP address of the client that made the request, if known.
+    fn remote_addr(&self) -> Option<IpAddr>;
+}
+
+impl<B> RequestInfo for http::Request<B> {
+    fn method(&self) -> Method {
+        self.method().clone()
+    }
+
+    fn path(&self) -> &str {
+        self.uri().path()
+    }
+
+    fn header(&self, name: &str) -> Option<Cow<'_, str>> {
+        self.headers()
+            .get(name)
+            .map(|h| String::from_utf8_lossy(h.as_bytes()))
+    }
+
+    /// With the `axum` feature, Axum adds this when the app is served with
+    /// `into_make_service_with_connect_info::<SocketAddr>()`. Otherwise, the server can insert
+    /// the peer's [`SocketAddr`] into the request's extensions.
+    fn remote_addr(&self) -> Option<IpAddr> {
+        #[cfg(feature = "axum")]
+        {
+            if let Some(info) = self
+                .extensions()
+                .get::<axum::extract::ConnectInfo<SocketAddr>>()
+            {
+                return Some(info.0.ip());
+            }
+        }
+        self.extensions().get::<SocketAddr>().map(SocketAddr::ip)
+    }
+}
+
+#[cfg(feature = "actix")]
+impl RequestInfo for crate::request::actix::ActixRequest {
+    fn method(&self) -> Method {
+        // Actix uses its own `Method` type, which always holds a valid method
+        Method::from_bytes(self.0 .0.method().as_str().as_bytes())
+            .unwrap_or_default()
+    }
+
+    fn path(&self) -> &str {
+        self.0 .0.path()
+    }
+
+    fn header(&self, name: &str) -> Option<Cow<'_, str>> {
+        self.0
+             .0
+            .headers()
+            .get(name)
+            .map(|h| String::from_utf8_lossy(h.as_bytes()))
+    }
+
+    fn remote_addr(&self) -> Option<IpAddr> {
+        self.0 .0.peer_addr().map(|addr| addr.ip())
+    }
+}
+
+type KeyFn = dyn Fn(&dyn RequestInfo) -> Option<String> + Send + Sync;
+
+/// A middleware that limits how often each caller can call a server function, using an
+/// in-memory token bucket per caller.
+///
+/// Each caller may make a burst of up to `requests` calls, and then one more call each time
+/// `per / requests` passes. Calls over the limit are answered with
+/// [`ServerFnError::TooManyRequests`] and a `429 Too Many Requests` status, with a
+/// `Retry-After` header giving the number of seconds until the next call will be allowed.
+///
+/// Callers are told apart by the key returned by [`RateLimit::key_by`], which defaults to
+/// their IP address. Requests without a key are rejected with a `500 Internal Server Error`,
+/// as letting them through would let any caller get around the limit, and putting them all
+/// in one bucket would let one caller use up the calls of every other. With the default key,
+/// this means the server must make the caller's address available (see
+/// [`RequestInfo::remote_addr`]).
+///
+/// ```rust,ignore
+/// #[server]
+/// #[middleware(RateLimit::new(5, Duration::from_secs(60)).by_header("x-session-id"))]
+/// pub async fn send_message(text: String) -> Result<(), ServerFnError> {
+///     todo!()
+/// }
+/// ```
+///
+/// ## State
+/// The buckets live in a single map for the whole process, keyed by the path of the server
+/// function and the caller's key, because middleware is created anew for each request. They are
+/// not shared between multiple instances of a server. Buckets that have refilled completely are
+/// dropped from time to time as new callers are added.
+#[derive(Clone)]
+pub struct RateLimit {
+    requests: u32,
+    per: Duration,
+    key: Arc<KeyFn>,
+}
+
+impl std::fmt::Debug for RateLimit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RateLimit")
+            .field("requests", &self.requests)
+            .field("per", &self.per)
+            .finish_non_exhaustive()
+    }
+}
+
+impl RateLimit {
+    /// Allows each caller `requests` calls every `per`, keyed by their IP address.
+    pub fn new(requests: u32, per: Duration) -> Self {
+        Self {
+            requests: requests.max(1),
+            per,
+            key: Arc::new(|req: &dyn RequestInfo| {
+                req.remote_addr().map(|ip| ip.to_string())
+            }),
+        }
+    }
+
+    /// Tells callers apart with the given function, which could read a session id from a
+    /// cookie, or an API key from a header.
+    pub fn key_by(
+        mut self,
+        key: impl Fn(&dyn RequestInfo) -> Option<String> + Send + Sync + 'static,
+    ) -> Self {
+        self.key = Arc::new(key);
+        self
+    }
+
+    /// Tells callers apart by the value of the given header.
+    pub fn by_header(self, name: &'static str) -> Self {
+        self.key_by(move |req| req.header(name).map(Cow::into_owned))
+    }
+
+    /// Tells callers apart by their IP address.
+    pub fn by_ip(self) -> Self {
+        self.key_by(|req| req.remote_addr().map(|ip| ip.to_string()))
+    }
+
+    /// Takes a token from the caller's bucket, or returns why the call isn't allowed.
+    pub fn check(&self, req: &dyn RequestInfo) -> Result<(), RateLimited> {
+        let key = (self.key)(req).ok_or(RateLimited::UnknownCaller)?;
+        self.check_key(req.path(), &key, Instant::now())
+            .map_err(RateLimited::RetryAfter)
+    }
+
+    fn check_key(
+        &self,
+        path: &str,
+        key: &str,
+        now: Instant,
+    ) -> Result<(), Duration> {
+        let capacity = f64::from(self.requests);
+        let refill = self.per.as_secs_f64() / capacity;
+
+        let mut inserted = false;
+        let res = {
+            let mut bucket =
+                match BUCKETS.entry((path.to_string(), key.to_string())) {
+                    Entry::Occupied(entry) => entry.into_ref(),
+                    Entry::Vacant(entry) => {
+                        inserted = true;
+                        entry.insert(Bucket {
+                            tokens: capacity,
+                            updated: now,
+                            full_at: now,
+                        })
+                    }
+                };
+            let elapsed = now.saturating_duration_since(bucket.updated);
+            bucket.updated = now;
+            bucket.tokens = if refill > 0.0 {
+                (bucket.tokens + elapsed.as_secs_f64() / refill).min(capacity)
+            } else {
+                capacity
+            };
+
+            let res = if bucket.tokens >= 1.0 {
+                bucket.tokens -= 1.0;
+                Ok(())
+            } else {
+                Err(Duration::from_secs_f64((1.0 - bucket.tokens) * refill))
+            };
+            bucket.full_at = now
+                + Duration::from_secs_f64((capacity - bucket.tokens) * refill);
+            res
+        };
+
+        if inserted
+            && INSERTS.fetch_add(1, Ordering::Relaxed) % PRUNE_EVERY == 0
+        {
+            prune(&BUCKETS, now);
+        }
+
+        res
+    }
+}
+
+/// The reason a call was refused by [`RateLimit::check`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateLimited {
+    /// The caller has used up their calls, and can make another after this long.
+    RetryAfter(Duration),
+    /// No key could be found to tell the caller apart from others.
+    UnknownCaller,
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+    // when the bucket will have refilled completely, at the rate of the limit that filled it
+    full_at: Instant,
+}
+
+// the map is pruned each time this many buckets have been added to it
+const PRUNE_EVERY: usize = 1024;
+
+static BUCKETS: Lazy<DashMap<(String, String), Bucket>> =
+    Lazy::new(DashMap::new);
+
+static INSERTS: AtomicUsize = AtomicUsize::new(0);
+
+// a full bucket is the same as having none, so the buckets of callers who have been idle
+// long enough can be dropped to keep the map from growing forever
+fn prune(buckets: &DashMap<(String, String), Bucket>, now: Instant) {
+    buckets.retain(|_, bucket| bucket.full_at > now);
+}
+
+impl<Req, Resp> Layer<Req, Resp> for RateLimit
+where
+    Req: RequestInfo + Send + 'static,
+    Resp: Res<NoCustomError> + Send + 'static,
+{
+    fn layer(&self, inner: BoxedService<Req, Resp>) -> BoxedService<Req, Resp> {
+        BoxedService::new(RateLimitService {
+            limit: self.clone(),
+            inner,
+        })
+    }
+
+    fn rejections(&self) -> Vec<ServerFnError> {
+        vec![ServerFnError::TooManyRequests(String::new())]
+    }
+}
+
+struct RateLimitService<Req, Resp> {
+    limit: RateLimit,
+    inner: BoxedService<Req, Resp>,
+}
+
+impl<Req, Resp> Service<Req, Resp> for RateLimitService<Req, Resp>
+where
+    Req: RequestInfo + Send + 'static,
+    Resp: Res<NoCustomError> + Send + 'static,
+{
+    fn run(&mut self, req: Req) -> Pin<Box<dyn Future<Output = Resp> + Send>> {
+        match self.limit.check(&req) {
+            Ok(()) => self.inner.0.run(req),
+            Err(RateLimited::UnknownCaller) => {
+                let err = ServerFnError::ServerError(
+                    "the caller could not be told apart from others to limit \
+                     their calls"
+                        .into(),
+                );
+                let res = Resp::error_response(req.path(), &err);
+                Box::pin(async move { res })
+            }
+            Err(RateLimited::RetryAfter(retry_after)) => {
+                // Retry-After only takes whole seconds
+                let secs = retry_after.as_secs_f64().ceil() as u64;
+                let err = ServerFnError::TooManyRequests(format!(
+                    "retry after {secs} seconds"
+                ));
+                let mut res = Resp::error_response(req.path(), &err);
+                res.insert_header("retry-after", &secs.to_string());
+                Box::pin(async move { res })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the buckets are shared by the whole process, so each test uses its own path
+    fn limit() -> RateLimit {
+        RateLimit::new(2, Duration::from_secs(10))
+    }
+
+    #[test]
+    fn allows_a_burst_up_to_the_limit() {
+        let limit = limit();
+        let now = Instant::now();
+        assert_eq!(limit.check_key("/burst", "a", now), Ok(()));
+        assert_eq!(limit.check_key("/burst", "a", now), Ok(()));
+        // one call is refilled every 5 seconds
+        assert_eq!(
+            limit.check_key("/burst", "a", now),
+            Err(Duration::from_secs(5))
+        );
+    }
+
+    #[test]
+    fn refills_over_time() {
+        let limit = limit();
+        let now = Instant::now();
+        assert!(limit.check_key("/refill", "a", now).is_ok());
+        assert!(limit.check_key("/refill", "a", now).is_ok());
+
+        let later = now + Duration::from_secs(3);
+        assert!(limit.check_key("/refill", "a", later).is_err());
+        let later = now + Duration::from_secs(5);
+        assert_eq!(limit.check_key("/refill", "a", later), Ok(()));
+        assert!(limit.check_key("/refill", "a", later).is_err());
+
+        // the bucket never holds more than the limit
+        let much_later = now + Duration::from_secs(60);
+        assert!(limit.check_key("/refill", "a", much_later).is_ok());
+        assert!(limit.check_key("/refill", "a", much_later).is_ok());
+        assert!(limit.check_key("/refill", "a", much_later).is_err());
+    }
+
+    #[test]
+    fn keys_and_paths_have_separate_buckets() {
+        let limit = limit();
+        let now = Instant::now();
+        assert!(limit.check_key("/isolation", "a", now).is_ok());
+        assert!(limit.check_key("/isolation", "a", now).is_ok());
+        assert!(limit.check_key("/isolation", "a", now).is_err());
+
+        assert!(limit.check_key("/isolation", "b", now).is_ok());
+        assert!(limit.check_key("/isolation-other", "a", now).is_ok());
+    }
+
+    #[test]
+    fn prunes_only_buckets_that_have_refilled() {
+        let now = Instant::now();
+        let bucket = |full_in: u64| Bucket {
+            tokens: 0.0,
+            updated: now,
+            full_at: now + Duration::from_secs(full_in),
+        };
+        let buckets = DashMap::new();
+        buckets.insert(("/a".to_string(), "short".to_string()), bucket(1));
+        buckets.insert(("/b".to_string(), "long".to_string()), bucket(3600));
+
+        prune(&buckets, now + Duration::from_secs(60));
+        assert!(!buckets.contains_key(&("/a".to_string(), "short".to_string())));
+        assert!(buckets.contains_key(&("/b".to_string(), "long".to_string())));
+    }
+
+    #[test]
+    fn a_bucket_is_full_again_after_its_own_period() {
+        let now = Instant::now();
+        let limit = RateLimit::new(2, Duration::from_secs(3600));
+        assert!(limit.check_key("/full-at", "a", now).is_ok());
+        let key = ("/full-at".to_string(), "a".to_string());
+        // one call is refilled every 30 minutes
+        assert_eq!(
+            BUCKETS.get(&key).unwrap().full_at,
+            now + Duration::from_secs(1800)
+        );
+    }
+
+    #[test]
+    fn callers_without_a_key_are_refused() {
+        let limit = limit();
+        let req = http::Request::builder()
+            .uri("/unknown-caller")
+            .body(())
+            .unwrap();
+        assert_eq!(limit.check(&req), Err(RateLimited::UnknownCaller));
+
+        let limit = limit.by_header("x-session-id");
+        assert_eq!(limit.check(&req), Err(RateLimited::UnknownCaller));
+        let req = http::Request::builder()
+            .uri("/unknown-caller")
+            .header("x-session-id", "abc")
+            .body(())
+            .unwrap();
+        assert_eq!(limit.check(&req), Ok(()));
+    }
+}
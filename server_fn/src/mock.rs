@@ -0,0 +1,623 @@
+//! An in-process transport for testing code that calls server functions, without running a
+//! server.
+//!
+//! [`MockClient`] hands each call to a handler registered for that server function:
+//! - [`register`] runs the real body of the server function, which is only available when the
+//!   `ssr` feature is enabled.
+//! - [`stub`] runs the given function instead, which works in any build.
+//!
+//! Either way, the arguments and output go through the server function's real input and output
+//! encodings, and every call is recorded, so it can be inspected with [`calls`].
+//!
+//! The `mock` feature is usually only enabled for tests, as a dev-dependency:
+//! ```toml
+//! [dev-dependencies]
+//! server_fn = { version = "...", features = ["mock"] }
+//! ```
+//!
+//! A server function only uses [`MockClient`] if it is set as its `client`, which can be limited
+//! to tests with a type alias:
+//! ```rust,ignore
+//! #[cfg(test)]
+//! type ServerClient = server_fn::mock::MockClient;
+//! #[cfg(not(test))]
+//! type ServerClient = server_fn::client::browser::BrowserClient;
+//!
+//! #[server(client = ServerClient)]
+//! pub async fn add_todo(title: String) -> Result<u32, ServerFnError> {
+//!     todo!()
+//! }
+//!
+//! #[tokio::test]
+//! async fn adding_a_todo() {
+//!     mock::stub::<AddTodo, _, _>(|_| async { Ok(1) });
+//!
+//!     let action = ServerAction::<AddTodo>::new();
+//!     action.dispatch(AddTodo { title: "Test".into() });
+//!     // ...
+//!
+//!     assert_eq!(mock::calls::<AddTodo>()[0].title, "Test");
+//! }
+//! ```
+//!
+//! Handlers and calls belong to a [`MockScope`]. Each thread has its own, and the test harness
+//! runs each test on its own thread, so tests don't see each other's handlers, and [`reset`]
+//! only affects the current test. A call made on another thread, such as by a task spawned on a
+//! multi-threaded runtime, needs to be given the test's scope explicitly:
+//! ```rust,ignore
+//! let scope = MockScope::current();
+//! tokio::spawn(scope.scope(async { add_todo("Test".into()).await }));
+//! ```
+
+use crate::{
+    client::Client,
+    codec::{FromReq, IntoRes},
+    error::{ServerFnError, ServerFnErrorStatus},
+    redirect::REDIRECT_HEADER,
+    request::ClientReq,
+    response::{generic::Body, ClientRes, Res},
+    ServerFn,
+};
+use bytes::Bytes;
+use dashmap::DashMap;
+use futures::{stream, Stream, TryStreamExt};
+use http::{header, HeaderName, HeaderValue, Method, Request, Response};
+use std::{
+    any::Any, cell::RefCell, fmt::Debug, future::Future, pin::Pin,
+    str::FromStr, sync::Arc,
+};
+
+type Handler = Arc<
+    dyn Fn(
+            MockScope,
+            Request<Bytes>,
+        ) -> Pin<Box<dyn Future<Output = Response<Bytes>> + Send>>
+        + Send
+        + Sync,
+>;
+
+/// The handlers registered with [`register`] and [`stub`], and the calls recorded for
+/// [`calls`].
+///
+/// Each thread starts with its own scope, which is used unless another one has been entered
+/// with [`MockScope::scope`]. Cloning a scope returns a handle to the same handlers and calls.
+#[derive(Clone, Default)]
+pub struct MockScope(Arc<ScopeState>);
+
+#[derive(Default)]
+struct ScopeState {
+    handlers: DashMap<String, Handler>,
+    calls: DashMap<String, Vec<Box<dyn Any + Send + Sync>>>,
+}
+
+thread_local! {
+    static CURRENT: RefCell<MockScope> = RefCell::default();
+}
+
+impl MockScope {
+    /// Creates a new scope, with no handlers and no calls.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the scope in use on the current thread.
+    pub fn current() -> Self {
+        CURRENT.with_borrow(Clone::clone)
+    }
+
+    /// Wraps `fut` so that it uses this scope whenever it is polled, whichever thread that
+    /// happens on.
+    pub fn scope<F>(&self, fut: F) -> impl Future<Output = F::Output>
+    where
+        F: Future,
+    {
+        let scope = self.clone();
+        let mut fut = Box::pin(fut);
+        futures::future::poll_fn(move |cx| {
+            let _entered = scope.enter();
+            fut.as_mut().poll(cx)
+        })
+    }
+
+    fn enter(&self) -> Entered {
+        Entered(CURRENT.replace(self.clone()))
+    }
+}
+
+// restores the scope that was in use before one was entered
+struct Entered(MockScope);
+
+impl Drop for Entered {
+    fn drop(&mut self) {
+        CURRENT.set(self.0.clone());
+    }
+}
+
+/// Handles calls to the server function `T` by running its body.
+pub fn register<T>()
+where
+    T: ServerFn
+        + FromReq<T::InputEncoding, Request<Bytes>, T::Error>
+        + Clone
+        + Send
+        + Sync
+        + 'static,
+    T::Output: IntoRes<T::OutputEncoding, Response<Body>, T::Error>,
+    T::Error: Send + Sync + Debug,
+{
+    handle::<T, _, _>(T::run_body)
+}
+
+/// Handles calls to the server function `T` with `handler`, which receives the decoded
+/// arguments.
+pub fn stub<T, F, Fut>(handler: F)
+where
+    T: ServerFn
+        + FromReq<T::InputEncoding, Request<Bytes>, T::Error>
+        + Clone
+        + Send
+        + Sync
+        + 'static,
+    T::Output: IntoRes<T::OutputEncoding, Response<Body>, T::Error>,
+    T::Error: Send + Sync + Debug,
+    F: Fn(T) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<T::Output, ServerFnError<T::Error>>> + Send,
+{
+    handle::<T, _, _>(handler)
+}
+
+fn handle<T, F, Fut>(handler: F)
+where
+    T: ServerFn
+        + FromReq<T::InputEncoding, Request<Bytes>, T::Error>
+        + Clone
+        + Send
+        + Sync
+        + 'static,
+    T::Output: IntoRes<T::OutputEncoding, Response<Body>, T::Error>,
+    T::Error: Send + Sync + Debug,
+    F: Fn(T) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<T::Output, ServerFnError<T::Error>>> + Send,
+{
+    let handler = Arc::new(handler);
+    let handler: Handler = Arc::new(move |scope, req| {
+        let handler = Arc::clone(&handler);
+        Box::pin(async move {
+            let res: Response<Body> = async {
+                let args = <T as FromReq<
+                    T::InputEncoding,
+                    Request<Bytes>,
+                    T::Error,
+                >>::from_req(req)
+                .await?;
+                scope
+                    .0
+                    .calls
+                    .entry(T::PATH.to_string())
+                    .or_default()
+                    .push(Box::new(args.clone()));
+                let output = handler(args).await?;
+                <T::Output as IntoRes<
+                    T::OutputEncoding,
+                    Response<Body>,
+                    T::Error,
+                >>::into_res(output)
+                .await
+            }
+            .await
+            .unwrap_or_else(|e| Res::error_response(T::PATH, &e));
+            match <Response<Body> as Res<T::Error>>::try_into_buffered(res)
+                .await
+            {
+                Ok((res, body)) => res.map(|_| body.unwrap_or_default()),
+                Err(e) => Response::builder()
+                    .status(e.status_code())
+                    .body(Bytes::from(e.to_string()))
+                    .unwrap(),
+            }
+        })
+    });
+    MockScope::current()
+        .0
+        .handlers
+        .insert(T::PATH.to_string(), handler);
+}
+
+/// Returns the arguments of every call to the server function `T` so far in the current
+/// [`MockScope`], in order.
+pub fn calls<T>() -> Vec<T>
+where
+    T: ServerFn + Clone + 'static,
+{
+    MockScope::current()
+        .0
+        .calls
+        .get(T::PATH)
+        .map(|calls| {
+            calls
+                .iter()
+                .filter_map(|call| call.downcast_ref::<T>().cloned())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Removes every handler and forgets every recorded call in the current [`MockScope`].
+pub fn reset() {
+    let scope = MockScope::current();
+    scope.0.handlers.clear();
+    scope.0.calls.clear();
+}
+
+/// A [`Client`] that calls the handlers registered in the current [`MockScope`], instead of
+/// sending requests to a server.
+pub struct MockClient;
+
+impl<CustErr> Client<CustErr> for MockClient {
+    type Request = MockRequest;
+    type Response = MockResponse;
+
+    async fn send(
+        req: Self::Request,
+    ) -> Result<Self::Response, ServerFnError<CustErr>> {
+        let req = req.into_request().await?;
+        let path = req.uri().path();
+        let scope = MockScope::current();
+        let handler = scope
+            .0
+            .handlers
+            .get(path)
+            .map(|handler| Arc::clone(&handler))
+            .ok_or_else(|| {
+                ServerFnError::Registration(format!(
+                    "no mock handler has been registered for the server \
+                     function at {path}"
+                ))
+            })?;
+        Ok(MockResponse(handler(scope, req).await))
+    }
+}
+
+enum MockBody {
+    Bytes(Bytes),
+    Stream(Pin<Box<dyn Stream<Item = Result<Bytes, ServerFnError>> + Send>>),
+}
+
+/// A call to a server function made with [`MockClient`].
+pub struct MockRequest {
+    method: Method,
+    path: String,
+    headers: Vec<(HeaderName, HeaderValue)>,
+    body: MockBody,
+}
+
+impl Debug for MockRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MockRequest")
+            .field("method", &self.method)
+            .field("path", &self.path)
+            .field("headers", &self.headers)
+            .finish_non_exhaustive()
+    }
+}
+
+impl MockRequest {
+    fn new(
+        method: Method,
+        path: String,
+        accepts: &str,
+        content_type: Option<&str>,
+        body: MockBody,
+    ) -> Self {
+        let mut req = Self {
+            method,
+            path,
+            headers: Vec::new(),
+            body,
+        };
+        req.set_header(header::ACCEPT.as_str(), accepts);
+        if let Some(content_type) = content_type {
+            req.set_header(header::CONTENT_TYPE.as_str(), content_type);
+        }
+        req
+    }
+
+    fn set_header(&mut self, name: &str, value: &str) {
+        if let (Ok(name), Ok(value)) =
+            (HeaderName::from_str(name), HeaderValue::from_str(value))
+        {
+            self.headers.retain(|(existing, _)| existing != name);
+            self.headers.push((name, value));
+        }
+    }
+
+    async fn into_request<CustErr>(
+        self,
+    ) -> Result<Request<Bytes>, ServerFnError<CustErr>> {
+        let body = match self.body {
+            MockBody::Bytes(body) => body,
+            MockBody::Stream(stream) => stream
+                .try_fold(Vec::new(), |mut acc, chunk| async move {
+                    acc.extend_from_slice(&chunk);
+                    Ok(acc)
+                })
+                .await
+                .map_err(|e| ServerFnError::Request(e.to_string()))?
+                .into(),
+        };
+        let mut req = Request::builder().method(self.method).uri(self.path);
+        for (name, value) in self.headers {
+            req = req.header(name, value);
+        }
+        req.body(body)
+            .map_err(|e| ServerFnError::Request(e.to_string()))
+    }
+}
+
+fn unsupported<CustErr>(kind: &str) -> ServerFnError<CustErr> {
+    ServerFnError::Request(format!(
+        "{kind} requests are not supported by MockClient"
+    ))
+}
+
+impl<CustErr> ClientReq<CustErr> for MockRequest {
+    #[cfg(feature = "browser")]
+    type FormData = crate::request::browser::BrowserFormData;
+    #[cfg(not(feature = "browser"))]
+    type FormData = ();
+
+    fn try_new_get(
+        path: &str,
+        accepts: &str,
+        content_type: &str,
+        query: &str,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        Ok(Self::new(
+            Method::GET,
+            format!("{path}?{query}"),
+            accepts,
+            Some(content_type),
+            MockBody::Bytes(Bytes::new()),
+        ))
+    }
+
+    fn try_new_post(
+        path: &str,
+        accepts: &str,
+        content_type: &str,
+        body: String,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        Ok(Self::new(
+            Method::POST,
+            path.to_string(),
+            accepts,
+            Some(content_type),
+            MockBody::Bytes(Bytes::from(body)),
+        ))
+    }
+
+    fn try_new_post_bytes(
+        path: &str,
+        accepts: &str,
+        content_type: &str,
+        body: Bytes,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        Ok(Self::new(
+            Method::POST,
+            path.to_string(),
+            accepts,
+            Some(content_type),
+            MockBody::Bytes(body),
+        ))
+    }
+
+    fn try_new_post_form_data(
+        _path: &str,
+        _accepts: &str,
+        _content_type: &str,
+        _body: Self::FormData,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        Err(unsupported("form data"))
+    }
+
+    fn try_new_multipart(
+        _path: &str,
+        _accepts: &str,
+        _body: Self::FormData,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        Err(unsupported("multipart"))
+    }
+
+    fn try_new_streaming(
+        path: &str,
+        accepts: &str,
+        content_type: &str,
+        body: impl Stream<Item = Result<Bytes, ServerFnError>> + Send + 'static,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        Ok(Self::new(
+            Method::POST,
+            path.to_string(),
+            accepts,
+            Some(content_type),
+            MockBody::Stream(Box::pin(body)),
+        ))
+    }
+
+    fn request_url(&self) -> String {
+        self.path.clone()
+    }
+
+    fn insert_header(&mut self, name: &str, value: &str) {
+        self.set_header(name, value);
+    }
+
+    fn try_clone(&self) -> Option<Self> {
+        match &self.body {
+            MockBody::Bytes(body) => Some(Self {
+                method: self.method.clone(),
+                path: self.path.clone(),
+                headers: self.headers.clone(),
+                body: MockBody::Bytes(body.clone()),
+            }),
+            MockBody::Stream(_) => None,
+        }
+    }
+}
+
+/// The response to a call made with [`MockClient`].
+#[derive(Debug)]
+pub struct MockResponse(Response<Bytes>);
+
+impl MockResponse {
+    fn header_value(&self, name: &str) -> Option<String> {
+        self.0
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string)
+    }
+}
+
+impl<CustErr> ClientRes<CustErr> for MockResponse {
+    async fn try_into_string(self) -> Result<String, ServerFnError<CustErr>> {
+        String::from_utf8(self.0.into_body().into())
+            .map_err(|e| ServerFnError::Deserialization(e.to_string()))
+    }
+
+    async fn try_into_bytes(self) -> Result<Bytes, ServerFnError<CustErr>> {
+        Ok(self.0.into_body())
+    }
+
+    fn try_into_stream(
+        self,
+    ) -> Result<
+        impl Stream<Item = Result<Bytes, ServerFnError>> + Send + Sync + 'static,
+        ServerFnError<CustErr>,
+    > {
+        Ok(stream::iter([Ok(self.0.into_body())]))
+    }
+
+    fn status(&self) -> u16 {
+        self.0.status().as_u16()
+    }
+
+    fn status_text(&self) -> String {
+        self.0
+            .status()
+            .canonical_reason()
+            .unwrap_or_default()
+            .to_string()
+    }
+
+    fn location(&self) -> String {
+        self.header_value(header::LOCATION.as_str())
+            .unwrap_or_default()
+    }
+
+    fn has_redirect(&self) -> bool {
+        self.0.headers().contains_key(REDIRECT_HEADER)
+    }
+
+    fn header(&self, name: &str) -> Option<String> {
+        self.header_value(name)
+    }
+}
+
+#[cfg(all(test, feature = "json"))]
+mod tests {
+    use super::*;
+    use crate::{codec::Json, error::NoCustomError};
+    use futures::executor::block_on;
+    use serde::{Deserialize, Serialize};
+
+    macro_rules! add_fn {
+        ($name:ident, $path:literal) => {
+            #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+            struct $name {
+                a: u32,
+                b: u32,
+            }
+
+            impl ServerFn for $name {
+                const PATH: &'static str = $path;
+                type Client = MockClient;
+                type ServerRequest = Request<Bytes>;
+                type ServerResponse = Response<Body>;
+                type Output = u32;
+                type InputEncoding = Json;
+                type OutputEncoding = Json;
+                type Error = NoCustomError;
+
+                async fn run_body(self) -> Result<u32, ServerFnError> {
+                    Ok(self.a + self.b)
+                }
+            }
+        };
+    }
+
+    add_fn!(Registered, "/api/registered");
+    add_fn!(Stubbed, "/api/stubbed");
+    add_fn!(Failing, "/api/failing");
+    add_fn!(Missing, "/api/missing");
+    add_fn!(Scoped, "/api/scoped");
+
+    #[test]
+    fn registered_calls_run_the_body() {
+        register::<Registered>();
+        let output =
+            block_on(Registered { a: 1, b: 2 }.run_on_client()).unwrap();
+        assert_eq!(output, 3);
+        assert_eq!(calls::<Registered>(), vec![Registered { a: 1, b: 2 }]);
+    }
+
+    #[test]
+    fn stubs_replace_the_body() {
+        stub::<Stubbed, _, _>(|args| async move { Ok(args.a * args.b) });
+        let output = block_on(Stubbed { a: 3, b: 4 }.run_on_client()).unwrap();
+        assert_eq!(output, 12);
+        assert_eq!(calls::<Stubbed>(), vec![Stubbed { a: 3, b: 4 }]);
+    }
+
+    #[test]
+    fn errors_from_stubs_reach_the_caller() {
+        stub::<Failing, _, _>(|_| async {
+            Err(ServerFnError::ServerError("failed".into()))
+        });
+        let err = block_on(Failing { a: 1, b: 1 }.run_on_client()).unwrap_err();
+        assert_eq!(err, ServerFnError::ServerError("failed".into()));
+    }
+
+    #[test]
+    fn calls_without_a_handler_fail() {
+        let err = block_on(Missing { a: 1, b: 1 }.run_on_client()).unwrap_err();
+        assert!(matches!(err, ServerFnError::Registration(_)));
+        assert!(calls::<Missing>().is_empty());
+    }
+
+    #[test]
+    fn threads_have_separate_scopes() {
+        register::<Scoped>();
+        let err = std::thread::spawn(|| {
+            block_on(Scoped { a: 1, b: 1 }.run_on_client()).unwrap_err()
+        })
+        .join()
+        .unwrap();
+        assert!(matches!(err, ServerFnError::Registration(_)));
+
+        std::thread::spawn(reset).join().unwrap();
+        assert_eq!(block_on(Scoped { a: 1, b: 1 }.run_on_client()), Ok(2));
+    }
+
+    #[test]
+    fn a_scope_can_be_used_on_another_thread() {
+        register::<Scoped>();
+        let scope = MockScope::current();
+        let output = std::thread::spawn(move || {
+            block_on(scope.scope(Scoped { a: 2, b: 3 }.run_on_client()))
+        })
+        .join()
+        .unwrap();
+        assert_eq!(output, Ok(5));
+        assert_eq!(calls::<Scoped>(), vec![Scoped { a: 2, b: 3 }]);
+    }
+}
@@ -0,0 +1,430 @@
+//! Generates an [OpenAPI 3.1](https://spec.openapis.org/oas/v3.1.0) document
+//! describing every server function registered in this binary.
+//!
+//! With the `openapi` feature enabled, the `#[server]` macro registers a
+//! [`ServerFnDoc`] for each server function alongside its handler. The document
+//! lists each function's path, method and content types. The schemas of its
+//! arguments and return type are included whenever they implement
+//! [`JsonSchema`], and left open (`{}`) when they don't. Arguments of type
+//! `Option<_>` are documented as optional.
+//!
+//! Besides the successful response, each function documents the error statuses
+//! it can respond with: `500` and `422` for errors returned by the function
+//! itself, `413` if its body size is limited, `403` if its CSRF token is
+//! checked, `429` if it is rate limited, and `410` if a build version has been
+//! set. Middleware reports the errors it can respond with through
+//! [`Layer::rejections`](crate::middleware::Layer::rejections).
+//!
+//! ```rust,ignore
+//! #[derive(Serialize, Deserialize, JsonSchema)]
+//! pub struct Todo {
+//!     id: u32,
+//!     title: String,
+//! }
+//!
+//! /// Returns a single todo item.
+//! #[server]
+//! pub async fn get_todo(id: u32) -> Result<Todo, ServerFnError> {
+//!     todo!()
+//! }
+//!
+//! let doc = server_fn::openapi::openapi_document("Todos", "1.0.0");
+//! ```
+//!
+//! The `axum` and `actix` integrations can also serve the document directly,
+//! with `server_fn::axum::openapi_route` and `server_fn::actix::openapi_route`.
+
+use crate::{
+    codec::Encoding,
+    error::{ServerFnErrorStatus, ValidationErrors},
+    ServerFn, ServerFnError,
+};
+use http::Method;
+pub use schemars;
+use schemars::{
+    gen::{SchemaGenerator, SchemaSettings},
+    schema::Schema,
+    JsonSchema,
+};
+use serde_json::{json, Map, Value};
+use std::{collections::BTreeMap, marker::PhantomData};
+
+/// Produces the schemas of a server function's arguments, by name, along with
+/// whether each argument is required.
+pub type ArgSchemas =
+    fn(&mut SchemaGenerator) -> Vec<(&'static str, bool, Option<Schema>)>;
+
+/// Produces the schema of a server function's return type.
+pub type OutputSchema = fn(&mut SchemaGenerator) -> Option<Schema>;
+
+/// Produces the errors a server function can respond with, other than a
+/// success.
+pub type ErrorResponses = fn() -> Vec<ServerFnError>;
+
+/// A description of a single server function, used to build its entry in the
+/// OpenAPI document.
+pub struct ServerFnDoc {
+    path: &'static str,
+    method: Method,
+    input_content_type: &'static str,
+    output_content_type: &'static str,
+    name: &'static str,
+    docs: &'static str,
+    args: ArgSchemas,
+    output: OutputSchema,
+    errors: ErrorResponses,
+}
+
+impl ServerFnDoc {
+    /// Describes a server function.
+    #[allow(clippy::too_many_arguments)]
+    pub const fn new(
+        path: &'static str,
+        method: Method,
+        input_content_type: &'static str,
+        output_content_type: &'static str,
+        name: &'static str,
+        docs: &'static str,
+        args: ArgSchemas,
+        output: OutputSchema,
+        errors: ErrorResponses,
+    ) -> Self {
+        Self {
+            path,
+            method,
+            input_content_type,
+            output_content_type,
+            name,
+            docs,
+            args,
+            output,
+            errors,
+        }
+    }
+
+    /// The path of the server function.
+    pub fn path(&self) -> &'static str {
+        self.path
+    }
+
+    /// The HTTP method the server function expects.
+    pub fn method(&self) -> Method {
+        self.method.clone()
+    }
+
+    /// The content type of the server function's request body.
+    pub fn input_content_type(&self) -> &'static str {
+        self.input_content_type
+    }
+
+    /// The content type of the server function's response body.
+    pub fn output_content_type(&self) -> &'static str {
+        self.output_content_type
+    }
+
+    /// The name of the Rust function.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// The doc comments on the Rust function.
+    pub fn docs(&self) -> &'static str {
+        self.docs
+    }
+
+    /// The schemas of the server function's arguments, by name, along with
+    /// whether each argument is required.
+    pub fn args(
+        &self,
+        gen: &mut SchemaGenerator,
+    ) -> Vec<(&'static str, bool, Option<Schema>)> {
+        (self.args)(gen)
+    }
+
+    /// The schema of the server function's return type.
+    pub fn output(&self, gen: &mut SchemaGenerator) -> Option<Schema> {
+        (self.output)(gen)
+    }
+
+    /// The errors the server function can respond with, other than a success.
+    pub fn errors(&self) -> Vec<ServerFnError> {
+        (self.errors)()
+    }
+
+    fn operation(&self, gen: &mut SchemaGenerator) -> Value {
+        let args = self.args(gen);
+        let output = schema_value(self.output(gen));
+
+        let mut op = Map::new();
+        op.insert("operationId".into(), self.name.into());
+        if !self.docs.is_empty() {
+            op.insert("description".into(), self.docs.into());
+        }
+
+        // GET server functions take their arguments in the query string;
+        // everything else takes them in the request body
+        if self.method == Method::GET {
+            let params = args
+                .into_iter()
+                .map(|(name, required, schema)| {
+                    json!({
+                        "name": name,
+                        "in": "query",
+                        "required": required,
+                        "schema": schema_value(schema),
+                    })
+                })
+                .collect::<Vec<_>>();
+            op.insert("parameters".into(), params.into());
+        } else {
+            let required = args
+                .iter()
+                .filter(|(_, required, _)| *required)
+                .map(|(name, _, _)| *name)
+                .collect::<Vec<_>>();
+            let properties = args
+                .into_iter()
+                .map(|(name, _, schema)| {
+                    (name.to_string(), schema_value(schema))
+                })
+                .collect::<Map<_, _>>();
+            op.insert(
+                "requestBody".into(),
+                json!({
+                    "required": true,
+                    "content": {
+                        self.input_content_type: {
+                            "schema": {
+                                "type": "object",
+                                "properties": properties,
+                                "required": required,
+                            }
+                        }
+                    }
+                }),
+            );
+        }
+
+        let mut responses = Map::new();
+        responses.insert(
+            "200".into(),
+            json!({
+                "description": "The value returned by the server function.",
+                "content": {
+                    self.output_content_type: { "schema": output }
+                }
+            }),
+        );
+        // several errors can share a status, so they are grouped by it
+        let mut errors = BTreeMap::<u16, Vec<&'static str>>::new();
+        for err in self.errors() {
+            let descriptions =
+                errors.entry(err.status_code().as_u16()).or_default();
+            let description = error_description(&err);
+            if !descriptions.contains(&description) {
+                descriptions.push(description);
+            }
+        }
+        for (status, descriptions) in errors {
+            responses.insert(
+                status.to_string(),
+                json!({
+                    "description": descriptions.join(" "),
+                    "content": {
+                        "text/plain": { "schema": { "type": "string" } }
+                    }
+                }),
+            );
+        }
+        op.insert("responses".into(), responses.into());
+        op.into()
+    }
+}
+
+inventory::collect!(ServerFnDoc);
+
+/// Returns the errors the server function `T` can respond with, based on its
+/// body limits and middleware, and on the server's configuration.
+///
+/// This is used by the `#[server]` macro, and should not need to be used
+/// directly.
+#[doc(hidden)]
+pub fn error_responses<T>() -> Vec<ServerFnError>
+where
+    T: ServerFn,
+    T::ServerRequest: 'static,
+    T::ServerResponse: 'static,
+{
+    // any server function can fail, or reject its arguments
+    let mut errors = vec![
+        ServerFnError::ServerError(String::new()),
+        ServerFnError::Validation(ValidationErrors::new()),
+    ];
+
+    let limits = T::body_limits();
+    if limits.max_body_size().is_some()
+        || limits.max_multipart_size().is_some()
+        || limits.max_fields().is_some()
+        || limits.max_field_size().is_some()
+    {
+        errors.push(ServerFnError::PayloadTooLarge(String::new()));
+    }
+
+    #[cfg(feature = "csrf")]
+    if crate::csrf::csrf_protection_enabled() {
+        errors.push(ServerFnError::InvalidCsrfToken(String::new()));
+    }
+    for middleware in T::middlewares() {
+        errors.extend(middleware.rejections());
+    }
+    // the CSRF token is not checked for safe methods
+    if T::InputEncoding::METHOD == Method::GET {
+        errors.retain(|err| !matches!(err, ServerFnError::InvalidCsrfToken(_)));
+    }
+
+    // once the build has a version, the paths of server functions without an
+    // explicit version change between builds, and older clients are told so
+    if crate::version::get_build_version().is_some() {
+        errors.push(ServerFnError::ClientOutdated(String::new()));
+    }
+
+    errors
+}
+
+fn error_description(err: &ServerFnError) -> &'static str {
+    match err {
+        ServerFnError::PayloadTooLarge(_) => "The request body is too large.",
+        ServerFnError::TooManyRequests(_) => {
+            "Too many calls have been made by this caller."
+        }
+        ServerFnError::InvalidCsrfToken(_) => {
+            "The CSRF token is missing or does not match."
+        }
+        ServerFnError::ClientOutdated(_) => {
+            "The client is running a different build from the server."
+        }
+        ServerFnError::Validation(_) => "The arguments are invalid.",
+        _ => {
+            "The error returned by the server function, in its serialized \
+              form."
+        }
+    }
+}
+
+/// Returns the description of every server function registered in this binary.
+pub fn server_fn_docs() -> impl Iterator<Item = &'static ServerFnDoc> {
+    inventory::iter::<ServerFnDoc>.into_iter()
+}
+
+/// Builds an OpenAPI 3.1 document describing every server function registered
+/// in this binary, with the given API title and version.
+pub fn openapi_document(title: &str, version: &str) -> Value {
+    let mut gen = SchemaSettings::draft2019_09()
+        .with(|settings| {
+            settings.definitions_path = "#/components/schemas/".into();
+            settings.meta_schema = None;
+        })
+        .into_generator();
+
+    let mut paths = Map::new();
+    for doc in server_fn_docs() {
+        let operation = doc.operation(&mut gen);
+        let item = paths
+            .entry(doc.path)
+            .or_insert_with(|| Value::Object(Map::new()));
+        if let Value::Object(item) = item {
+            item.insert(doc.method.as_str().to_lowercase(), operation);
+        }
+    }
+
+    let schemas = gen
+        .take_definitions()
+        .into_iter()
+        .map(|(name, schema)| (name, schema_value(Some(schema))))
+        .collect::<Map<_, _>>();
+
+    json!({
+        "openapi": "3.1.0",
+        "info": {
+            "title": title,
+            "version": version,
+        },
+        "paths": paths,
+        "components": {
+            "schemas": schemas,
+        },
+    })
+}
+
+// a missing schema is described by the empty schema, which accepts any value
+fn schema_value(schema: Option<Schema>) -> Value {
+    schema
+        .and_then(|schema| serde_json::to_value(schema).ok())
+        .unwrap_or_else(|| json!({}))
+}
+
+/// A wrapper used to look up the schema of a type if it implements
+/// [`JsonSchema`], without requiring that it does.
+///
+/// This is used by the `#[server]` macro, and should not need to be used
+/// directly.
+#[doc(hidden)]
+pub struct WrapSchema<T>(PhantomData<fn() -> T>);
+
+impl<T> WrapSchema<T> {
+    #[doc(hidden)]
+    pub const fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T> Default for WrapSchema<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Looks up the schema of the wrapped type, if it has one.
+///
+/// Call this as `(&&WrapSchema::<T>::new()).schema(gen)`, so that the
+/// [`JsonSchema`] implementation is preferred when it exists.
+#[doc(hidden)]
+pub trait ViaSchema {
+    /// Returns the schema of the wrapped type, if it has one.
+    fn schema(&self, gen: &mut SchemaGenerator) -> Option<Schema>;
+}
+
+impl<T: JsonSchema> ViaSchema for &WrapSchema<T> {
+    fn schema(&self, gen: &mut SchemaGenerator) -> Option<Schema> {
+        Some(gen.subschema_for::<T>())
+    }
+}
+
+impl<T> ViaSchema for WrapSchema<T> {
+    fn schema(&self, _gen: &mut SchemaGenerator) -> Option<Schema> {
+        None
+    }
+}
+
+/// Checks whether the wrapped type must be provided, which it must unless it
+/// is an `Option`.
+///
+/// Call this as `(&&WrapSchema::<T>::new()).required()`, so that the `Option`
+/// implementation is preferred when it applies.
+#[doc(hidden)]
+pub trait ViaRequired {
+    /// Returns whether the wrapped type must be provided.
+    fn required(&self) -> bool;
+}
+
+impl<T> ViaRequired for &WrapSchema<Option<T>> {
+    fn required(&self) -> bool {
+        false
+    }
+}
+
+impl<T> ViaRequired for WrapSchema<T> {
+    fn required(&self) -> bool {
+        true
+    }
+}
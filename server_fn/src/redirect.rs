@@ -1,4 +1,12 @@
-use std::sync::OnceLock;
+use pin_project_lite::pin_project;
+use std::{
+    cell::RefCell,
+    fmt::{self, Debug},
+    future::Future,
+    pin::Pin,
+    sync::{Arc, OnceLock},
+    task::{Context, Poll},
+};
 
 /// A custom header that can be set with any value to indicate
 /// that the server function client should redirect to a new route.
@@ -31,3 +39,237 @@ pub fn call_redirect_hook(loc: &str) {
         hook(loc)
     }
 }
+
+/// Resolves a redirect location against `base`, the path that the app is served under.
+///
+/// A server function inside an app served under `/admin` can redirect to `users`, which the
+/// router's redirect hook resolves against its own `base` to land on `/admin/users`. Absolute
+/// paths, full URLs, and locations that only change the query or fragment are returned
+/// unchanged.
+pub fn resolve_redirect(base: &str, loc: &str) -> String {
+    let is_relative = !loc.is_empty()
+        && !loc.starts_with(['/', '?', '#'])
+        && !loc.contains("://");
+    if !is_relative {
+        return loc.to_string();
+    }
+
+    let mut segments = base
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .collect::<Vec<_>>();
+    let (path, rest) = loc
+        .find(['?', '#'])
+        .map(|idx| loc.split_at(idx))
+        .unwrap_or((loc, ""));
+    for segment in path.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            segment => segments.push(segment),
+        }
+    }
+    format!("/{}{rest}", segments.join("/"))
+}
+
+/// Controls what the client does when a server function responds with a redirect, which it
+/// does when the server calls `redirect()`.
+///
+/// The default, [`Follow`](RedirectPolicy::Follow), calls the hook set by
+/// [`set_redirect_hook`], which the router uses to navigate to the new location without
+/// reloading the page. The policy can be changed for a single call with
+/// [`with_redirect_policy`].
+#[derive(Clone, Default)]
+pub enum RedirectPolicy {
+    /// Calls the hook set by [`set_redirect_hook`].
+    #[default]
+    Follow,
+    /// Loads the new location with a full page load. Outside the browser, this is the same as
+    /// [`Follow`](RedirectPolicy::Follow).
+    Reload,
+    /// Doesn't redirect, so the caller can decide what to do with the location returned by
+    /// [`with_redirect_policy`].
+    Ignore,
+    /// Calls the given function instead of the hook set by [`set_redirect_hook`].
+    Custom(Arc<dyn Fn(&str) + Send + Sync>),
+}
+
+impl RedirectPolicy {
+    /// Creates a policy that calls `hook` with the location to redirect to.
+    pub fn custom(hook: impl Fn(&str) + Send + Sync + 'static) -> Self {
+        Self::Custom(Arc::new(hook))
+    }
+}
+
+impl Debug for RedirectPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Follow => f.write_str("Follow"),
+            Self::Reload => f.write_str("Reload"),
+            Self::Ignore => f.write_str("Ignore"),
+            Self::Custom(_) => f.write_str("Custom(..)"),
+        }
+    }
+}
+
+struct RedirectScope {
+    policy: RedirectPolicy,
+    location: Option<String>,
+}
+
+thread_local! {
+    static CURRENT_SCOPE: RefCell<Option<RedirectScope>> = const { RefCell::new(None) };
+}
+
+/// Handles the redirects of any server function called by `fut` according to `policy`,
+/// returning its output along with the location of the last redirect, if any.
+///
+/// The location is the one sent by the server, so a relative location may need to be resolved
+/// with [`resolve_redirect`].
+///
+/// This is useful to open the target of a redirect in a modal route, or with other
+/// navigation options, rather than replacing the current page:
+///
+/// ```rust,ignore
+/// let (res, location) =
+///     with_redirect_policy(RedirectPolicy::Ignore, create_post(post)).await;
+/// if let Some(location) = location {
+///     navigate(&location, NavigateOptions { scroll: false, ..Default::default() });
+/// }
+/// ```
+///
+/// The policy applies to server functions that `fut` calls directly, but not to those called
+/// in tasks that it spawns.
+pub fn with_redirect_policy<F>(
+    policy: RedirectPolicy,
+    fut: F,
+) -> WithRedirectPolicy<F>
+where
+    F: Future,
+{
+    WithRedirectPolicy {
+        inner: fut,
+        scope: Some(RedirectScope {
+            policy,
+            location: None,
+        }),
+    }
+}
+
+pin_project! {
+    /// The future returned by [`with_redirect_policy`].
+    pub struct WithRedirectPolicy<F> {
+        #[pin]
+        inner: F,
+        scope: Option<RedirectScope>,
+    }
+}
+
+impl<F> Future for WithRedirectPolicy<F>
+where
+    F: Future,
+{
+    type Output = (F::Output, Option<String>);
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let prev =
+            CURRENT_SCOPE.with(|current| current.replace(this.scope.take()));
+        let res = this.inner.poll(cx);
+        *this.scope = CURRENT_SCOPE.with(|current| current.replace(prev));
+        res.map(|output| {
+            let location =
+                this.scope.as_mut().and_then(|scope| scope.location.take());
+            (output, location)
+        })
+    }
+}
+
+/// Whether a response with the given status should be handled as a redirect.
+///
+/// `304 Not Modified` is in the `3xx` range, but only tells the client that it can reuse the
+/// value it already has, so it is not a redirect.
+pub(crate) fn is_redirect(status: u16, has_redirect_header: bool) -> bool {
+    has_redirect_header || (status != 304 && (300..=399).contains(&status))
+}
+
+/// Handles a redirect to `loc` according to the policy of the current call.
+pub(crate) fn handle_redirect(loc: &str, hook: Option<&RedirectHook>) {
+    let policy = CURRENT_SCOPE.with(|current| {
+        current.borrow_mut().as_mut().map(|scope| {
+            scope.location = Some(loc.to_string());
+            scope.policy.clone()
+        })
+    });
+    match policy.unwrap_or_default() {
+        RedirectPolicy::Ignore => {}
+        RedirectPolicy::Custom(custom) => custom(loc),
+        #[cfg(feature = "browser")]
+        RedirectPolicy::Reload => {
+            if let Err(e) = web_sys::window()
+                .expect("no window")
+                .location()
+                .set_href(loc)
+            {
+                web_sys::console::error_1(&e);
+            }
+        }
+        _ => {
+            if let Some(hook) = hook {
+                hook(loc);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn not_modified_is_not_a_redirect() {
+        assert!(!is_redirect(304, false));
+        assert!(!is_redirect(200, false));
+        assert!(is_redirect(302, false));
+        assert!(is_redirect(303, false));
+        // the header still redirects, whatever the status
+        assert!(is_redirect(304, true));
+        assert!(is_redirect(200, true));
+    }
+
+    #[test]
+    fn relative_redirects_resolve_against_the_base() {
+        assert_eq!(resolve_redirect("/admin", "users"), "/admin/users");
+        assert_eq!(resolve_redirect("/admin/", "./users/1"), "/admin/users/1");
+        assert_eq!(
+            resolve_redirect("/admin/users", "../posts"),
+            "/admin/posts"
+        );
+        assert_eq!(resolve_redirect("", "users"), "/users");
+        // `..` can't go above the root
+        assert_eq!(resolve_redirect("/admin", "../../users"), "/users");
+    }
+
+    #[test]
+    fn relative_redirects_keep_the_query_and_fragment() {
+        assert_eq!(
+            resolve_redirect("/admin", "users?page=2#top"),
+            "/admin/users?page=2#top"
+        );
+        assert_eq!(
+            resolve_redirect("/admin", "users?next=../posts"),
+            "/admin/users?next=../posts"
+        );
+    }
+
+    #[test]
+    fn other_redirects_are_unchanged() {
+        for loc in
+            ["/users", "?page=2", "#top", "https://example.com/users", ""]
+        {
+            assert_eq!(resolve_redirect("/admin", loc), loc);
+        }
+    }
+}
@@ -1,6 +1,19 @@
-use crate::{error::ServerFnError, request::Req};
-use actix_web::{web::Payload, HttpRequest};
+#[cfg(feature = "actix-websocket")]
+use crate::{
+    codec::ByteStream,
+    request::{WebsocketReq, WebsocketSender},
+    response::actix::ActixResponse,
+};
+use crate::{
+    error::ServerFnError,
+    request::{get_body_limits, too_large, BodyLimits, Req},
+};
+use actix_web::{web::Payload, HttpMessage, HttpRequest};
+#[cfg(feature = "actix-websocket")]
+use actix_ws::Message;
 use bytes::Bytes;
+#[cfg(feature = "actix-websocket")]
+use futures::channel::{mpsc, oneshot};
 use futures::{Stream, StreamExt};
 use send_wrapper::SendWrapper;
 use std::{borrow::Cow, future::Future};
@@ -25,6 +38,36 @@ impl ActixRequest {
             .get(name)
             .map(|h| String::from_utf8_lossy(h.as_bytes()))
     }
+
+    fn limits(&self) -> BodyLimits {
+        self.0
+             .0
+            .extensions()
+            .get::<BodyLimits>()
+            .copied()
+            .unwrap_or_else(get_body_limits)
+    }
+
+    // reads the whole payload, failing as soon as it grows past the body size limit
+    async fn read_body<CustErr>(self) -> Result<Bytes, ServerFnError<CustErr>> {
+        let limits = self.limits();
+        let content_length = self.header("Content-Length").map(Cow::into_owned);
+        limits.check_content_length(
+            content_length.as_deref().map(str::as_bytes),
+        )?;
+        let payload = self.0.take().1;
+        match limits.max_body_size() {
+            None => payload
+                .to_bytes()
+                .await
+                .map_err(|e| ServerFnError::Deserialization(e.to_string())),
+            Some(max) => payload
+                .to_bytes_limited(max)
+                .await
+                .map_err(|_| too_large(max))?
+                .map_err(|e| ServerFnError::Deserialization(e.to_string())),
+        }
+    }
 }
 
 impl From<(HttpRequest, Payload)> for ActixRequest {
@@ -53,19 +96,25 @@ where
         self.header("Referer")
     }
 
+    fn if_none_match(&self) -> Option<Cow<'_, str>> {
+        self.header("If-None-Match")
+    }
+
+    fn body_limits(&self) -> BodyLimits {
+        self.limits()
+    }
+
+    fn set_body_limits(&mut self, limits: BodyLimits) {
+        self.0 .0.extensions_mut().insert(limits);
+    }
+
     fn try_into_bytes(
         self,
     ) -> impl Future<Output = Result<Bytes, ServerFnError<CustErr>>> + Send
     {
         // Actix is going to keep this on a single thread anyway so it's fine to wrap it
         // with SendWrapper, which makes it `Send` but will panic if it moves to another thread
-        SendWrapper::new(async move {
-            let payload = self.0.take().1;
-            payload
-                .to_bytes()
-                .await
-                .map_err(|e| ServerFnError::Deserialization(e.to_string()))
-        })
+        SendWrapper::new(self.read_body())
     }
 
     fn try_into_string(
@@ -75,11 +124,7 @@ where
         // Actix is going to keep this on a single thread anyway so it's fine to wrap it
         // with SendWrapper, which makes it `Send` but will panic if it moves to another thread
         SendWrapper::new(async move {
-            let payload = self.0.take().1;
-            let bytes = payload
-                .to_bytes()
-                .await
-                .map_err(|e| ServerFnError::Deserialization(e.to_string()))?;
+            let bytes = self.read_body().await?;
             String::from_utf8(bytes.into())
                 .map_err(|e| ServerFnError::Deserialization(e.to_string()))
         })
@@ -98,3 +143,72 @@ where
         Ok(SendWrapper::new(stream))
     }
 }
+
+#[cfg(feature = "actix-websocket")]
+impl<CustErr> WebsocketReq<CustErr> for ActixRequest
+where
+    CustErr: 'static,
+{
+    type WebsocketResponse = ActixResponse;
+
+    fn try_into_websocket(
+        self,
+    ) -> impl Future<
+        Output = Result<
+            (ByteStream, WebsocketSender, Self::WebsocketResponse),
+            ServerFnError<CustErr>,
+        >,
+    > + Send {
+        // Actix is going to keep this on a single thread anyway so it's fine to wrap it
+        // with SendWrapper, which makes it `Send` but will panic if it moves to another thread
+        SendWrapper::new(async move {
+            let (req, payload) = self.0.take();
+            let (response, session, mut stream) =
+                actix_ws::handle(&req, payload)
+                    .map_err(|e| ServerFnError::Request(e.to_string()))?;
+
+            let (incoming_tx, incoming_rx) = mpsc::unbounded();
+            let (outgoing_tx, outgoing_rx) = oneshot::channel::<ByteStream>();
+
+            let mut incoming_session = session.clone();
+            actix_web::rt::spawn(async move {
+                while let Some(Ok(msg)) = stream.next().await {
+                    let msg = match msg {
+                        Message::Binary(data) => data,
+                        Message::Text(data) => data.into_bytes(),
+                        Message::Ping(data) => {
+                            if incoming_session.pong(&data).await.is_err() {
+                                break;
+                            }
+                            continue;
+                        }
+                        Message::Close(_) => break,
+                        _ => continue,
+                    };
+                    if incoming_tx.unbounded_send(Ok(msg)).is_err() {
+                        break;
+                    }
+                }
+            });
+
+            let mut outgoing_session = session;
+            actix_web::rt::spawn(async move {
+                if let Ok(outgoing) = outgoing_rx.await {
+                    let mut outgoing = outgoing.into_inner();
+                    while let Some(Ok(msg)) = outgoing.next().await {
+                        if outgoing_session.binary(msg).await.is_err() {
+                            break;
+                        }
+                    }
+                }
+                _ = outgoing_session.close(None).await;
+            });
+
+            Ok((
+                ByteStream::new(incoming_rx),
+                outgoing_tx,
+                ActixResponse::from(response),
+            ))
+        })
+    }
+}
@@ -1,13 +1,30 @@
-use crate::{error::ServerFnError, request::Req};
+#[cfg(feature = "websocket")]
+use crate::{
+    codec::ByteStream,
+    request::{WebsocketReq, WebsocketSender},
+};
+use crate::{
+    error::ServerFnError,
+    request::{get_body_limits, too_large, BodyLimits, Req},
+};
 use axum::body::{Body, Bytes};
 use futures::{Stream, StreamExt};
+#[cfg(feature = "websocket")]
+use http::Response;
 use http::{
-    header::{ACCEPT, CONTENT_TYPE, REFERER},
+    header::{ACCEPT, CONTENT_LENGTH, CONTENT_TYPE, IF_NONE_MATCH, REFERER},
     Request,
 };
-use http_body_util::BodyExt;
+use http_body_util::{BodyExt, LengthLimitError, Limited};
 use std::borrow::Cow;
 
+fn limits_of(req: &Request<Body>) -> BodyLimits {
+    req.extensions()
+        .get::<BodyLimits>()
+        .copied()
+        .unwrap_or_else(get_body_limits)
+}
+
 impl<CustErr> Req<CustErr> for Request<Body>
 where
     CustErr: 'static,
@@ -34,13 +51,45 @@ where
             .map(|h| String::from_utf8_lossy(h.as_bytes()))
     }
 
+    fn if_none_match(&self) -> Option<Cow<'_, str>> {
+        self.headers()
+            .get(IF_NONE_MATCH)
+            .map(|h| String::from_utf8_lossy(h.as_bytes()))
+    }
+
+    fn body_limits(&self) -> BodyLimits {
+        limits_of(self)
+    }
+
+    fn set_body_limits(&mut self, limits: BodyLimits) {
+        self.extensions_mut().insert(limits);
+    }
+
     async fn try_into_bytes(self) -> Result<Bytes, ServerFnError<CustErr>> {
-        let (_parts, body) = self.into_parts();
+        let limits = limits_of(&self);
+        let (parts, body) = self.into_parts();
 
-        body.collect()
+        let Some(max) = limits.max_body_size() else {
+            return body
+                .collect()
+                .await
+                .map(|c| c.to_bytes())
+                .map_err(|e| ServerFnError::Deserialization(e.to_string()));
+        };
+        limits.check_content_length(
+            parts.headers.get(CONTENT_LENGTH).map(|h| h.as_bytes()),
+        )?;
+        Limited::new(body, max)
+            .collect()
             .await
             .map(|c| c.to_bytes())
-            .map_err(|e| ServerFnError::Deserialization(e.to_string()))
+            .map_err(|e| {
+                if e.is::<LengthLimitError>() {
+                    too_large(max)
+                } else {
+                    ServerFnError::Deserialization(e.to_string())
+                }
+            })
     }
 
     async fn try_into_string(self) -> Result<String, ServerFnError<CustErr>> {
@@ -60,3 +109,66 @@ where
         }))
     }
 }
+
+#[cfg(feature = "websocket")]
+impl<CustErr> WebsocketReq<CustErr> for Request<Body>
+where
+    CustErr: 'static,
+{
+    type WebsocketResponse = Response<Body>;
+
+    async fn try_into_websocket(
+        self,
+    ) -> Result<
+        (ByteStream, WebsocketSender, Self::WebsocketResponse),
+        ServerFnError<CustErr>,
+    > {
+        use axum::extract::{
+            ws::{Message, WebSocketUpgrade},
+            FromRequestParts,
+        };
+        use futures::{
+            channel::{mpsc, oneshot},
+            SinkExt,
+        };
+
+        let (mut parts, _body) = self.into_parts();
+        let upgrade = WebSocketUpgrade::from_request_parts(&mut parts, &())
+            .await
+            .map_err(|e| ServerFnError::Request(e.to_string()))?;
+
+        let (incoming_tx, incoming_rx) = mpsc::unbounded();
+        let (outgoing_tx, outgoing_rx) = oneshot::channel::<ByteStream>();
+        let response = upgrade.on_upgrade(|socket| async move {
+            let (mut sink, mut stream) = socket.split();
+            let incoming = async move {
+                while let Some(Ok(msg)) = stream.next().await {
+                    let msg = match msg {
+                        Message::Binary(data) => Bytes::from(data),
+                        Message::Text(data) => Bytes::from(data),
+                        Message::Close(_) => break,
+                        _ => continue,
+                    };
+                    if incoming_tx.unbounded_send(Ok(msg)).is_err() {
+                        break;
+                    }
+                }
+            };
+            let outgoing = async move {
+                if let Ok(outgoing) = outgoing_rx.await {
+                    let mut outgoing = outgoing.into_inner();
+                    while let Some(Ok(msg)) = outgoing.next().await {
+                        if sink.send(Message::Binary(msg.into())).await.is_err()
+                        {
+                            break;
+                        }
+                    }
+                }
+                _ = sink.close().await;
+            };
+            futures::join!(incoming, outgoing);
+        });
+
+        Ok((ByteStream::new(incoming_rx), outgoing_tx, response))
+    }
+}
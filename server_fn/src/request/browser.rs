@@ -1,12 +1,20 @@
 use super::ClientReq;
 use crate::{client::get_server_url, error::ServerFnError};
 use bytes::Bytes;
-use futures::{Stream, StreamExt};
+use futures::{stream, Stream, StreamExt, TryStreamExt};
 pub use gloo_net::http::Request;
-use js_sys::{Reflect, Uint8Array};
+use http::{HeaderName, HeaderValue, Method};
+use js_sys::{Object, Reflect, Uint8Array};
 use send_wrapper::SendWrapper;
-use std::ops::{Deref, DerefMut};
-use wasm_bindgen::JsValue;
+use std::{
+    cell::Cell,
+    fmt::Debug,
+    ops::{Deref, DerefMut},
+    pin::Pin,
+    rc::Rc,
+    str::FromStr,
+};
+use wasm_bindgen::{closure::Closure, JsValue};
 use wasm_streams::ReadableStream;
 use web_sys::{
     AbortController, AbortSignal, FormData, Headers, RequestInit,
@@ -21,6 +29,43 @@ pub struct BrowserRequest(pub(crate) SendWrapper<RequestInner>);
 pub(crate) struct RequestInner {
     pub(crate) request: Request,
     pub(crate) abort_ctrl: Option<AbortOnDrop>,
+    pub(crate) buffered_body: Option<BufferedBody>,
+}
+
+/// A streaming body that is read into memory just before the request is sent, in browsers
+/// that cannot send a `ReadableStream` as the body of a request.
+pub(crate) struct BufferedBody(
+    Pin<Box<dyn Stream<Item = Result<Bytes, ServerFnError>>>>,
+);
+
+impl Debug for BufferedBody {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("BufferedBody").finish()
+    }
+}
+
+impl BufferedBody {
+    /// Reads the whole stream and returns a copy of `request` with it as the body.
+    pub(crate) async fn attach_to(
+        self,
+        request: Request,
+    ) -> Result<Request, JsValue> {
+        let body = self
+            .0
+            .try_fold(Vec::new(), |mut acc, chunk| async move {
+                acc.extend_from_slice(&chunk);
+                Ok(acc)
+            })
+            .await
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        let init = RequestInit::new();
+        init.set_body(&Uint8Array::from(body.as_slice()));
+        let req = web_sys::Request::new_with_request_and_init(
+            &request.into(),
+            &init,
+        )?;
+        Ok(Request::from(req))
+    }
 }
 
 #[derive(Debug)]
@@ -109,6 +154,7 @@ impl<CustErr> ClientReq<CustErr> for BrowserRequest {
                 .build()
                 .map_err(|e| ServerFnError::Request(e.to_string()))?,
             abort_ctrl,
+            buffered_body: None,
         })))
     }
 
@@ -131,6 +177,7 @@ impl<CustErr> ClientReq<CustErr> for BrowserRequest {
                 .body(body)
                 .map_err(|e| ServerFnError::Request(e.to_string()))?,
             abort_ctrl,
+            buffered_body: None,
         })))
     }
 
@@ -155,6 +202,7 @@ impl<CustErr> ClientReq<CustErr> for BrowserRequest {
                 .body(body)
                 .map_err(|e| ServerFnError::Request(e.to_string()))?,
             abort_ctrl,
+            buffered_body: None,
         })))
     }
 
@@ -175,6 +223,7 @@ impl<CustErr> ClientReq<CustErr> for BrowserRequest {
                 .body(body.0.take())
                 .map_err(|e| ServerFnError::Request(e.to_string()))?,
             abort_ctrl,
+            buffered_body: None,
         })))
     }
 
@@ -204,6 +253,7 @@ impl<CustErr> ClientReq<CustErr> for BrowserRequest {
                 .body(url_params)
                 .map_err(|e| ServerFnError::Request(e.to_string()))?,
             abort_ctrl,
+            buffered_body: None,
         })))
     }
 
@@ -211,30 +261,78 @@ impl<CustErr> ClientReq<CustErr> for BrowserRequest {
         path: &str,
         accepts: &str,
         content_type: &str,
-        body: impl Stream<Item = Bytes> + 'static,
+        body: impl Stream<Item = Result<Bytes, ServerFnError>> + 'static,
     ) -> Result<Self, ServerFnError<CustErr>> {
-        // TODO abort signal
-        let (request, abort_ctrl) =
-            streaming_request(path, accepts, content_type, body)
-                .map_err(|e| ServerFnError::Request(format!("{e:?}")))?;
+        let mut url = get_server_url().to_string();
+        url.push_str(path);
+        let (request, abort_ctrl, buffered_body) = if supports_request_streams()
+        {
+            let (request, abort_ctrl) =
+                streaming_request(&url, accepts, content_type, body)
+                    .map_err(|e| ServerFnError::Request(format!("{e:?}")))?;
+            (request, abort_ctrl, None)
+        } else {
+            let (abort_ctrl, abort_signal) = abort_signal();
+            let request = Request::post(&url)
+                .header("Content-Type", content_type)
+                .header("Accept", accepts)
+                .abort_signal(abort_signal.as_ref())
+                .build()
+                .map_err(|e| ServerFnError::Request(e.to_string()))?;
+            (request, abort_ctrl, Some(BufferedBody(Box::pin(body))))
+        };
         Ok(Self(SendWrapper::new(RequestInner {
             request,
             abort_ctrl,
+            buffered_body,
+        })))
+    }
+
+    fn request_url(&self) -> String {
+        self.url()
+    }
+
+    fn insert_header(&mut self, name: &str, value: &str) {
+        // `Headers.set()` throws if the name or value is invalid
+        if HeaderName::from_str(name).is_ok()
+            && HeaderValue::from_str(value).is_ok()
+        {
+            self.headers().set(name, value);
+        }
+    }
+
+    fn try_clone(&self) -> Option<Self> {
+        // the body of a `fetch` request can only be read once, so only requests without a
+        // body can be rebuilt
+        if self.method() != Method::GET {
+            return None;
+        }
+        let (abort_ctrl, abort_signal) = abort_signal();
+        let request = Request::get(&self.url())
+            .headers(self.headers())
+            .abort_signal(abort_signal.as_ref())
+            .build()
+            .ok()?;
+        Some(Self(SendWrapper::new(RequestInner {
+            request,
+            abort_ctrl,
+            buffered_body: None,
         })))
     }
 }
 
 fn streaming_request(
-    path: &str,
+    url: &str,
     accepts: &str,
     content_type: &str,
-    body: impl Stream<Item = Bytes> + 'static,
+    body: impl Stream<Item = Result<Bytes, ServerFnError>> + 'static,
 ) -> Result<(Request, Option<AbortOnDrop>), JsValue> {
     let (abort_ctrl, abort_signal) = abort_signal();
-    let stream = ReadableStream::from_stream(body.map(|bytes| {
-        let data = Uint8Array::from(bytes.as_ref());
-        let data = JsValue::from(data);
-        Ok(data) as Result<JsValue, JsValue>
+    // an error errors the stream, which makes the browser abort the request
+    let stream = ReadableStream::from_stream(body.map(|chunk| {
+        chunk
+            .map(|bytes| JsValue::from(Uint8Array::from(bytes.as_ref())))
+            .map_err(|e| JsValue::from_str(&e.to_string()))
     }))
     .into_raw();
 
@@ -254,6 +352,45 @@ fn streaming_request(
         &JsValue::from_str("duplex"),
         &JsValue::from_str("half"),
     )?;
-    let req = web_sys::Request::new_with_str_and_init(path, &init)?;
+    let req = web_sys::Request::new_with_str_and_init(url, &init)?;
     Ok((Request::from(req), abort_ctrl))
 }
+
+/// Whether this browser can send a `ReadableStream` as the body of a request.
+///
+/// Browsers that support it read the `duplex` option when a request is created, while
+/// others ignore it and turn the stream into the string `"[object ReadableStream]"`, which
+/// gives the request a `Content-Type` of `text/plain`.
+fn supports_request_streams() -> bool {
+    thread_local! {
+        static SUPPORTED: bool = detect_request_streams().unwrap_or(false);
+    }
+    SUPPORTED.with(|supported| *supported)
+}
+
+fn detect_request_streams() -> Result<bool, JsValue> {
+    let duplex_accessed = Rc::new(Cell::new(false));
+    let getter = Closure::<dyn FnMut() -> JsValue>::new({
+        let duplex_accessed = Rc::clone(&duplex_accessed);
+        move || {
+            duplex_accessed.set(true);
+            JsValue::from_str("half")
+        }
+    });
+    let descriptor = Object::new();
+    Reflect::set(&descriptor, &JsValue::from_str("get"), getter.as_ref())?;
+
+    let init = RequestInit::new();
+    init.set_method("POST");
+    init.set_body(
+        &ReadableStream::from_stream(
+            stream::empty::<Result<JsValue, JsValue>>(),
+        )
+        .into_raw(),
+    );
+    Object::define_property(&init, &JsValue::from_str("duplex"), &descriptor);
+
+    let req = web_sys::Request::new_with_str_and_init("", &init)?;
+    let has_content_type = req.headers().has("Content-Type")?;
+    Ok(duplex_accessed.get() && !has_content_type)
+}
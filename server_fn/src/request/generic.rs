@@ -12,29 +12,54 @@
 //! * `wasm32-wasip*` integration crate `leptos_wasi` is using this
 //!   crate under the hood.
 
-use crate::request::Req;
+use crate::request::{get_body_limits, too_large, BodyLimits, Req};
 use bytes::Bytes;
-use futures::{
-    stream::{self, Stream},
-    StreamExt,
-};
+use futures::stream::{self, Stream};
 use http::Request;
 use std::borrow::Cow;
 
+fn limits_of(req: &Request<Bytes>) -> BodyLimits {
+    req.extensions()
+        .get::<BodyLimits>()
+        .copied()
+        .unwrap_or_else(get_body_limits)
+}
+
+// the body has already been read into memory, but there's no reason to go on
+// decoding it if it's larger than the server function allows
+fn checked_body<CustErr>(
+    req: Request<Bytes>,
+) -> Result<Bytes, crate::ServerFnError<CustErr>> {
+    let max = limits_of(&req).max_body_size();
+    let body = req.into_body();
+    match max {
+        Some(max) if body.len() > max => Err(too_large(max)),
+        _ => Ok(body),
+    }
+}
+
 impl<CustErr> Req<CustErr> for Request<Bytes>
 where
     CustErr: 'static,
 {
+    fn body_limits(&self) -> BodyLimits {
+        limits_of(self)
+    }
+
+    fn set_body_limits(&mut self, limits: BodyLimits) {
+        self.extensions_mut().insert(limits);
+    }
+
     async fn try_into_bytes(
         self,
     ) -> Result<Bytes, crate::ServerFnError<CustErr>> {
-        Ok(self.into_body())
+        checked_body(self)
     }
 
     async fn try_into_string(
         self,
     ) -> Result<String, crate::ServerFnError<CustErr>> {
-        String::from_utf8(self.into_body().into()).map_err(|err| {
+        String::from_utf8(checked_body(self)?.into()).map_err(|err| {
             crate::ServerFnError::Deserialization(err.to_string())
         })
     }
@@ -45,9 +70,9 @@ where
         impl Stream<Item = Result<Bytes, crate::ServerFnError>> + Send + 'static,
         crate::ServerFnError<CustErr>,
     > {
-        Ok(stream::iter(self.into_body())
-            .ready_chunks(16)
-            .map(|chunk| Ok(Bytes::from(chunk))))
+        // the body has already been read into memory, so it is sent on as a single chunk
+        let body = self.into_body();
+        Ok(stream::iter((!body.is_empty()).then_some(Ok(body))))
     }
 
     fn to_content_type(&self) -> Option<Cow<'_, str>> {
@@ -68,6 +93,12 @@ where
             .map(|val| String::from_utf8_lossy(val.as_bytes()))
     }
 
+    fn if_none_match(&self) -> Option<Cow<'_, str>> {
+        self.headers()
+            .get(http::header::IF_NONE_MATCH)
+            .map(|val| String::from_utf8_lossy(val.as_bytes()))
+    }
+
     fn as_query(&self) -> Option<&str> {
         self.uri().query()
     }
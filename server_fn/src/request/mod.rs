@@ -1,7 +1,11 @@
+#[cfg(feature = "websocket")]
+use crate::codec::ByteStream;
 use crate::error::ServerFnError;
 use bytes::Bytes;
+#[cfg(feature = "websocket")]
+use futures::channel::oneshot;
 use futures::Stream;
-use std::{borrow::Cow, future::Future};
+use std::{borrow::Cow, future::Future, sync::OnceLock};
 
 /// Request types for Actix.
 #[cfg(feature = "actix")]
@@ -66,12 +70,176 @@ where
     ) -> Result<Self, ServerFnError<CustErr>>;
 
     /// Attempts to construct a new `POST` request with a streaming body.
+    ///
+    /// If the stream yields an error, the request is aborted rather than ending the body, so
+    /// that the server does not mistake the part sent so far for the whole body.
     fn try_new_streaming(
         path: &str,
         accepts: &str,
         content_type: &str,
-        body: impl Stream<Item = Bytes> + Send + 'static,
+        body: impl Stream<Item = Result<Bytes, ServerFnError>> + Send + 'static,
     ) -> Result<Self, ServerFnError<CustErr>>;
+
+    /// The full URL the request will be sent to, including its query string.
+    ///
+    /// This is used to look up cached responses. By default, it returns an empty string, so
+    /// that no response is ever reused.
+    fn request_url(&self) -> String {
+        String::new()
+    }
+
+    /// Sets a header on the request, replacing any existing value.
+    ///
+    /// Invalid header names or values are ignored. By default, this does nothing.
+    fn insert_header(&mut self, name: &str, value: &str) {
+        _ = (name, value);
+    }
+
+    /// Attempts to copy the request, so that it can be sent again.
+    ///
+    /// Returns `None` if the request cannot be copied, for example because its body is a stream.
+    /// By default, requests are never copied, so they are never retried.
+    fn try_clone(&self) -> Option<Self> {
+        None
+    }
+}
+
+/// The outgoing half of a websocket connection, as seen by the server.
+///
+/// The stream of messages to send to the client is passed through this once the server
+/// function has returned it, and is forwarded to the socket for as long as the connection
+/// remains open.
+#[cfg(feature = "websocket")]
+pub type WebsocketSender = oneshot::Sender<ByteStream>;
+
+static BODY_LIMITS: OnceLock<BodyLimits> = OnceLock::new();
+
+/// Set the default [`BodyLimits`] for requests to server functions.
+///
+/// Server functions that set their own limits with `#[server(body_limits = ...)]` ignore this.
+/// If this is not set, request bodies are not limited.
+pub fn set_body_limits(limits: BodyLimits) {
+    BODY_LIMITS.set(limits).unwrap();
+}
+
+/// Returns the default [`BodyLimits`] for requests to server functions.
+pub fn get_body_limits() -> BodyLimits {
+    BODY_LIMITS.get().copied().unwrap_or_default()
+}
+
+/// Limits on the size of the request body a server function will accept.
+///
+/// A request whose body is larger than the limit fails with
+/// [`ServerFnError::PayloadTooLarge`], which is returned to the client with a
+/// `413 Payload Too Large` status.
+///
+/// The `max_body_size` applies to encodings that read the whole body into memory
+/// before decoding it, like `Json`, `Cbor` or `Rkyv`. Streaming encodings hand the body
+/// to the server function as it arrives, and are not limited by it. Multipart bodies are
+/// limited separately, as the limits are only enforced while the server function reads
+/// the fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BodyLimits {
+    max_body_size: Option<usize>,
+    max_multipart_size: Option<usize>,
+    max_fields: Option<usize>,
+    max_field_size: Option<usize>,
+}
+
+impl Default for BodyLimits {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BodyLimits {
+    /// Creates the default limits, which accept a request body of any size.
+    ///
+    /// This is the same as [`BodyLimits::unlimited`].
+    pub const fn new() -> Self {
+        Self::unlimited()
+    }
+
+    /// Creates limits that accept a request body of any size.
+    pub const fn unlimited() -> Self {
+        Self {
+            max_body_size: None,
+            max_multipart_size: None,
+            max_fields: None,
+            max_field_size: None,
+        }
+    }
+
+    /// Limits a body that is read into memory to `max` bytes.
+    pub fn with_max_body_size(mut self, max: usize) -> Self {
+        self.max_body_size = Some(max);
+        self
+    }
+
+    /// Limits a whole multipart body to `max` bytes.
+    pub fn with_max_multipart_size(mut self, max: usize) -> Self {
+        self.max_multipart_size = Some(max);
+        self
+    }
+
+    /// Limits a multipart body to `max` fields, counting each file as a field.
+    pub fn with_max_fields(mut self, max: usize) -> Self {
+        self.max_fields = Some(max);
+        self
+    }
+
+    /// Limits each field of a multipart body, including files, to `max` bytes.
+    pub fn with_max_field_size(mut self, max: usize) -> Self {
+        self.max_field_size = Some(max);
+        self
+    }
+
+    /// The maximum size of a body that is read into memory, if any.
+    pub fn max_body_size(&self) -> Option<usize> {
+        self.max_body_size
+    }
+
+    /// The maximum size of a whole multipart body, if any.
+    pub fn max_multipart_size(&self) -> Option<usize> {
+        self.max_multipart_size
+    }
+
+    /// The maximum number of fields in a multipart body, if any.
+    pub fn max_fields(&self) -> Option<usize> {
+        self.max_fields
+    }
+
+    /// The maximum size of each field in a multipart body, if any.
+    pub fn max_field_size(&self) -> Option<usize> {
+        self.max_field_size
+    }
+
+    /// Fails if the body is known to be larger than `max_body_size`, without reading it.
+    #[cfg(any(feature = "actix", feature = "axum-no-default"))]
+    pub(crate) fn check_content_length<CustErr>(
+        &self,
+        content_length: Option<&[u8]>,
+    ) -> Result<(), ServerFnError<CustErr>> {
+        let len = content_length
+            .and_then(|len| std::str::from_utf8(len).ok())
+            .and_then(|len| len.trim().parse::<usize>().ok());
+        match (self.max_body_size, len) {
+            (Some(max), Some(len)) if len > max => Err(too_large(max)),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(any(
+    feature = "actix",
+    feature = "axum-no-default",
+    feature = "generic",
+    feature = "multipart"
+))]
+pub(crate) fn too_large<CustErr>(max: usize) -> ServerFnError<CustErr> {
+    ServerFnError::PayloadTooLarge(format!(
+        "the request body is larger than the limit of {max} bytes"
+    ))
 }
 
 /// Represents the request as received by the server.
@@ -91,6 +259,26 @@ where
     /// Returns the `Referer` header, if any.
     fn referer(&self) -> Option<Cow<'_, str>>;
 
+    /// Returns the `If-None-Match` header, if any.
+    fn if_none_match(&self) -> Option<Cow<'_, str>> {
+        None
+    }
+
+    /// Returns the limits on the size of the request body.
+    ///
+    /// By default, these are the global limits set with [`set_body_limits`].
+    fn body_limits(&self) -> BodyLimits {
+        get_body_limits()
+    }
+
+    /// Sets the limits on the size of the request body, which are enforced when
+    /// the body is read.
+    ///
+    /// Request types that do not support limits ignore this.
+    fn set_body_limits(&mut self, limits: BodyLimits) {
+        _ = limits;
+    }
+
     /// Attempts to extract the body of the request into [`Bytes`].
     fn try_into_bytes(
         self,
@@ -110,6 +298,27 @@ where
     >;
 }
 
+/// A request that can be upgraded to a websocket connection, as needed by server functions
+/// that use the [`Websocket`](crate::codec::Websocket) protocol.
+#[cfg(feature = "websocket")]
+pub trait WebsocketReq<CustErr>: Req<CustErr> {
+    /// The type of the response that completes a websocket upgrade.
+    type WebsocketResponse: Send;
+
+    /// Attempts to upgrade the request to a websocket connection.
+    ///
+    /// Returns the stream of messages received from the client, a [`WebsocketSender`]
+    /// for the messages that should be sent back, and the response that completes the upgrade.
+    fn try_into_websocket(
+        self,
+    ) -> impl Future<
+        Output = Result<
+            (ByteStream, WebsocketSender, Self::WebsocketResponse),
+            ServerFnError<CustErr>,
+        >,
+    > + Send;
+}
+
 /// A mocked request type that can be used in place of the actual server request,
 /// when compiling for the browser.
 pub struct BrowserMockReq;
@@ -150,3 +359,20 @@ where
         Ok(futures::stream::once(async { unreachable!() }))
     }
 }
+
+#[cfg(feature = "websocket")]
+impl<CustErr> WebsocketReq<CustErr> for BrowserMockReq
+where
+    CustErr: 'static,
+{
+    type WebsocketResponse = crate::response::BrowserMockRes;
+
+    async fn try_into_websocket(
+        self,
+    ) -> Result<
+        (ByteStream, WebsocketSender, Self::WebsocketResponse),
+        ServerFnError<CustErr>,
+    > {
+        unreachable!()
+    }
+}
@@ -1,10 +1,19 @@
 use super::ClientReq;
 use crate::{client::get_server_url, error::ServerFnError};
 use bytes::Bytes;
-use futures::Stream;
+use futures::{Stream, StreamExt};
 use once_cell::sync::Lazy;
-use reqwest::header::{ACCEPT, CONTENT_TYPE};
+use reqwest::{
+    header::{HeaderName, HeaderValue, ACCEPT, CONTENT_TYPE},
+    Body,
+};
 pub use reqwest::{multipart::Form, Client, Method, Request, Url};
+use std::{
+    pin::Pin,
+    str::FromStr,
+    sync::{Mutex, PoisonError},
+    task::{Context, Poll},
+};
 
 pub(crate) static CLIENT: Lazy<Client> = Lazy::new(Client::new);
 
@@ -91,28 +100,68 @@ impl<CustErr> ClientReq<CustErr> for Request {
     }
 
     fn try_new_streaming(
-        _path: &str,
-        _accepts: &str,
-        _content_type: &str,
-        _body: impl Stream<Item = Bytes> + 'static,
+        path: &str,
+        accepts: &str,
+        content_type: &str,
+        body: impl Stream<Item = Result<Bytes, ServerFnError>> + Send + 'static,
     ) -> Result<Self, ServerFnError<CustErr>> {
-        todo!("Streaming requests are not yet implemented for reqwest.")
-        // We run into a fundamental issue here.
-        // To be a reqwest body, the type must be Sync
-        // That means the streaming types need to be wrappers over Sync streams
-        // However, Axum BodyDataStream is !Sync, so we can't use the same wrapper type there
+        let url = format!("{}{}", get_server_url(), path);
+        // an error aborts the request
+        let body = Body::wrap_stream(SyncStream::new(body).map(|chunk| {
+            chunk.map_err(|e| std::io::Error::other(e.to_string()))
+        }));
+        CLIENT
+            .post(url)
+            .header(CONTENT_TYPE, content_type)
+            .header(ACCEPT, accepts)
+            .body(body)
+            .build()
+            .map_err(|e| ServerFnError::Request(e.to_string()))
+    }
+
+    fn request_url(&self) -> String {
+        Request::url(self).to_string()
+    }
+
+    fn insert_header(&mut self, name: &str, value: &str) {
+        if let (Ok(name), Ok(value)) =
+            (HeaderName::from_str(name), HeaderValue::from_str(value))
+        {
+            self.headers_mut().insert(name, value);
+        }
+    }
+
+    fn try_clone(&self) -> Option<Self> {
+        Request::try_clone(self)
+    }
+}
+
+/// Makes a `Send` stream `Sync`, as `reqwest` requires for a streaming body.
+///
+/// The stream is only ever polled through `&mut self`, so the lock is never actually taken.
+struct SyncStream<S>(Mutex<Pin<Box<S>>>);
+
+impl<S> SyncStream<S> {
+    fn new(stream: S) -> Self {
+        Self(Mutex::new(Box::pin(stream)))
+    }
+}
+
+impl<S> Stream for SyncStream<S>
+where
+    S: Stream,
+{
+    type Item = S::Item;
 
-        /*        let url = format!("{}{}", get_server_url(), path);
-            let body = Body::wrap_stream(
-                body.map(|chunk| Ok(chunk) as Result<Bytes, ServerFnErrorErr>),
-            );
-            CLIENT
-                .post(url)
-                .header(CONTENT_TYPE, content_type)
-                .header(ACCEPT, accepts)
-                .body(body)
-                .build()
-                .map_err(|e| ServerFnError::Request(e.to_string()))
-        }*/
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        self.get_mut()
+            .0
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner)
+            .as_mut()
+            .poll_next(cx)
     }
 }
@@ -1,11 +1,13 @@
 use super::Res;
 use crate::error::{
-    ServerFnError, ServerFnErrorErr, ServerFnErrorSerde, SERVER_FN_ERROR_HEADER,
+    ServerFnError, ServerFnErrorErr, ServerFnErrorSerde, ServerFnErrorStatus,
+    SERVER_FN_ERROR_HEADER,
 };
 use actix_web::{
+    body::{to_bytes, BoxBody},
     http::{
         header,
-        header::{HeaderValue, LOCATION},
+        header::{HeaderName, HeaderValue, LOCATION},
         StatusCode,
     },
     HttpResponse,
@@ -15,6 +17,7 @@ use futures::{Stream, StreamExt};
 use send_wrapper::SendWrapper;
 use std::{
     fmt::{Debug, Display},
+    future::Future,
     str::FromStr,
 };
 
@@ -81,10 +84,22 @@ where
     }
 
     fn error_response(path: &str, err: &ServerFnError<CustErr>) -> Self {
+        let mut builder = HttpResponse::build(
+            StatusCode::from_u16(err.status_code().as_u16())
+                .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+        );
+        builder.append_header((SERVER_FN_ERROR_HEADER, path));
+        // Actix uses its own header types, so these are converted one by one
+        for (name, value) in err.headers().iter() {
+            if let (Ok(name), Ok(value)) = (
+                HeaderName::from_bytes(name.as_str().as_bytes()),
+                HeaderValue::from_bytes(value.as_bytes()),
+            ) {
+                builder.append_header((name, value));
+            }
+        }
         ActixResponse(SendWrapper::new(
-            HttpResponse::build(StatusCode::INTERNAL_SERVER_ERROR)
-                .append_header((SERVER_FN_ERROR_HEADER, path))
-                .body(err.ser().unwrap_or_else(|_| err.to_string())),
+            builder.body(err.ser().unwrap_or_else(|_| err.to_string())),
         ))
     }
 
@@ -94,4 +109,35 @@ where
             self.0.headers_mut().insert(LOCATION, path);
         }
     }
+
+    fn insert_header(&mut self, name: &str, value: &str) {
+        if let (Ok(name), Ok(value)) =
+            (HeaderName::from_str(name), HeaderValue::from_str(value))
+        {
+            self.0.headers_mut().insert(name, value);
+        }
+    }
+
+    fn try_into_buffered(
+        self,
+    ) -> impl Future<
+        Output = Result<(Self, Option<Bytes>), ServerFnError<CustErr>>,
+    > + Send {
+        // Actix is going to keep this on a single thread anyway so it's fine to wrap it
+        // with SendWrapper, which makes it `Send` but will panic if it moves to another thread
+        SendWrapper::new(async move {
+            let (res, body) = self.0.take().into_parts();
+            let body = to_bytes(body)
+                .await
+                .map_err(|e| ServerFnError::Response(e.to_string()))?;
+            let res = res.set_body(BoxBody::new(body.clone()));
+            Ok((ActixResponse(SendWrapper::new(res)), Some(body)))
+        })
+    }
+
+    fn into_not_modified(self) -> Self {
+        let mut res = self.0.take();
+        *res.status_mut() = StatusCode::NOT_MODIFIED;
+        ActixResponse(SendWrapper::new(res.set_body(BoxBody::new(()))))
+    }
 }
@@ -85,4 +85,8 @@ impl<CustErr> ClientRes<CustErr> for BrowserResponse {
     fn has_redirect(&self) -> bool {
         self.0.headers().get(REDIRECT_HEADER).is_some()
     }
+
+    fn header(&self, name: &str) -> Option<String> {
+        self.0.headers().get(name)
+    }
 }
@@ -1,11 +1,13 @@
 use super::Res;
 use crate::error::{
-    ServerFnError, ServerFnErrorErr, ServerFnErrorSerde, SERVER_FN_ERROR_HEADER,
+    ServerFnError, ServerFnErrorErr, ServerFnErrorSerde, ServerFnErrorStatus,
+    SERVER_FN_ERROR_HEADER,
 };
 use axum::body::Body;
 use bytes::Bytes;
 use futures::{Stream, StreamExt};
-use http::{header, HeaderValue, Response, StatusCode};
+use http::{header, HeaderName, HeaderValue, Response, StatusCode};
+use http_body_util::BodyExt;
 use std::{
     fmt::{Debug, Display},
     str::FromStr,
@@ -56,11 +58,13 @@ where
     }
 
     fn error_response(path: &str, err: &ServerFnError<CustErr>) -> Self {
-        Response::builder()
-            .status(http::StatusCode::INTERNAL_SERVER_ERROR)
+        let mut res = Response::builder()
+            .status(err.status_code())
             .header(SERVER_FN_ERROR_HEADER, path)
             .body(err.ser().unwrap_or_else(|_| err.to_string()).into())
-            .unwrap()
+            .unwrap();
+        res.headers_mut().extend(err.headers());
+        res
     }
 
     fn redirect(&mut self, path: &str) {
@@ -69,4 +73,33 @@ where
             *self.status_mut() = StatusCode::FOUND;
         }
     }
+
+    fn insert_header(&mut self, name: &str, value: &str) {
+        if let (Ok(name), Ok(value)) =
+            (HeaderName::from_str(name), HeaderValue::from_str(value))
+        {
+            self.headers_mut().insert(name, value);
+        }
+    }
+
+    async fn try_into_buffered(
+        self,
+    ) -> Result<(Self, Option<Bytes>), ServerFnError<CustErr>> {
+        let (parts, body) = self.into_parts();
+        let body = body
+            .collect()
+            .await
+            .map(|c| c.to_bytes())
+            .map_err(|e| ServerFnError::Response(e.to_string()))?;
+        Ok((
+            Response::from_parts(parts, Body::from(body.clone())),
+            Some(body),
+        ))
+    }
+
+    fn into_not_modified(mut self) -> Self {
+        *self.status_mut() = StatusCode::NOT_MODIFIED;
+        *self.body_mut() = Body::empty();
+        self
+    }
 }
@@ -43,11 +43,43 @@ where
             + 'static,
     ) -> Result<Self, ServerFnError<CustErr>>;
 
-    /// Converts an error into a response, with a `500` status code and the error text as its body.
+    /// Converts an error into a response, with the status code and headers chosen by its
+    /// [`ServerFnErrorStatus`](crate::error::ServerFnErrorStatus) implementation and the error
+    /// text as its body.
     fn error_response(path: &str, err: &ServerFnError<CustErr>) -> Self;
 
     /// Redirect the response by setting a 302 code and Location header.
     fn redirect(&mut self, path: &str);
+
+    /// Sets a header on the response, replacing any existing value.
+    ///
+    /// Invalid header names or values are ignored. By default, this does nothing.
+    fn insert_header(&mut self, name: &str, value: &str) {
+        _ = (name, value);
+    }
+
+    /// Reads the whole body of the response into memory, returning it along with the
+    /// response, whose body is replaced with the same bytes.
+    ///
+    /// By default, the body is not read, and `None` is returned in its place. This means that
+    /// no `ETag` is sent with the response.
+    fn try_into_buffered(
+        self,
+    ) -> impl Future<
+        Output = Result<(Self, Option<Bytes>), ServerFnError<CustErr>>,
+    > + Send
+    where
+        Self: Send,
+    {
+        async move { Ok((self, None)) }
+    }
+
+    /// Replaces the response with an empty `304 Not Modified` response, keeping its headers.
+    ///
+    /// By default, the response is returned unchanged.
+    fn into_not_modified(self) -> Self {
+        self
+    }
 }
 
 /// Represents the response as received by the client.
@@ -81,6 +113,14 @@ pub trait ClientRes<CustErr> {
 
     /// Whether the response has the [`REDIRECT_HEADER`](crate::redirect::REDIRECT_HEADER) set.
     fn has_redirect(&self) -> bool;
+
+    /// The value of the given header, if it is set.
+    ///
+    /// By default, this returns `None`.
+    fn header(&self, name: &str) -> Option<String> {
+        _ = name;
+        None
+    }
 }
 
 /// A mocked response type that can be used in place of the actual server response,
@@ -120,4 +160,18 @@ impl<CustErr> Res<CustErr> for BrowserMockRes {
     fn redirect(&mut self, _path: &str) {
         unreachable!()
     }
+
+    fn insert_header(&mut self, _name: &str, _value: &str) {
+        unreachable!()
+    }
+
+    async fn try_into_buffered(
+        self,
+    ) -> Result<(Self, Option<Bytes>), ServerFnError<CustErr>> {
+        unreachable!()
+    }
+
+    fn into_not_modified(self) -> Self {
+        unreachable!()
+    }
 }
@@ -46,4 +46,10 @@ impl<CustErr> ClientRes<CustErr> for Response {
     fn has_redirect(&self) -> bool {
         self.headers().get("Location").is_some()
     }
+
+    fn header(&self, name: &str) -> Option<String> {
+        self.headers()
+            .get(name)
+            .map(|value| String::from_utf8_lossy(value.as_bytes()).to_string())
+    }
 }
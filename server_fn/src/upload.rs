@@ -0,0 +1,420 @@
+//! Uploads of large files that report their progress and can be resumed.
+//!
+//! [`upload`] splits a file into chunks and sends them one at a time to a server function that
+//! uses the [`Chunked`] encoding, which appends each chunk to a partial file with
+//! [`save_chunk`]. Before sending anything, it asks the server how much of the file it already
+//! has, so an upload that was interrupted (by a dropped connection, a reload, or a
+//! cancellation) continues where it stopped rather than starting over.
+//!
+//! ```rust,ignore
+//! #[server(input = Chunked, body_limits = BodyLimits::new().with_max_body_size(9 * 1024 * 1024))]
+//! pub async fn upload_video(
+//!     chunk: UploadChunk,
+//! ) -> Result<UploadStatus, ServerFnError> {
+//!     let status = upload::save_chunk(&chunk, "uploads/partial").await?;
+//!     if status.is_complete() {
+//!         let path = upload::partial_path("uploads/partial", chunk.id())?;
+//!         tokio::fs::rename(path, format!("videos/{}", chunk.id())).await?;
+//!     }
+//!     Ok(status)
+//! }
+//!
+//! // in the browser
+//! let options = UploadOptions::new().with_chunk_size(8 * 1024 * 1024);
+//! let (call, handle) = abortable(upload::upload::<UploadVideo>(
+//!     &file,
+//!     options,
+//!     |status| set_progress.set(status),
+//! ));
+//! ```
+//!
+//! The upload can be cancelled with the [`AbortHandle`](crate::client::AbortHandle) returned by
+//! [`abortable`](crate::client::abortable), or by dropping it, which aborts the chunk in flight.
+
+pub use crate::codec::{Chunked, UploadChunk};
+use crate::{client::Client, error::ServerFnError, ServerFn};
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use xxhash_rust::const_xxh64::xxh64;
+
+/// How much of a file the server has received.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize,
+)]
+pub struct UploadStatus {
+    received: u64,
+    total: u64,
+}
+
+impl UploadStatus {
+    /// Creates a status for a file of `total` bytes, of which `received` have been received.
+    pub fn new(received: u64, total: u64) -> Self {
+        Self { received, total }
+    }
+
+    /// The number of bytes received so far.
+    pub fn received(&self) -> u64 {
+        self.received
+    }
+
+    /// The size of the whole file.
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    /// Whether the whole file has been received.
+    pub fn is_complete(&self) -> bool {
+        self.received >= self.total
+    }
+}
+
+/// A file that can be uploaded in chunks.
+pub trait UploadSource {
+    /// The size of the file in bytes.
+    fn size(&self) -> u64;
+
+    /// The name of the file, which is sent to the server with each chunk.
+    fn name(&self) -> String {
+        String::new()
+    }
+
+    /// An ID that stays the same for the same file, so that uploading it again resumes any
+    /// earlier upload of it.
+    fn id(&self) -> String;
+
+    /// Reads the bytes from `start` up to `end`.
+    fn read<CustErr>(
+        &self,
+        start: u64,
+        end: u64,
+    ) -> impl Future<Output = Result<Bytes, ServerFnError<CustErr>>>;
+}
+
+impl UploadSource for Bytes {
+    fn size(&self) -> u64 {
+        self.len() as u64
+    }
+
+    fn id(&self) -> String {
+        format!("{:016x}", xxh64(self, 0))
+    }
+
+    async fn read<CustErr>(
+        &self,
+        start: u64,
+        end: u64,
+    ) -> Result<Bytes, ServerFnError<CustErr>> {
+        Ok(self.slice(start as usize..end as usize))
+    }
+}
+
+#[cfg(feature = "browser")]
+impl UploadSource for web_sys::File {
+    fn size(&self) -> u64 {
+        web_sys::Blob::size(self) as u64
+    }
+
+    fn name(&self) -> String {
+        web_sys::File::name(self)
+    }
+
+    fn id(&self) -> String {
+        let key = format!(
+            "{}:{}:{}",
+            self.name(),
+            UploadSource::size(self),
+            self.last_modified()
+        );
+        format!("{:016x}", xxh64(key.as_bytes(), 0))
+    }
+
+    async fn read<CustErr>(
+        &self,
+        start: u64,
+        end: u64,
+    ) -> Result<Bytes, ServerFnError<CustErr>> {
+        let read_err = |e: wasm_bindgen::JsValue| {
+            ServerFnError::Request(format!("could not read the file: {e:?}"))
+        };
+        let blob = self
+            .slice_with_f64_and_f64(start as f64, end as f64)
+            .map_err(read_err)?;
+        let buffer = wasm_bindgen_futures::JsFuture::from(blob.array_buffer())
+            .await
+            .map_err(read_err)?;
+        Ok(js_sys::Uint8Array::new(&buffer).to_vec().into())
+    }
+}
+
+/// Options for an [`upload`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UploadOptions {
+    chunk_size: u64,
+    max_retries: u32,
+}
+
+impl Default for UploadOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UploadOptions {
+    /// Creates the default options, which send chunks of 1 MiB and retry each chunk up to 3
+    /// times.
+    pub const fn new() -> Self {
+        Self {
+            chunk_size: 1024 * 1024,
+            max_retries: 3,
+        }
+    }
+
+    /// Sends the file in chunks of `chunk_size` bytes.
+    ///
+    /// Each chunk is read into memory on the server, so this must not be larger than the
+    /// `max_body_size` of the server function's [`BodyLimits`](crate::request::BodyLimits).
+    pub fn with_chunk_size(mut self, chunk_size: u64) -> Self {
+        self.chunk_size = chunk_size.max(1);
+        self
+    }
+
+    /// Retries a chunk that could not reach the server up to `max_retries` times, waiting
+    /// according to the server function's [`CallPolicy`](crate::client::CallPolicy) between
+    /// attempts.
+    pub fn with_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// The size of each chunk in bytes.
+    pub fn chunk_size(&self) -> u64 {
+        self.chunk_size
+    }
+
+    /// The maximum number of times each chunk is retried.
+    pub fn max_retries(&self) -> u32 {
+        self.max_retries
+    }
+}
+
+/// Uploads `source` to the server function `T` in chunks, calling `on_progress` whenever the
+/// server confirms that it has received more of the file.
+///
+/// Progress is reported once per chunk, so smaller chunks give finer-grained progress at the
+/// cost of more requests.
+pub async fn upload<T>(
+    source: &impl UploadSource,
+    options: UploadOptions,
+    mut on_progress: impl FnMut(UploadStatus),
+) -> Result<UploadStatus, ServerFnError<T::Error>>
+where
+    T: ServerFn<Output = UploadStatus> + From<UploadChunk>,
+    T::Client: 'static,
+    T::Error: Send + 'static,
+{
+    let id = source.id();
+    let name = source.name();
+    let total = source.size();
+
+    // an empty chunk doesn't change anything, but tells us how much the server already has
+    let mut status =
+        T::from(UploadChunk::new(&id, &name, 0, total, Bytes::new()))
+            .run_on_client()
+            .await?;
+    on_progress(status);
+
+    let mut retry = 0;
+    while !status.is_complete() {
+        let start = status.received();
+        let end = start.saturating_add(options.chunk_size).min(total);
+        let data = source.read::<T::Error>(start, end).await?;
+        let chunk = UploadChunk::new(&id, &name, start, total, data);
+        match T::from(chunk).run_on_client().await {
+            Ok(next) if next.received() <= start => {
+                return Err(ServerFnError::Response(format!(
+                    "the server did not save the chunk at {start} of upload \
+                     {id}"
+                )));
+            }
+            Ok(next) => {
+                status = next;
+                retry = 0;
+                on_progress(status);
+            }
+            // if the chunk was saved but the response was lost, sending it again just
+            // returns the current status
+            Err(ServerFnError::Request(_) | ServerFnError::Timeout(_))
+                if retry < options.max_retries =>
+            {
+                if let Some(delay) = <T::Client as Client<T::Error>>::sleep(
+                    T::call_policy().backoff(retry),
+                ) {
+                    delay.await;
+                }
+                retry += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(status)
+}
+
+/// Returns the path of the partial file for the upload `id` in `dir`.
+///
+/// Fails if the ID could refer to a file outside of `dir`.
+#[cfg(feature = "ssr")]
+pub fn partial_path(
+    dir: impl AsRef<std::path::Path>,
+    id: &str,
+) -> Result<std::path::PathBuf, ServerFnError> {
+    let valid = !id.is_empty()
+        && id.len() <= 128
+        && id
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_');
+    if !valid {
+        return Err(ServerFnError::Args(format!("invalid upload ID {id:?}")));
+    }
+    Ok(dir.as_ref().join(format!("{id}.part")))
+}
+
+/// Appends a chunk to the partial file for its upload in `dir`, if it is the next chunk the
+/// file needs, and returns how much of the file has been received.
+///
+/// Chunks that have already been saved, or that skip ahead, are ignored, so the client can
+/// resend a chunk or find out where to resume by sending an empty one. Chunks of the same
+/// upload must be saved one at a time, which [`upload`] does.
+///
+/// Once the status [`is_complete`](UploadStatus::is_complete), the whole file is at
+/// [`partial_path`], and can be moved wherever it should be stored.
+///
+/// The file is written with [`tokio::fs`], so this must be called from within a Tokio runtime.
+#[cfg(all(feature = "ssr", not(target_arch = "wasm32")))]
+pub async fn save_chunk(
+    chunk: &UploadChunk,
+    dir: impl AsRef<std::path::Path>,
+) -> Result<UploadStatus, ServerFnError> {
+    use std::io::SeekFrom;
+    use tokio::{
+        fs,
+        io::{AsyncSeekExt, AsyncWriteExt},
+    };
+
+    let io_err = |e: std::io::Error| ServerFnError::ServerError(e.to_string());
+    let path = partial_path(&dir, chunk.id())?;
+    let mut received = match fs::metadata(&path).await {
+        Ok(metadata) => metadata.len(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => 0,
+        Err(e) => return Err(io_err(e)),
+    };
+    if chunk.offset() == received {
+        fs::create_dir_all(&dir).await.map_err(io_err)?;
+        // another request for the same upload may have written to the file since its length
+        // was read, so the chunk is written at its own offset rather than appended: a chunk
+        // sent twice then overwrites itself instead of being duplicated
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(false)
+            .open(&path)
+            .await
+            .map_err(io_err)?;
+        file.seek(SeekFrom::Start(chunk.offset()))
+            .await
+            .map_err(io_err)?;
+        file.write_all(chunk.data()).await.map_err(io_err)?;
+        received = chunk.offset() + chunk.data().len() as u64;
+        file.set_len(received).await.map_err(io_err)?;
+        // a Tokio file finishes writing in the background unless it is flushed
+        file.flush().await.map_err(io_err)?;
+    }
+    Ok(UploadStatus::new(received, chunk.total()))
+}
+
+#[cfg(all(test, feature = "ssr", not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir()
+            .join(format!("server_fn-upload-{}-{name}", std::process::id()));
+        _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn chunk(id: &str, offset: u64, data: &'static [u8]) -> UploadChunk {
+        UploadChunk::new(id, "file", offset, 8, Bytes::from_static(data))
+    }
+
+    #[test]
+    fn partial_path_stays_in_the_directory() {
+        assert_eq!(
+            partial_path("uploads", "abc-123_x").unwrap(),
+            PathBuf::from("uploads").join("abc-123_x.part")
+        );
+        for id in [
+            "",
+            "../secret",
+            "a/b",
+            "a\\b",
+            ".",
+            "a".repeat(129).as_str(),
+        ] {
+            assert!(partial_path("uploads", id).is_err(), "{id:?}");
+        }
+    }
+
+    #[tokio::test]
+    async fn saves_chunks_in_order() {
+        let dir = temp_dir("order");
+        let status = save_chunk(&chunk("a", 0, b"abcd"), &dir).await.unwrap();
+        assert_eq!(status, UploadStatus::new(4, 8));
+        let status = save_chunk(&chunk("a", 4, b"efgh"), &dir).await.unwrap();
+        assert!(status.is_complete());
+        let path = partial_path(&dir, "a").unwrap();
+        assert_eq!(std::fs::read(path).unwrap(), b"abcdefgh");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn ignores_resent_and_skipped_chunks() {
+        let dir = temp_dir("ignored");
+        save_chunk(&chunk("a", 0, b"abcd"), &dir).await.unwrap();
+        // the same chunk again
+        let status = save_chunk(&chunk("a", 0, b"abcd"), &dir).await.unwrap();
+        assert_eq!(status, UploadStatus::new(4, 8));
+        // a chunk after the one the file needs next
+        let status = save_chunk(&chunk("a", 6, b"gh"), &dir).await.unwrap();
+        assert_eq!(status, UploadStatus::new(4, 8));
+        // an empty chunk asks where to resume
+        let status = save_chunk(&chunk("a", 0, b""), &dir).await.unwrap();
+        assert_eq!(status, UploadStatus::new(4, 8));
+        let path = partial_path(&dir, "a").unwrap();
+        assert_eq!(std::fs::read(path).unwrap(), b"abcd");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn concurrent_copies_of_a_chunk_are_written_once() {
+        let dir = temp_dir("concurrent");
+        let first = chunk("a", 0, b"abcd");
+        let sends = (0..8).map(|_| save_chunk(&first, &dir));
+        for status in futures::future::join_all(sends).await {
+            assert_eq!(status.unwrap(), UploadStatus::new(4, 8));
+        }
+        let path = partial_path(&dir, "a").unwrap();
+        assert_eq!(std::fs::read(path).unwrap(), b"abcd");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn rejects_ids_outside_the_directory() {
+        let dir = temp_dir("traversal");
+        let result = save_chunk(&chunk("../escape", 0, b"abcd"), &dir).await;
+        assert!(matches!(result, Err(ServerFnError::Args(_))));
+        assert!(!dir.exists());
+        assert!(!dir.parent().unwrap().join("escape.part").exists());
+    }
+}
@@ -0,0 +1,142 @@
+use crate::error::ServerFnError;
+use std::sync::OnceLock;
+
+/// The header in which the client sends the version of the build it is running, if one has
+/// been set with [`set_build_version`].
+pub const BUILD_VERSION_HEADER: &str = "x-server-fn-build";
+
+static BUILD_VERSION: OnceLock<&'static str> = OnceLock::new();
+
+/// Sets the version of this build of the app, which should be the same on the server and in
+/// the client that it serves, for example `env!("CARGO_PKG_VERSION")` or a commit hash.
+///
+/// Once this is set, the client sends its version with each call. If a call is made to a server
+/// function that the server doesn't have (because the client was loaded from an older build,
+/// and the function has since been changed or removed), and the versions differ, the server
+/// responds with [`ServerFnError::ClientOutdated`] instead of a generic error, so that the app
+/// can ask the user to reload (see [`set_client_outdated_hook`]).
+///
+/// Server functions that older clients may still call can be kept around with an explicit
+/// version, like `#[server(version = 1)]`, which gives them a URL that does not change between
+/// builds. When such a function changes in a way that is still compatible with its previous
+/// version, `#[server(version = 2, alias_version = 1)]` also serves it at the previous version's
+/// URL. A call to a version that is no longer served fails with
+/// [`ServerFnError::ClientOutdated`] whether or not the build version is set (see
+/// [`unserved_version`]).
+pub fn set_build_version(version: &'static str) {
+    BUILD_VERSION.set(version).unwrap();
+}
+
+/// Returns the version of this build, if it has been set.
+pub fn get_build_version() -> Option<&'static str> {
+    BUILD_VERSION.get().copied()
+}
+
+/// Returns [`ServerFnError::ClientOutdated`] if a client that sent the given build version is
+/// running a different build from the server.
+///
+/// This is used by the server integrations when a request does not match any server function.
+pub fn client_outdated(client_version: Option<&str>) -> Option<ServerFnError> {
+    let server_version = get_build_version()?;
+    let client_version = client_version?;
+    (client_version != server_version).then(|| {
+        ServerFnError::ClientOutdated(format!(
+            "the client is running build {client_version}, but the server is \
+             running build {server_version}"
+        ))
+    })
+}
+
+/// Returns [`ServerFnError::ClientOutdated`] if `path` is the path of a version of a server
+/// function that the server no longer serves, because it only serves other versions of it at
+/// `paths`.
+///
+/// The paths of two versions of a server function differ only in their `/v{version}` segment.
+/// This is used by the server integrations when a request does not match any server function.
+pub fn unserved_version<'a>(
+    path: &str,
+    paths: impl IntoIterator<Item = &'a str>,
+) -> Option<ServerFnError> {
+    let served = paths
+        .into_iter()
+        .find(|served| is_other_version(path, served))?;
+    Some(ServerFnError::ClientOutdated(format!(
+        "the server no longer serves the server function at {path}, which is \
+         now at {served}"
+    )))
+}
+
+// whether the paths differ in exactly one segment, which holds a different version in each
+fn is_other_version(a: &str, b: &str) -> bool {
+    let is_version = |segment: &str| {
+        segment.strip_prefix('v').is_some_and(|n| {
+            !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit())
+        })
+    };
+    let a = a.split('/').collect::<Vec<_>>();
+    let b = b.split('/').collect::<Vec<_>>();
+    let mut differing = a.iter().zip(&b).filter(|(a, b)| a != b);
+    a.len() == b.len()
+        && matches!(
+            (differing.next(), differing.next()),
+            (Some((a, b)), None) if is_version(a) && is_version(b)
+        )
+}
+
+/// A function that will be called when a server function returns
+/// [`ServerFnError::ClientOutdated`].
+pub type ClientOutdatedHook = Box<dyn Fn() + Send + Sync>;
+
+static CLIENT_OUTDATED_HOOK: OnceLock<ClientOutdatedHook> = OnceLock::new();
+
+/// Sets a function that will be called when a server function returns
+/// [`ServerFnError::ClientOutdated`], for example to reload the page. Returns `Err(_)` if the
+/// hook has already been set.
+///
+/// The error is still returned to the caller as usual.
+pub fn set_client_outdated_hook(
+    hook: impl Fn() + Send + Sync + 'static,
+) -> Result<(), ClientOutdatedHook> {
+    CLIENT_OUTDATED_HOOK.set(Box::new(hook))
+}
+
+/// Calls the hook that has been set by [`set_client_outdated_hook`].
+pub(crate) fn call_client_outdated_hook() {
+    if let Some(hook) = CLIENT_OUTDATED_HOOK.get() {
+        hook()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn other_versions_of_a_served_function_are_outdated() {
+        let served = [
+            "/api/v3/get_user123",
+            "/api/v2/get_user123",
+            "/api/v1/other",
+        ];
+        assert!(matches!(
+            unserved_version("/api/v1/get_user123", served),
+            Some(ServerFnError::ClientOutdated(_))
+        ));
+        assert!(matches!(
+            unserved_version("/custom/v4/a/b", ["/custom/v2/a/b"]),
+            Some(ServerFnError::ClientOutdated(_))
+        ));
+    }
+
+    #[test]
+    fn unknown_functions_are_not_outdated() {
+        let served = ["/api/v3/get_user123", "/api/get_post456"];
+        // a different function
+        assert!(unserved_version("/api/v1/get_post456", served).is_none());
+        assert!(unserved_version("/api/v3/get_user789", served).is_none());
+        // a different prefix
+        assert!(unserved_version("/other/v1/get_user123", served).is_none());
+        // not a version
+        assert!(unserved_version("/api/vx/get_user123", served).is_none());
+    }
+}
@@ -220,9 +220,20 @@ pub fn server_macro_impl(
         client,
         custom_wrapper,
         impl_from,
+        protocol,
+        policy,
+        body_limits,
+        cache,
+        version,
+        alias_version,
     } = args;
     let prefix = prefix.unwrap_or_else(|| Literal::string(default_path));
     let fn_path = fn_path.unwrap_or_else(|| Literal::string(""));
+    // a protocol replaces both the input and output encodings
+    let (input, output) = match &protocol {
+        Some(protocol) => (Some(protocol.clone()), Some(protocol.clone())),
+        None => (input, output),
+    };
     let input_ident = match &input {
         Some(Type::Path(path)) => {
             path.path.segments.last().map(|seg| seg.ident.to_string())
@@ -330,6 +341,33 @@ pub fn server_macro_impl(
             }
         });
 
+    // a Protobuf request body is a single message, which is the only argument
+    let protobuf_impl = if input_ident.as_deref() == Some("Protobuf") {
+        let Some((name, ty)) = first_field.filter(|_| body.inputs.len() == 1)
+        else {
+            return Err(syn::Error::new(
+                body.ident.span(),
+                "server functions that use the `Protobuf` input encoding \
+                 must take exactly one argument, which is the message",
+            ));
+        };
+        Some(quote! {
+            impl #server_fn_path::codec::ProtobufArgs for #struct_name {
+                type Message = #ty;
+
+                fn into_message(self) -> Self::Message {
+                    self.#name
+                }
+
+                fn from_message(#name: Self::Message) -> Self {
+                    #struct_name { #name }
+                }
+            }
+        })
+    } else {
+        None
+    };
+
     // check output type
     let output_arrow = body.output_arrow;
     let return_ty = body.return_ty;
@@ -390,6 +428,59 @@ pub fn server_macro_impl(
                     },
                     #wrapped_struct_name_turbofish::middlewares
                 )
+                .with_body_limits(#wrapped_struct_name_turbofish::body_limits)
+            }}
+
+            #server_fn_path::inventory::submit! {{
+                use #server_fn_path::{
+                    ServerFn,
+                    error::{ErrorStatusRegistration, ViaErrorStatus, WrapErrorStatus}
+                };
+                ErrorStatusRegistration(|| {
+                    (&&WrapErrorStatus::<<#wrapped_struct_name as ServerFn>::Error>::new())
+                        .error_status()
+                })
+            }}
+        }
+    } else {
+        quote! {}
+    };
+
+    // describe the function for the OpenAPI document
+    let openapi = if cfg!(feature = "ssr") && cfg!(feature = "openapi") {
+        let arg_names = field_names
+            .iter()
+            .map(|name| name.to_token_stream().to_string());
+        let arg_tys = fn_args.iter().map(|arg| &arg.ty);
+        let doc_str = body
+            .docs
+            .iter()
+            .map(|(doc, _)| doc.trim())
+            .collect::<Vec<_>>()
+            .join("\n");
+        quote! {
+            #server_fn_path::inventory::submit! {{
+                use #server_fn_path::{
+                    ServerFn, codec::Encoding,
+                    openapi::{error_responses, ServerFnDoc, ViaRequired, ViaSchema, WrapSchema}
+                };
+                ServerFnDoc::new(
+                    #wrapped_struct_name_turbofish::PATH,
+                    <#wrapped_struct_name as ServerFn>::InputEncoding::METHOD,
+                    <<#wrapped_struct_name as ServerFn>::InputEncoding as Encoding>::CONTENT_TYPE,
+                    <<#wrapped_struct_name as ServerFn>::OutputEncoding as Encoding>::CONTENT_TYPE,
+                    #fn_name_as_str,
+                    #doc_str,
+                    |gen| vec![
+                        #((
+                            #arg_names,
+                            (&&WrapSchema::<#arg_tys>::new()).required(),
+                            (&&WrapSchema::<#arg_tys>::new()).schema(gen)
+                        )),*
+                    ],
+                    |gen| (&&WrapSchema::<#output_ty>::new()).schema(gen),
+                    error_responses::<#wrapped_struct_name>
+                )
             }}
         }
     } else {
@@ -495,7 +586,11 @@ pub fn server_macro_impl(
         ),
         Some("MultipartFormData")
         | Some("Streaming")
-        | Some("StreamingText") => (PathInfo::None, quote! {}),
+        | Some("StreamingText")
+        | Some("Websocket")
+        | Some("Protobuf")
+        | Some("StreamingProtobuf") => (PathInfo::None, quote! {}),
+        Some("Chunked") => (PathInfo::None, quote! { Clone }),
         Some("SerdeLite") => (
             PathInfo::Serde,
             quote! {
@@ -609,23 +704,76 @@ pub fn server_macro_impl(
     } else {
         quote! { concat!("/", #fn_path) }
     };
-    let path = quote! {
-        if #fn_path.is_empty() {
-            #server_fn_path::const_format::concatcp!(
-                #prefix,
-                "/",
-                #fn_name_as_str,
-                #server_fn_path::xxhash_rust::const_xxh64::xxh64(
-                    concat!(env!(#key_env_var), ":", file!(), ":", line!(), ":", column!()).as_bytes(),
-                    0
+    // a versioned path only depends on the module, name and version of the function, so it
+    // stays the same across builds, and only differs from the paths of its other versions in the
+    // `/v{version}` segment
+    let versioned_path = |version: &LitInt| {
+        // quoted as a string, as `concatcp!` can't infer the type of an integer literal
+        let version = version.base10_digits();
+        quote! {
+            if #fn_path.is_empty() {
+                #server_fn_path::const_format::concatcp!(
+                    #prefix,
+                    "/v",
+                    #version,
+                    "/",
+                    #fn_name_as_str,
+                    #server_fn_path::xxhash_rust::const_xxh64::xxh64(
+                        concat!(module_path!(), "::", #fn_name_as_str).as_bytes(),
+                        0
+                    )
                 )
-            )
-        } else {
-            #server_fn_path::const_format::concatcp!(
-                #prefix,
-                #fn_path
-            )
+            } else {
+                #server_fn_path::const_format::concatcp!(
+                    #prefix,
+                    "/v",
+                    #version,
+                    #fn_path
+                )
+            }
+        }
+    };
+    // a server function with an alias version also handles calls made to that version's path
+    let alias_inventory = match &alias_version {
+        Some(alias_version) if cfg!(feature = "ssr") => {
+            let alias_path = versioned_path(alias_version);
+            quote! {
+                #server_fn_path::inventory::submit! {{
+                    use #server_fn_path::{ServerFn, codec::Encoding};
+                    #server_fn_path::ServerFnTraitObj::new(
+                        #alias_path,
+                        <#wrapped_struct_name as ServerFn>::InputEncoding::METHOD,
+                        |req| {
+                            Box::pin(#wrapped_struct_name_turbofish::run_on_server(req))
+                        },
+                        #wrapped_struct_name_turbofish::middlewares
+                    )
+                    .with_body_limits(#wrapped_struct_name_turbofish::body_limits)
+                }}
+            }
         }
+        _ => quote! {},
+    };
+    let path = match &version {
+        Some(version) => versioned_path(version),
+        None => quote! {
+            if #fn_path.is_empty() {
+                #server_fn_path::const_format::concatcp!(
+                    #prefix,
+                    "/",
+                    #fn_name_as_str,
+                    #server_fn_path::xxhash_rust::const_xxh64::xxh64(
+                        concat!(env!(#key_env_var), ":", file!(), ":", line!(), ":", column!()).as_bytes(),
+                        0
+                    )
+                )
+            } else {
+                #server_fn_path::const_format::concatcp!(
+                    #prefix,
+                    #fn_path
+                )
+            }
+        },
     };
 
     // only emit the dummy (unmodified server-only body) for the server build
@@ -642,6 +790,71 @@ pub fn server_macro_impl(
         quote! { vec![] }
     };
 
+    // a protocol takes over both the client and server sides of the call
+    let protocol_methods = protocol.map(|protocol| {
+        quote! {
+            fn run_on_server(
+                req: Self::ServerRequest,
+            ) -> impl std::future::Future<Output = Self::ServerResponse> + Send {
+                <#protocol>::run_server::<Self>(req)
+            }
+
+            fn run_on_client(
+                self,
+            ) -> impl std::future::Future<
+                Output = Result<Self::Output, #server_fn_path::ServerFnError<Self::Error>>
+            > + Send {
+                <#protocol>::run_client::<Self>(self)
+            }
+        }
+    });
+
+    let policy_method = policy.map(|policy| {
+        quote! {
+            fn call_policy() -> #server_fn_path::client::CallPolicy {
+                #policy
+            }
+        }
+    });
+
+    let body_limits_method = body_limits.map(|body_limits| {
+        quote! {
+            fn body_limits() -> #server_fn_path::request::BodyLimits {
+                #body_limits
+            }
+        }
+    });
+
+    // a cached server function also remembers its last output on the client, to reuse
+    // when the server responds with 304 Not Modified
+    let cache_methods = cache.map(|cache| {
+        quote! {
+            fn cache_policy() -> #server_fn_path::cache::CachePolicy {
+                #cache
+            }
+
+            fn cache_output(url: &str, etag: &str, output: &Self::Output) {
+                #server_fn_path::cache::store_output(url, etag, output)
+            }
+
+            fn cached_output(url: &str) -> Option<Self::Output> {
+                #server_fn_path::cache::load_output(url)
+            }
+        }
+    });
+
+    // arguments sent as a URL-encoded form can be sent back to a `<form>` that failed
+    // validation, encoded the same way
+    let form_values_method = (custom_wrapper.is_none()
+        && matches!(input_ident.as_deref(), Some("PostUrl" | "GetUrl")))
+    .then(|| {
+        quote! {
+            fn form_values(&self) -> Option<String> {
+                #server_fn_path::codec::form_values(self)
+            }
+        }
+    });
+
     Ok(quote::quote! {
         #args_docs
         #docs
@@ -653,6 +866,8 @@ pub fn server_macro_impl(
 
         #from_impl
 
+        #protobuf_impl
+
         impl #server_fn_path::ServerFn for #wrapped_struct_name {
             const PATH: &'static str = #path;
 
@@ -669,10 +884,24 @@ pub fn server_macro_impl(
             }
 
             #run_body
+
+            #policy_method
+
+            #body_limits_method
+
+            #cache_methods
+
+            #form_values_method
+
+            #protocol_methods
         }
 
         #inventory
 
+        #alias_inventory
+
+        #openapi
+
         #func
 
         #dummy
@@ -791,6 +1020,12 @@ struct ServerFnArgs {
     custom_wrapper: Option<Path>,
     builtin_encoding: bool,
     impl_from: Option<LitBool>,
+    protocol: Option<Type>,
+    policy: Option<Expr>,
+    body_limits: Option<Expr>,
+    cache: Option<Expr>,
+    version: Option<LitInt>,
+    alias_version: Option<LitInt>,
 }
 
 impl Parse for ServerFnArgs {
@@ -810,6 +1045,12 @@ impl Parse for ServerFnArgs {
         let mut client: Option<Type> = None;
         let mut custom_wrapper: Option<Path> = None;
         let mut impl_from: Option<LitBool> = None;
+        let mut protocol: Option<Type> = None;
+        let mut policy: Option<Expr> = None;
+        let mut body_limits: Option<Expr> = None;
+        let mut cache: Option<Expr> = None;
+        let mut version: Option<LitInt> = None;
+        let mut alias_version: Option<LitInt> = None;
 
         let mut use_key_and_value = false;
         let mut arg_pos = 0;
@@ -933,6 +1174,58 @@ impl Parse for ServerFnArgs {
                             ));
                         }
                         impl_from = Some(stream.parse()?);
+                    } else if key == "protocol" {
+                        if protocol.is_some() {
+                            return Err(syn::Error::new(
+                                key.span(),
+                                "keyword argument repeated: `protocol`",
+                            ));
+                        }
+                        protocol = Some(stream.parse()?);
+                    } else if key == "policy" {
+                        if policy.is_some() {
+                            return Err(syn::Error::new(
+                                key.span(),
+                                "keyword argument repeated: `policy`",
+                            ));
+                        }
+                        policy = Some(stream.parse()?);
+                    } else if key == "body_limits" {
+                        if body_limits.is_some() {
+                            return Err(syn::Error::new(
+                                key.span(),
+                                "keyword argument repeated: `body_limits`",
+                            ));
+                        }
+                        body_limits = Some(stream.parse()?);
+                    } else if key == "cache" {
+                        if cache.is_some() {
+                            return Err(syn::Error::new(
+                                key.span(),
+                                "keyword argument repeated: `cache`",
+                            ));
+                        }
+                        cache = Some(stream.parse()?);
+                    } else if key == "version" {
+                        if version.is_some() {
+                            return Err(syn::Error::new(
+                                key.span(),
+                                "keyword argument repeated: `version`",
+                            ));
+                        }
+                        let lit: LitInt = stream.parse()?;
+                        lit.base10_parse::<u32>()?;
+                        version = Some(lit);
+                    } else if key == "alias_version" {
+                        if alias_version.is_some() {
+                            return Err(syn::Error::new(
+                                key.span(),
+                                "keyword argument repeated: `alias_version`",
+                            ));
+                        }
+                        let lit: LitInt = stream.parse()?;
+                        lit.base10_parse::<u32>()?;
+                        alias_version = Some(lit);
                     } else {
                         return Err(lookahead.error());
                     }
@@ -984,6 +1277,16 @@ impl Parse for ServerFnArgs {
             }
         }
 
+        if let Some(protocol) = &protocol {
+            if encoding.is_some() || input.is_some() || output.is_some() {
+                return Err(syn::Error::new(
+                    protocol.span(),
+                    "`protocol` should not be specified along with \
+                     `encoding`, `input`, or `output`",
+                ));
+            }
+        }
+
         // parse legacy encoding into input/output
         let mut builtin_encoding = false;
         if let Some(encoding) = encoding {
@@ -1017,6 +1320,27 @@ impl Parse for ServerFnArgs {
             }
         }
 
+        if let Some(alias_version) = &alias_version {
+            match &version {
+                None => {
+                    return Err(syn::Error::new(
+                        alias_version.span(),
+                        "`alias_version` can only be used with `version`",
+                    ))
+                }
+                Some(version)
+                    if version.base10_parse::<u32>()?
+                        == alias_version.base10_parse::<u32>()? =>
+                {
+                    return Err(syn::Error::new(
+                        alias_version.span(),
+                        "`alias_version` must differ from `version`",
+                    ))
+                }
+                _ => {}
+            }
+        }
+
         Ok(Self {
             struct_name,
             prefix,
@@ -1030,6 +1354,12 @@ impl Parse for ServerFnArgs {
             client,
             custom_wrapper,
             impl_from,
+            protocol,
+            policy,
+            body_limits,
+            cache,
+            version,
+            alias_version,
         })
     }
 }